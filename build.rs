@@ -0,0 +1,252 @@
+//! Generate the protocol table and the `raw` bindings from
+//! `protocol/termux-gui.json`
+
+use std::env;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+
+use serde_json::Value;
+
+const DESCRIPTION: &str = "protocol/termux-gui.json";
+
+struct Param {
+    name: String,
+    ty: String,
+    required: bool,
+}
+
+struct Method {
+    name: String,
+    func: String,
+    doc: String,
+    params: Vec<Param>,
+    reply: String,
+    returns: Option<String>,
+}
+
+fn main() {
+    println!("cargo:rerun-if-changed={}", DESCRIPTION);
+    println!("cargo:rerun-if-changed=build.rs");
+
+    let text = fs::read_to_string(DESCRIPTION)
+        .unwrap_or_else(|e| panic!("failed to read {}: {}", DESCRIPTION, e));
+    let description: Value = serde_json::from_str(&text)
+        .unwrap_or_else(|e| panic!("{} is not valid JSON: {}", DESCRIPTION, e));
+    let methods: Vec<Method> = description["methods"]
+        .as_array()
+        .unwrap_or_else(|| panic!("{}: missing \"methods\" array", DESCRIPTION))
+        .iter()
+        .map(parse_method)
+        .collect();
+
+    for (i, method) in methods.iter().enumerate() {
+        if methods[..i].iter().any(|m| m.name == method.name || m.func == method.func) {
+            panic!("{}: {} is listed twice", DESCRIPTION, method.name);
+        }
+    }
+
+    let out = env::var("OUT_DIR").expect("OUT_DIR not set");
+    write(Path::new(&out).join("methods.rs"), &methods_table(&methods));
+    write(Path::new(&out).join("raw.rs"), &raw_bindings(&methods));
+}
+
+fn parse_method(value: &Value) -> Method {
+    let field = |key: &str| value[key].as_str().map(String::from);
+    let method = field("name").unwrap_or_else(|| panic!("{}: method without a name", DESCRIPTION));
+
+    let params = value["params"]
+        .as_array()
+        .unwrap_or_else(|| fail(&method, "missing \"params\" array".to_string()))
+        .iter()
+        .map(|param| {
+            let spec = param.as_str().unwrap_or_else(|| fail(&method, format!("parameter {} is not a string", param)));
+            let (name, ty) = spec
+                .split_once(':')
+                .unwrap_or_else(|| fail(&method, format!("parameter {:?} is not \"name: type\"", spec)));
+            let (name, required) = match name.trim().strip_suffix('?') {
+                Some(name) => (name, false),
+                None => (name.trim(), true),
+            };
+            let ty = ty.trim();
+            if param_type(ty).is_none() {
+                fail(&method, format!("unknown parameter type {:?}", ty));
+            }
+            Param { name: name.to_string(), ty: ty.to_string(), required }
+        })
+        .collect();
+
+    let reply = field("reply").unwrap_or_else(|| fail(&method, "missing \"reply\"".to_string()));
+    if reply_type(&reply).is_none() {
+        fail(&method, format!("unknown reply {:?}", reply));
+    }
+
+    Method {
+        func: field("fn").unwrap_or_else(|| snake_case(&method)),
+        doc: field("doc").unwrap_or_else(|| fail(&method, "missing \"doc\"".to_string())),
+        returns: field("returns"),
+        params,
+        reply,
+        name: method,
+    }
+}
+
+fn fail(method: &str, what: String) -> ! {
+    panic!("{}: {}: {}", DESCRIPTION, method, what)
+}
+
+/// `ParamType` variant and Rust type of a parameter type
+fn param_type(ty: &str) -> Option<(&'static str, &'static str)> {
+    Some(match ty {
+        "int" => ("Int", "i64"),
+        "bool" => ("Bool", "bool"),
+        "str" => ("Str", "String"),
+        "str_list" => ("StrList", "Vec<String>"),
+        "size" => ("Size", "Size"),
+        _ => return None,
+    })
+}
+
+/// `Reply` variant and Rust type of a reply
+fn reply_type(reply: &str) -> Option<(&'static str, &'static str)> {
+    Some(match reply {
+        "none" => ("None", "()"),
+        "id" => ("Id", "i64"),
+        "int" => ("Int", "i64"),
+        "activity" => ("Activity", "ActivityIds"),
+        "bool" => ("Bool", "bool"),
+        "str" => ("Str", "String"),
+        "int_pair" => ("IntPair", "(i64, i64)"),
+        "object" => ("Object", "Value"),
+        _ => return None,
+    })
+}
+
+fn methods_table(methods: &[Method]) -> String {
+    let mut out = String::new();
+    out.push_str("/// Every method of the Termux:GUI protocol\n");
+    out.push_str("pub static METHODS: &[Method] = &[\n");
+    for method in methods {
+        let _ = writeln!(out, "    Method {{");
+        let _ = writeln!(out, "        name: {:?},", method.name);
+        let _ = writeln!(out, "        params: &[");
+        for param in &method.params {
+            let _ = writeln!(
+                out,
+                "            Param {{ name: {:?}, ty: ParamType::{}, required: {} }},",
+                param.name,
+                param_type(&param.ty).unwrap().0,
+                param.required
+            );
+        }
+        let _ = writeln!(out, "        ],");
+        let _ = writeln!(out, "        reply: Reply::{},", reply_type(&method.reply).unwrap().0);
+        let _ = writeln!(out, "    }},");
+    }
+    out.push_str("];\n");
+    out
+}
+
+fn raw_bindings(methods: &[Method]) -> String {
+    let mut out = String::new();
+    for method in methods {
+        let ty = pascal_case(&method.func);
+        let reply = method
+            .returns
+            .as_deref()
+            .unwrap_or_else(|| reply_type(&method.reply).unwrap().1);
+        let answer = if method.reply == "none" {
+            "the service doesn't reply".to_string()
+        } else {
+            format!("replies with `{}`", reply)
+        };
+
+        let _ = writeln!(out, "/// Parameters of `{}`", method.name);
+        let _ = writeln!(out, "#[derive(Debug, Clone, Default, PartialEq, Serialize)]");
+        if method.params.is_empty() {
+            let _ = writeln!(out, "pub struct {} {{}}\n", ty);
+        } else {
+            let _ = writeln!(out, "pub struct {} {{", ty);
+            for param in &method.params {
+                let field = field_name(&param.name);
+                let mut serde = Vec::new();
+                if field.trim_start_matches("r#") != param.name {
+                    serde.push(format!("rename = {:?}", param.name));
+                }
+                if !param.required {
+                    serde.push("skip_serializing_if = \"Option::is_none\"".to_string());
+                }
+                if !serde.is_empty() {
+                    let _ = writeln!(out, "    #[serde({})]", serde.join(", "));
+                }
+                let rust_ty = param_type(&param.ty).unwrap().1;
+                if param.required {
+                    let _ = writeln!(out, "    pub {}: {},", field, rust_ty);
+                } else {
+                    let _ = writeln!(out, "    pub {}: Option<{}>,", field, rust_ty);
+                }
+            }
+            let _ = writeln!(out, "}}\n");
+        }
+
+        let _ = writeln!(out, "impl Request for {} {{", ty);
+        let _ = writeln!(out, "    const METHOD: &'static str = {:?};", method.name);
+        let _ = writeln!(out, "    type Reply = {};", reply);
+        let _ = writeln!(out, "}}\n");
+
+        let _ = writeln!(out, "/// {}", method.doc);
+        let _ = writeln!(out, "///");
+        let _ = writeln!(out, "/// Sends `{}`; {}.", method.name, answer);
+        let _ = writeln!(
+            out,
+            "pub fn {}<C: Client + ?Sized>(client: &mut C, params: &{}) -> Result<{}> {{",
+            method.func, ty, reply
+        );
+        let _ = writeln!(out, "    call(client, params)");
+        let _ = writeln!(out, "}}\n");
+    }
+    out
+}
+
+/// `statusBarColor` -> `status_bar_color`, `loadURI` -> `load_uri`
+fn snake_case(name: &str) -> String {
+    let chars: Vec<char> = name.chars().collect();
+    let mut out = String::new();
+    for (i, &c) in chars.iter().enumerate() {
+        if c.is_ascii_uppercase() && i > 0 {
+            let prev_lower = chars[i - 1].is_ascii_lowercase();
+            let next_lower = chars.get(i + 1).is_some_and(|c| c.is_ascii_lowercase());
+            if prev_lower || (chars[i - 1].is_ascii_uppercase() && next_lower) {
+                out.push('_');
+            }
+        }
+        out.push(c.to_ascii_lowercase());
+    }
+    out
+}
+
+/// `load_uri` -> `LoadUri`
+fn pascal_case(name: &str) -> String {
+    name.split('_')
+        .map(|word| {
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map(|first| first.to_ascii_uppercase().to_string() + chars.as_str())
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
+fn field_name(param: &str) -> String {
+    let name = snake_case(param);
+    match name.as_str() {
+        "type" | "move" | "ref" | "loop" | "match" | "mod" | "use" | "where" => format!("r#{}", name),
+        _ => name,
+    }
+}
+
+fn write(path: impl AsRef<Path>, contents: &str) {
+    let path = path.as_ref();
+    fs::write(path, contents).unwrap_or_else(|e| panic!("failed to write {}: {}", path.display(), e));
+}
@@ -0,0 +1,53 @@
+// 批量请求演示 - 一次性发送大量 create/set 调用，不逐个等待响应
+// 对比逐个创建和批量创建 60 个控件所需的时间
+// 运行: cargo run --example batch_demo --release
+
+use std::time::Instant;
+use termux_gui::{Activity, Color, Event, Result};
+
+const ROWS: usize = 30;
+
+fn main() -> Result<()> {
+    let mut activity = Activity::new(false)?;
+    let scroll = activity.create_nested_scroll_view(None)?;
+    let root = activity.create_linear_layout(Some(scroll.id()))?;
+
+    // 逐个创建：每个 create 都要等待一次往返
+    let start = Instant::now();
+    let slow = activity.create_linear_layout(Some(root.id()))?;
+    for i in 0..ROWS {
+        let row = activity.create_linear_layout_horizontal(Some(slow.id()))?;
+        let label = activity.create_text_view(&format!("逐个 #{}", i), Some(row.id()))?;
+        label.view().set_linear_layout_params(&mut activity, 1, None)?;
+        activity.create_checkbox("", Some(row.id()))?;
+    }
+    let sequential = start.elapsed();
+
+    // 批量创建：只在需要父布局 ID 时等待，每一层一次往返
+    let start = Instant::now();
+    let mut batch = activity.batch();
+    let fast = batch.create_linear_layout(&root);
+    batch.set_background_color(fast, Color::rgb(0xE3, 0xF2, 0xFD));
+    let mut labels = Vec::new();
+    for i in 0..ROWS {
+        let row = batch.create_linear_layout_horizontal(fast);
+        let label = batch.create_text_view(&format!("批量 #{}", i), row);
+        batch.set_linear_layout_params(label, 1);
+        batch.create_checkbox("", row);
+        labels.push(label);
+    }
+    let created = batch.run()?;
+    let batched = start.elapsed();
+
+    // 批量创建的控件之后可以像普通控件一样使用
+    let first = created.get(labels[0])?;
+    first.set_text(&mut activity, &format!("逐个: {:?} / 批量: {:?}", sequential, batched))?;
+    println!("逐个创建: {:?}", sequential);
+    println!("批量创建: {:?}", batched);
+
+    loop {
+        if let Event::Destroy { .. } = activity.next_event()? {
+            return Ok(());
+        }
+    }
+}
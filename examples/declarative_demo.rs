@@ -0,0 +1,94 @@
+// 声明式 UI 演示 - 用节点树描述界面，状态变化时只发送差异
+// 展示 Node/Ui 的用法：添加、勾选、删除待办事项
+// 运行: cargo run --example declarative_demo --release
+
+use termux_gui::declarative::{Node, Ui};
+use termux_gui::{Activity, Color, Event, Result};
+
+/// 应用状态
+struct Todo {
+    title: String,
+    done: bool,
+}
+
+/// 根据状态构建整个界面
+fn view(todos: &[Todo]) -> Node {
+    let remaining = todos.iter().filter(|t| !t.done).count();
+    
+    Node::linear_layout()
+        .child(
+            Node::text_view("待办事项 🦀")
+                .text_size(26)
+                .margin(10),
+        )
+        .child(
+            Node::text_view(format!("剩余: {} / {}", remaining, todos.len()))
+                .text_color(if remaining == 0 { Color::rgb(0x4C, 0xAF, 0x50) } else { Color::GRAY })
+                .margin(10),
+        )
+        .child(
+            Node::row()
+                .child(Node::edit_text("").key("input").weight(1))
+                .child(Node::button("➕ 添加").key("add")),
+        )
+        // 每个待办项用标题作为 key，删除或勾选时其它项保持不变
+        .children(todos.iter().map(|todo| {
+            Node::checkbox(todo.title.as_str())
+                .key(format!("todo:{}", todo.title))
+                .checked(todo.done)
+        }))
+        .child(Node::button("🧹 清除已完成").key("clear"))
+}
+
+fn main() -> Result<()> {
+    println!("=== 声明式 UI 演示 ===\n");
+    
+    let mut activity = Activity::new(false)?;
+    println!("✓ 连接建立\n");
+    
+    let mut todos = vec![
+        Todo { title: "学习 Rust".to_string(), done: true },
+        Todo { title: "写一个 Termux 应用".to_string(), done: false },
+    ];
+    
+    let mut ui = Ui::mount(&mut activity, view(&todos), None)?;
+    println!("✓ 界面创建完成\n");
+    
+    // 输入框的内容通过 text 事件跟踪
+    let mut input = String::new();
+    
+    loop {
+        let clicked = match activity.next_event()? {
+            Event::Click { id, .. } => id,
+            Event::Text { id, text, .. } if Some(id) == ui.id("input") => {
+                input = text;
+                continue;
+            }
+            Event::Destroy { .. } => break,
+            _ => continue,
+        };
+        
+        if Some(clicked) == ui.id("add") {
+            let title = input.trim().to_string();
+            if !title.is_empty() && !todos.iter().any(|t| t.title == title) {
+                println!("添加: {}", title);
+                todos.push(Todo { title, done: false });
+            }
+        } else if Some(clicked) == ui.id("clear") {
+            todos.retain(|t| !t.done);
+        } else if let Some(todo) = todos
+            .iter_mut()
+            .find(|t| ui.id(&format!("todo:{}", t.title)) == Some(clicked))
+        {
+            todo.done = !todo.done;
+        } else {
+            continue;
+        }
+        
+        // 只发送变化的部分
+        ui.update(&mut activity, view(&todos))?;
+    }
+    
+    println!("✓ 程序结束");
+    Ok(())
+}
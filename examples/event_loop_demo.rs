@@ -0,0 +1,78 @@
+// EventLoop 演示 - 使用回调处理事件
+// 展示如何按控件注册点击/勾选/选择回调，而不是手写 match 循环
+// 运行: cargo run --example event_loop_demo --release
+
+use std::cell::Cell;
+
+use termux_gui::{Activity, Color, EventLoop, Result};
+
+fn main() -> Result<()> {
+    println!("=== EventLoop 回调演示 ===\n");
+    
+    let mut activity = Activity::new(true)?;
+    println!("✓ 连接建立\n");
+    
+    let layout = activity.create_linear_layout(None)?;
+    
+    let title = activity.create_text_view("EventLoop 演示 🦀", Some(layout.id()))?;
+    title.set_text_size(&mut activity, 26)?;
+    title.view().set_margin(&mut activity, 10)?;
+    
+    let counter = activity.create_text_view("点击次数: 0", Some(layout.id()))?;
+    counter.set_text_size(&mut activity, 20)?;
+    counter.view().set_margin(&mut activity, 10)?;
+    
+    let inc_button = activity.create_button("➕ 增加", Some(layout.id()))?;
+    let reset_button = activity.create_button("🔄 重置", Some(layout.id()))?;
+    
+    let checkbox = activity.create_checkbox("显示调试信息", Some(layout.id()))?;
+    
+    let spinner = activity.create_spinner(Some(layout.id()))?;
+    spinner.set_list(&mut activity, &["红色", "绿色", "蓝色"])?;
+    
+    let quit_button = activity.create_button("❌ 退出", Some(layout.id()))?;
+    
+    // 事件处理 - 多个回调共享的状态使用 Cell
+    let count = Cell::new(0);
+    let verbose = Cell::new(false);
+    
+    let mut events = EventLoop::new();
+    events.on_click(&inc_button, |ctx| {
+        count.set(count.get() + 1);
+        counter.set_text(ctx.activity, &format!("点击次数: {}", count.get()))
+    });
+    events.on_click(&reset_button, |ctx| {
+        count.set(0);
+        counter.set_text(ctx.activity, "点击次数: 0")
+    });
+    events.on_checked(&checkbox, |_ctx, checked| {
+        verbose.set(checked);
+        println!("调试信息: {}", if checked { "开" } else { "关" });
+        Ok(())
+    });
+    events.on_item_selected(&spinner, |ctx, item| {
+        let color = match item.as_text() {
+            Some("红色") => Color::rgb(0xF4, 0x43, 0x36),
+            Some("绿色") => Color::rgb(0x4C, 0xAF, 0x50),
+            _ => Color::rgb(0x21, 0x96, 0xF3),
+        };
+        counter.set_text_color(ctx.activity, color)
+    });
+    events.on_click(&quit_button, |ctx| {
+        ctx.activity.finish()?;
+        ctx.quit();
+        Ok(())
+    });
+    events.on_event(|ctx| {
+        if verbose.get() {
+            println!("事件: {:?}", ctx.event);
+        }
+        Ok(())
+    });
+    
+    println!("✓ 界面创建完成，开始事件循环\n");
+    events.run(&mut activity)?;
+    
+    println!("✓ 程序结束");
+    Ok(())
+}
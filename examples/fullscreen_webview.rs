@@ -6,7 +6,7 @@
 //! - JavaScript 支持
 //! - 导航功能
 
-use termux_gui::{Activity, WebView, LinearLayout, Result};
+use termux_gui::{Activity, WebView, Result};
 use termux_gui::connection::read_message;
 use std::io::{self, Write};
 use std::time::Duration;
@@ -424,7 +424,6 @@ fn create_demo_html() -> String {
 fn interactive_mode(activity: &mut Activity, webview: &WebView) -> Result<()> {
     use std::sync::mpsc;
     use std::thread;
-    use std::os::unix::net::UnixStream;
     
     // 创建通道用于接收用户输入
     let (tx, rx) = mpsc::channel();
@@ -0,0 +1,54 @@
+// 布局文件演示 - 从 JSON 文件创建界面
+// 修改 examples/layouts/calculator.json 后重新运行即可，无需重新编译
+// 运行: cargo run --example layout_file_demo --release [布局文件]
+
+use termux_gui::layout_file::LoadedLayout;
+use termux_gui::{Activity, EditText, Event, Result};
+
+fn main() -> Result<()> {
+    println!("=== 布局文件演示 ===\n");
+    
+    let path = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| "examples/layouts/calculator.json".to_string());
+    
+    let mut activity = Activity::new(false)?;
+    println!("✓ 连接建立\n");
+    
+    let layout = LoadedLayout::load(&mut activity, &path, None)?;
+    println!("✓ 已加载 {}，命名控件: {:?}\n", path, layout.names().collect::<Vec<_>>());
+    
+    // 按名称和类型取出控件
+    let display: &EditText = layout.get("display").expect("布局中需要 display");
+    let mut value = String::new();
+    
+    loop {
+        let id = match activity.next_event()? {
+            Event::Click { id, .. } => id,
+            Event::Destroy { .. } => break,
+            _ => continue,
+        };
+        
+        let digit = [("one", '1'), ("two", '2'), ("three", '3')]
+            .iter()
+            .find(|(name, _)| layout.id(name) == Some(id))
+            .map(|(_, digit)| *digit);
+        
+        if let Some(digit) = digit {
+            value.push(digit);
+        } else if layout.id("clear") == Some(id) {
+            value.clear();
+        } else if layout.id("quit") == Some(id) {
+            activity.finish()?;
+            break;
+        } else {
+            continue;
+        }
+        
+        let text = if value.is_empty() { "0" } else { value.as_str() };
+        display.set_text(&mut activity, text)?;
+    }
+    
+    println!("✓ 程序结束");
+    Ok(())
+}
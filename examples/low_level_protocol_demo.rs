@@ -126,7 +126,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     
     // 3. 通过Android Broadcast通知Termux:GUI插件
     let output = Command::new("termux-am")
-        .args(&[
+        .args([
             "broadcast",
             "-n", "com.termux.gui/.GUIReceiver",
             "--es", "mainSocket", &addr_main,
@@ -139,7 +139,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             if !out.status.success() {
                 eprintln!("警告: termux-am 可能失败，尝试使用 am 命令...");
                 Command::new("am")
-                    .args(&[
+                    .args([
                         "broadcast",
                         "-n", "com.termux.gui/.GUIReceiver",
                         "--es", "mainSocket", &addr_main,
@@ -151,7 +151,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         Err(_) => {
             println!("termux-am 不可用，尝试 am 命令...");
             Command::new("am")
-                .args(&[
+                .args([
                     "broadcast",
                     "-n", "com.termux.gui/.GUIReceiver",
                     "--es", "mainSocket", &addr_main,
@@ -117,7 +117,6 @@ fn main() -> Result<()> {
     
     // 进度状态
     let mut progress1_value = 0;
-    let mut progress2_value = 0;
     
     // 事件循环
     loop {
@@ -151,7 +150,6 @@ fn main() -> Result<()> {
                 } else if clicked_id == reset_btn.id() {
                     // 重置
                     progress1_value = 0;
-                    progress2_value = 0;
                     progress1.set_progress(&mut activity, 0)?;
                     progress2.set_progress(&mut activity, 0)?;
                     progress1_text.set_text(&mut activity, "0%")?;
@@ -163,7 +161,6 @@ fn main() -> Result<()> {
                 } else if clicked_id == complete_btn.id() {
                     // 完成
                     progress1_value = 100;
-                    progress2_value = 100;
                     progress1.set_progress(&mut activity, 100)?;
                     progress2.set_progress(&mut activity, 100)?;
                     progress1_text.set_text(&mut activity, "100% ✓")?;
@@ -176,12 +173,11 @@ fn main() -> Result<()> {
                     // 自动演示 - 模拟加载过程
                     println!("▶️ 开始自动演示...");
                     progress1_value = 0;
-                    progress2_value = 0;
                     
                     // 模拟两个进度条同时加载
                     for i in 0..=10 {
                         progress1_value = i * 10;
-                        progress2_value = i * 10;
+                        let progress2_value = i * 10;
                         
                         progress1.set_progress(&mut activity, progress1_value)?;
                         progress2.set_progress(&mut activity, progress2_value)?;
@@ -0,0 +1,48 @@
+// 底层类型化接口演示 - 使用 termux_gui::raw 调用尚无高层封装的协议方法
+// 展示文字对齐、光标、Toast 和通知，无需手写 JSON
+// 运行: cargo run --example raw_api_demo --release
+
+use termux_gui::raw::{
+    self, CreateNotification, CreateNotificationChannel, SetGravity, ShowCursor, Toast,
+};
+use termux_gui::{Activity, Event, Result};
+
+fn main() -> Result<()> {
+    let mut activity = Activity::new(true)?;
+    let aid = activity.id();
+
+    let layout = activity.create_linear_layout(None)?;
+    let title = activity.create_text_view("居中的标题", Some(layout.id()))?;
+    let input = activity.create_edit_text("没有光标的输入框", Some(layout.id()))?;
+    let notify = activity.create_button("发送通知", Some(layout.id()))?;
+
+    // 高层 API 没有的方法: 直接使用生成的类型化函数
+    raw::set_gravity(&mut activity, &SetGravity { aid, id: title.id(), horizontal: 0, vertical: 0 })?;
+    raw::show_cursor(&mut activity, &ShowCursor { aid, id: input.id(), show: false })?;
+
+    // 通知渠道只需创建一次
+    raw::create_notification_channel(&mut activity, &CreateNotificationChannel {
+        id: "raw_demo".to_string(),
+        importance: 3,
+        name: "Raw API Demo".to_string(),
+    })?;
+    let version = raw::get_version(&mut activity, &raw::GetVersion {})?;
+    println!("Termux:GUI 版本: {}", version);
+
+    loop {
+        match activity.next_event()? {
+            Event::Click { id, .. } if id == notify.id() => {
+                let nid = raw::create_notification(&mut activity, &CreateNotification {
+                    channel: "raw_demo".to_string(),
+                    importance: 3,
+                    title: Some("来自 Rust 的通知".to_string()),
+                    content: Some("通过 raw::create_notification 发送".to_string()),
+                    ..Default::default()
+                })?;
+                raw::toast(&mut activity, &Toast { text: format!("通知 ID: {}", nid), long: None })?;
+            }
+            Event::Destroy { .. } => return Ok(()),
+            _ => {}
+        }
+    }
+}
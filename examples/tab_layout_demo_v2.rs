@@ -135,7 +135,7 @@ fn main() -> Result<()> {
                 if view_id == tabs.id() {
                     if let Some(new_tab) = event_value["selected"].as_i64() {
                         let new_tab = new_tab as i32;
-                        if new_tab >= 0 && new_tab < 3 {
+                        if (0..3).contains(&new_tab) {
                             let scroll_x = page_width * new_tab;
                             println!("Switch to tab {}: {} (scroll to {}px)", 
                                     new_tab, ["Home", "Messages", "Profile"][new_tab as usize], scroll_x);
@@ -306,10 +306,8 @@ fn interactive_loop(activity: &mut Activity, webview: &WebView) -> Result<()> {
             io::stdout().flush().unwrap();
             
             let mut input = String::new();
-            if handle.read_line(&mut input).is_ok() {
-                if tx.send(input.trim().to_string()).is_err() {
-                    break;
-                }
+            if handle.read_line(&mut input).is_ok() && tx.send(input.trim().to_string()).is_err() {
+                break;
             }
         }
     });
@@ -57,12 +57,9 @@ fn main() -> Result<()> {
         let event = read_message(activity.event_stream())?;
         let event_type = event["type"].as_str().unwrap_or("");
         
-        match event_type {
-            "destroy" => {
-                println!("\n✓ Activity 已关闭");
-                return Ok(());
-            },
-            _ => {}
+        if event_type == "destroy" {
+            println!("\n✓ Activity 已关闭");
+            return Ok(());
         }
     }
 }
@@ -1,6 +1,6 @@
 //! 简单的 WebView 测试 - 确保颜色对比清晰
 
-use termux_gui::{Activity, WebView, Result};
+use termux_gui::{Activity, Result};
 use termux_gui::connection::read_message;
 
 fn main() -> Result<()> {
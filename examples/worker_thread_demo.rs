@@ -0,0 +1,70 @@
+// 后台线程演示 - 在工作线程中更新界面
+// 展示 ActivityHandle 和后台事件线程: 耗时任务运行时界面仍能响应点击
+// 运行: cargo run --example worker_thread_demo --release
+
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use termux_gui::{Activity, Event, Result};
+
+fn main() -> Result<()> {
+    println!("=== 后台线程演示 ===\n");
+
+    let mut activity = Activity::new(true)?;
+    println!("✓ 连接建立\n");
+
+    let layout = activity.create_linear_layout(None)?;
+
+    let title = activity.create_text_view("后台任务演示 🦀", Some(layout.id()))?;
+    title.set_text_size(&mut activity, 26)?;
+    title.view().set_margin(&mut activity, 10)?;
+
+    // 控件在工作线程和主线程之间共享
+    let status = Arc::new(activity.create_text_view("空闲", Some(layout.id()))?);
+    let progress = Arc::new(activity.create_progress_bar(Some(layout.id()))?);
+
+    let start_button = activity.create_button("▶️ 开始任务", Some(layout.id()))?;
+    let ping_button = activity.create_button("👋 点我 (任务中也有效)", Some(layout.id()))?;
+    let quit_button = activity.create_button("❌ 退出", Some(layout.id()))?;
+
+    // 事件由后台线程读取，通过 channel 送到主线程
+    let events = activity.gui().spawn_event_reader()?;
+    let mut pings = 0;
+
+    println!("✓ 界面创建完成\n");
+
+    loop {
+        match events.recv().expect("事件线程已退出")? {
+            Event::Click { id, .. } if id == start_button.id() => {
+                // 每个工作线程拿一个 ActivityHandle 的副本
+                let handle = activity.handle();
+                let status = Arc::clone(&status);
+                let progress = Arc::clone(&progress);
+
+                thread::spawn(move || -> Result<()> {
+                    let mut activity = handle.activity()?;
+                    for step in 0..=10 {
+                        progress.set_progress(&mut activity, step * 10)?;
+                        status.set_text(&mut activity, &format!("处理中... {}%", step * 10))?;
+                        thread::sleep(Duration::from_millis(500));
+                    }
+                    status.set_text(&mut activity, "✓ 任务完成")
+                });
+            }
+            Event::Click { id, .. } if id == ping_button.id() => {
+                pings += 1;
+                println!("👋 第 {} 次点击", pings);
+            }
+            Event::Click { id, .. } if id == quit_button.id() => {
+                activity.finish()?;
+                break;
+            }
+            event if event.is_final_destroy() => break,
+            _ => {}
+        }
+    }
+
+    println!("✓ 程序结束");
+    Ok(())
+}
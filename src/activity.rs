@@ -1,16 +1,249 @@
 //! Activity management
 
-use serde_json::{json, Value};
+use serde_json::Value;
 use std::os::unix::net::UnixStream;
 
 use crate::connection::Connection;
-use crate::error::Result;
+use crate::dimension::DisplayMetrics;
+use crate::gui::Gui;
+use crate::transport::Connector;
+use crate::error::{GuiError, Result};
+use crate::event::Event;
+use crate::lifecycle::ActivityState;
+use crate::theme::Theme;
+use crate::raw;
 use crate::components::*;
 
+/// Screen orientation of an Activity, see [`Activity::set_orientation`]
+///
+/// These map to Android's `ActivityInfo.SCREEN_ORIENTATION_*` constants.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Orientation {
+    /// Let the system choose
+    Unspecified,
+    /// Same orientation as the Activity behind it
+    Behind,
+    /// Any of the four orientations, chosen by the sensor
+    FullSensor,
+    /// Any of the four orientations, respecting the user's rotation lock
+    FullUser,
+    /// Landscape
+    Landscape,
+    /// Keep the current orientation
+    Locked,
+    /// Ignore the sensor
+    NoSensor,
+    /// Portrait
+    Portrait,
+    /// Upside-down landscape
+    ReverseLandscape,
+    /// Upside-down portrait
+    ReversePortrait,
+    /// Either landscape orientation, chosen by the sensor
+    SensorLandscape,
+    /// Either portrait orientation, chosen by the sensor
+    SensorPortrait,
+    /// The user's preferred orientation
+    User,
+    /// Landscape, rotated by the sensor if the user allows it
+    UserLandscape,
+    /// Portrait, rotated by the sensor if the user allows it
+    UserPortrait,
+}
+
+impl Orientation {
+    /// Get the name used by the protocol
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Orientation::Unspecified => "unspecified",
+            Orientation::Behind => "behind",
+            Orientation::FullSensor => "fullSensor",
+            Orientation::FullUser => "fullUser",
+            Orientation::Landscape => "landscape",
+            Orientation::Locked => "locked",
+            Orientation::NoSensor => "nosensor",
+            Orientation::Portrait => "portrait",
+            Orientation::ReverseLandscape => "reverseLandscape",
+            Orientation::ReversePortrait => "reversePortrait",
+            Orientation::SensorLandscape => "sensorLandscape",
+            Orientation::SensorPortrait => "sensorPortrait",
+            Orientation::User => "user",
+            Orientation::UserLandscape => "userLandscape",
+            Orientation::UserPortrait => "userPortrait",
+        }
+    }
+}
+
+/// How an Activity makes room for the soft keyboard, see [`Activity::set_input_mode`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SoftInputMode {
+    /// Pan the window so the focused view stays visible
+    Pan,
+    /// Resize the window to the space above the keyboard
+    Resize,
+}
+
+impl SoftInputMode {
+    /// Get the name used by the protocol
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SoftInputMode::Pan => "pan",
+            SoftInputMode::Resize => "resize",
+        }
+    }
+}
+
+/// Device configuration reported by the service, see [`Activity::configuration`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Configuration {
+    /// Whether the system is in dark mode
+    pub dark_mode: Option<bool>,
+    /// Country code of the current locale
+    pub country: Option<String>,
+    /// Language code of the current locale
+    pub language: Option<String>,
+    /// Screen orientation, `"portrait"` or `"landscape"`
+    pub orientation: Option<String>,
+    /// Whether the hardware keyboard is hidden
+    pub keyboard_hidden: Option<bool>,
+    /// Screen width in dp
+    pub screen_width: Option<i32>,
+    /// Screen height in dp
+    pub screen_height: Option<i32>,
+    /// The user's font size setting
+    pub font_scale: f64,
+    /// Pixels per dp
+    pub density: f64,
+}
+
+impl Configuration {
+    /// Decode a configuration, as returned by `getConfiguration` or sent in
+    /// `config` events
+    pub fn from_json(value: &Value) -> Result<Self> {
+        let density = value["density"].as_f64().ok_or_else(|| GuiError::InvalidResponse {
+            method: "getConfiguration".to_string(),
+            payload: value.clone(),
+        })?;
+        
+        Ok(Configuration {
+            dark_mode: value["dark_mode"].as_bool(),
+            country: value["country"].as_str().map(String::from),
+            language: value["language"].as_str().map(String::from),
+            orientation: value["orientation"].as_str().map(String::from),
+            keyboard_hidden: value["keyboardHidden"].as_bool(),
+            screen_width: value["screenwidth"].as_i64().map(|v| v as i32),
+            screen_height: value["screenheight"].as_i64().map(|v| v as i32),
+            font_scale: value["fontscale"].as_f64().unwrap_or(1.0),
+            density,
+        })
+    }
+    
+    /// Get the values needed to convert between dp, sp and px
+    pub fn display_metrics(&self) -> DisplayMetrics {
+        DisplayMetrics {
+            density: self.density,
+            font_scale: self.font_scale,
+        }
+    }
+}
+
+/// Options for launching an Activity
+///
+/// ```rust,no_run
+/// use termux_gui::{Activity, Result};
+///
+/// # fn main() -> Result<()> {
+/// // A dialog that stays open when touched outside
+/// let dialog = Activity::builder().dialog(true).cancel_outside(false).build()?;
+///
+/// // A full-screen Activity shown above the lock screen
+/// let clock = Activity::builder().lockscreen(true).build_in(dialog.gui())?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ActivityBuilder {
+    dialog: bool,
+    pip: bool,
+    lockscreen: bool,
+    cancel_outside: Option<bool>,
+    intercept_back_button: bool,
+    tid: Option<i64>,
+}
+
+impl ActivityBuilder {
+    /// Create a builder for a full-screen Activity in a new task
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Set whether to show the Activity as a dialog
+    pub fn dialog(mut self, dialog: bool) -> Self {
+        self.dialog = dialog;
+        self
+    }
+    
+    /// Set whether to start in picture-in-picture mode
+    pub fn pip(mut self, pip: bool) -> Self {
+        self.pip = pip;
+        self
+    }
+    
+    /// Set whether to show the Activity on top of the lock screen
+    pub fn lockscreen(mut self, lockscreen: bool) -> Self {
+        self.lockscreen = lockscreen;
+        self
+    }
+    
+    /// Set whether touching outside a dialog closes it
+    ///
+    /// Defaults to `true` for full-screen activities and `false` for dialogs.
+    pub fn cancel_outside(mut self, cancel_outside: bool) -> Self {
+        self.cancel_outside = Some(cancel_outside);
+        self
+    }
+    
+    /// Set whether the back button sends a `back` event instead of closing
+    /// the Activity
+    pub fn intercept_back_button(mut self, intercept: bool) -> Self {
+        self.intercept_back_button = intercept;
+        self
+    }
+    
+    /// Launch into the task of an existing Activity, see [`Activity::task_id`]
+    pub fn task(mut self, tid: i64) -> Self {
+        self.tid = Some(tid);
+        self
+    }
+    
+    /// Launch the Activity on a new connection
+    pub fn build(&self) -> Result<Activity> {
+        self.build_in(&Gui::new()?)
+    }
+    
+    /// Launch the Activity in an existing session
+    pub fn build_in(&self, gui: &Gui) -> Result<Activity> {
+        gui.launch(&raw::NewActivity {
+            tid: self.tid,
+            dialog: Some(self.dialog),
+            pip: Some(self.pip),
+            lockscreen: Some(self.lockscreen),
+            canceloutside: Some(self.cancel_outside.unwrap_or(!self.dialog)),
+            intercept: Some(self.intercept_back_button),
+            overlay: None,
+        })
+    }
+}
+
 /// Represents a GUI Activity (window)
+///
+/// Several activities can share one connection; see [`Gui`].
 pub struct Activity {
-    conn: Connection,
+    gui: Gui,
     aid: i64,
+    tid: i64,
+    events: UnixStream,
+    metrics: Option<DisplayMetrics>,
 }
 
 impl Activity {
@@ -19,26 +252,28 @@ impl Activity {
     /// # Arguments
     /// * `dialog` - If true, creates a dialog-style window; if false, creates a full-screen activity
     pub fn new(dialog: bool) -> Result<Self> {
-        eprintln!("[DEBUG] Activity::new() - creating connection...");
-        let mut conn = Connection::new()?;
-        
-        eprintln!("[DEBUG] Activity::new() - sending newActivity...");
-        let response = conn.send_read(&json!({
-            "method": "newActivity",
-            "params": {
-                "dialog": dialog,
-                "canceloutside": !dialog
-            }
-        }))?;
-        
-        eprintln!("[DEBUG] Activity::new() - got response: {:?}", response);
-        // Response is an array, aid is at index 0
-        let aid = response[0]
-            .as_i64()
-            .ok_or_else(|| crate::error::GuiError::InvalidResponse("Missing aid".to_string()))?;
-        
-        eprintln!("[DEBUG] Activity::new() - aid = {}", aid);
-        Ok(Activity { conn, aid })
+        Gui::new()?.new_activity(dialog)
+    }
+    
+    /// Start building an Activity with more options than [`new`](Self::new)
+    pub fn builder() -> ActivityBuilder {
+        ActivityBuilder::new()
+    }
+    
+    /// Create a new Activity, connecting with a custom [`Connector`]
+    pub fn with_connector<C: Connector + ?Sized>(connector: &C, dialog: bool) -> Result<Self> {
+        Gui::with_connector(connector)?.new_activity(dialog)
+    }
+    
+    /// Create a new Activity on an already established connection
+    pub fn with_connection(conn: Connection, dialog: bool) -> Result<Self> {
+        Gui::with_connection(conn)?.new_activity(dialog)
+    }
+    
+    /// Wrap an Activity the service has already created
+    pub(crate) fn attach(gui: Gui, aid: i64, tid: i64) -> Result<Self> {
+        let events = gui.clone_event_stream()?;
+        Ok(Activity { gui, aid, tid, events, metrics: None })
     }
     
     /// Get the Activity ID
@@ -46,19 +281,107 @@ impl Activity {
         self.aid
     }
     
+    /// Get the ID of the task this Activity runs in
+    pub fn task_id(&self) -> i64 {
+        self.tid
+    }
+    
+    /// Get the last known lifecycle state
+    ///
+    /// Updated whenever a lifecycle event for this Activity is read from
+    /// the session, by any Activity or thread.
+    pub fn state(&self) -> ActivityState {
+        self.gui.activity_state(self.aid).unwrap_or(ActivityState::Created)
+    }
+    
+    /// Get a cloneable, thread-safe handle to this Activity
+    ///
+    /// Requests through all handles and the Activity itself are serialized
+    /// over the shared main socket, so a worker thread can update the UI
+    /// while the main thread waits for events.
+    pub fn handle(&self) -> ActivityHandle {
+        ActivityHandle {
+            gui: self.gui.clone(),
+            aid: self.aid,
+            tid: self.tid,
+        }
+    }
+    
+    /// Get the session this Activity belongs to
+    ///
+    /// Use it to open more activities on the same connection.
+    pub fn gui(&self) -> &Gui {
+        &self.gui
+    }
+    
     /// Send a message and read response
+    ///
+    /// Fails with `GuiError::ActivityDestroyed` once the Activity is destroyed.
     pub fn send_read(&mut self, msg: &Value) -> Result<Value> {
-        self.conn.send_read(msg)
+        check_alive(&self.gui, self.aid)?;
+        self.gui.send_read(msg)
     }
     
     /// Send a message without waiting for response
+    ///
+    /// Fails with `GuiError::ActivityDestroyed` once the Activity is destroyed.
     pub fn send(&mut self, msg: &Value) -> Result<()> {
-        self.conn.send(msg)
+        check_alive(&self.gui, self.aid)?;
+        self.gui.send(msg)
+    }
+    
+    /// Send a `create*` message and return the ID of the new view
+    ///
+    /// Fails with `GuiError::InvalidResponse` if the reply isn't an ID.
+    pub(crate) fn create_view(&mut self, msg: &Value) -> Result<i64> {
+        let response = self.send_read(msg)?;
+        response
+            .as_i64()
+            .ok_or_else(|| GuiError::invalid_response(msg, &response))
+    }
+    
+    /// Fail with `GuiError::ActivityDestroyed` once the Activity is destroyed
+    pub(crate) fn check_alive(&self) -> Result<()> {
+        check_alive(&self.gui, self.aid)
     }
     
     /// Get mutable reference to event stream
+    ///
+    /// Reading from it directly bypasses the routing done by
+    /// [`next_event`](Self::next_event), so don't mix the two, and don't use
+    /// it when other activities share the connection.
     pub fn event_stream(&mut self) -> &mut UnixStream {
-        self.conn.event_stream()
+        &mut self.events
+    }
+    
+    /// Block until the next event for this Activity arrives and decode it
+    ///
+    /// Global events that don't belong to any Activity are returned too.
+    /// Events for other activities on the same connection are kept for them.
+    pub fn next_event(&mut self) -> Result<Event> {
+        let event = self.gui.next_event_for(self.aid)?;
+        if let Event::Config { configuration, .. } = &event {
+            self.metrics = Configuration::from_json(configuration).ok().map(|c| c.display_metrics());
+        }
+        Ok(event)
+    }
+    
+    /// Query the current device configuration
+    pub fn configuration(&mut self) -> Result<Configuration> {
+        let configuration = raw::get_configuration(self, &raw::GetConfiguration { aid: self.aid })?;
+        self.metrics = Some(configuration.display_metrics());
+        Ok(configuration)
+    }
+    
+    /// Get the display density, for converting between dp, sp and px
+    ///
+    /// Queried with [`configuration`](Self::configuration) on first use and
+    /// cached until a `config` event reports a change.
+    pub fn display_metrics(&mut self) -> Result<DisplayMetrics> {
+        match self.metrics {
+            Some(metrics) => Ok(metrics),
+            None => Ok(self.configuration()?.display_metrics()),
+        }
     }
     
     /// Create a LinearLayout
@@ -198,39 +521,286 @@ impl Activity {
     }
     
     /// Set the Activity title
+    ///
+    /// This is the label shown in the recent apps list; the theme is left
+    /// untouched.
     pub fn set_title(&mut self, title: &str) -> Result<()> {
-        self.send(&json!({
-            "method": "setTheme",
-            "params": {
-                "aid": self.aid,
-                "statusBarColor": 0,
-                "colorPrimary": 0,
-                "windowBackground": 0,
-                "textColor": 0,
-                "colorAccent": 0
-            }
-        }))?;
-        
-        self.send_read(&json!({
-            "method": "setTaskDescription",
-            "params": {
-                "aid": self.aid,
-                "label": title
-            }
-        }))?;
-        
-        Ok(())
+        self.set_task_description(title, None)
+    }
+    
+    /// Set the label and icon shown for the task in the recent apps list
+    ///
+    /// # Arguments
+    /// * `label` - The label of the task
+    /// * `icon_base64` - Optional base64 encoded PNG or JPEG icon
+    pub fn set_task_description(&mut self, label: &str, icon_base64: Option<&str>) -> Result<()> {
+        raw::set_task_description(self, &raw::SetTaskDescription {
+            aid: self.aid,
+            label: label.to_string(),
+            img: icon_base64.map(String::from),
+        })
+    }
+    
+    /// Set the colours of the Activity
+    ///
+    /// See [`Theme::light`] and [`Theme::dark`] for presets.
+    pub fn set_theme(&mut self, theme: &Theme) -> Result<()> {
+        raw::set_theme(self, &theme.to_params(self.aid))
     }
     
     /// Finish (close) the Activity
     pub fn finish(&mut self) -> Result<()> {
-        self.send_read(&json!({
-            "method": "finishActivity",
-            "params": {
-                "aid": self.aid,
-                "finishing": true
-            }
-        }))?;
+        raw::finish_activity(self, &raw::FinishActivity { aid: self.aid, finishing: Some(true) })
+    }
+    
+    /// Set the screen orientation
+    pub fn set_orientation(&mut self, orientation: Orientation) -> Result<()> {
+        raw::set_orientation(self, &raw::SetOrientation {
+            aid: self.aid,
+            orientation: orientation.as_str().to_string(),
+        })
+    }
+    
+    /// Keep the screen on while the Activity is visible
+    pub fn keep_screen_on(&mut self, on: bool) -> Result<()> {
+        raw::keep_screen_on(self, &raw::KeepScreenOn { aid: self.aid, on })
+    }
+    
+    /// Prevent screenshots and hide the content in the recent apps list
+    pub fn set_secure(&mut self, secure: bool) -> Result<()> {
+        raw::set_secure(self, &raw::SetSecure { aid: self.aid, secure })
+    }
+    
+    /// Enter or leave picture-in-picture mode
+    pub fn set_pip_mode(&mut self, pip: bool) -> Result<()> {
+        raw::set_pip_mode(self, &raw::SetPipMode { aid: self.aid, pip })
+    }
+    
+    /// Set whether to enter picture-in-picture mode automatically when the
+    /// user leaves the Activity
+    pub fn set_pip_mode_auto(&mut self, pip: bool) -> Result<()> {
+        raw::set_pip_mode_auto(self, &raw::SetPipModeAuto { aid: self.aid, pip })
+    }
+    
+    /// Set the aspect ratio of the picture-in-picture window
+    ///
+    /// # Arguments
+    /// * `num` - Numerator of the aspect ratio
+    /// * `den` - Denominator of the aspect ratio
+    pub fn set_pip_params(&mut self, num: i32, den: i32) -> Result<()> {
+        raw::set_pip_params(self, &raw::SetPipParams {
+            aid: self.aid,
+            num: num.into(),
+            den: den.into(),
+        })
+    }
+    
+    /// Set how the Activity makes room for the soft keyboard
+    pub fn set_input_mode(&mut self, mode: SoftInputMode) -> Result<()> {
+        raw::set_input_mode(self, &raw::SetInputMode {
+            aid: self.aid,
+            mode: mode.as_str().to_string(),
+        })
+    }
+    
+    /// Set whether the back button sends a `back` event instead of closing
+    /// the Activity
+    pub fn intercept_back_button(&mut self, intercept: bool) -> Result<()> {
+        raw::intercept_back_button(self, &raw::InterceptBackButton { aid: self.aid, intercept })
+    }
+    
+    /// Hide the soft keyboard
+    pub fn hide_soft_keyboard(&mut self) -> Result<()> {
+        raw::hide_soft_keyboard(self, &raw::HideSoftKeyboard { aid: self.aid })
+    }
+}
+
+/// A cloneable, `Send + Sync` handle to an [`Activity`]
+///
+/// Get one with [`Activity::handle`]. Widget methods take `&mut Activity`,
+/// so call [`activity`](Self::activity) on the thread that needs one.
+///
+/// ```rust,no_run
+/// use std::thread;
+/// use termux_gui::{Activity, Result};
+///
+/// # fn main() -> Result<()> {
+/// let mut activity = Activity::new(true)?;
+/// let status = activity.create_text_view("Working...", None)?;
+///
+/// let handle = activity.handle();
+/// let worker = thread::spawn(move || -> Result<()> {
+///     let mut activity = handle.activity()?;
+///     // ... long operation ...
+///     status.set_text(&mut activity, "Done")
+/// });
+///
+/// // Meanwhile the main thread keeps handling events
+/// let event = activity.next_event()?;
+/// worker.join().unwrap()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct ActivityHandle {
+    gui: Gui,
+    aid: i64,
+    tid: i64,
+}
+
+impl ActivityHandle {
+    /// Get the Activity ID
+    pub fn id(&self) -> i64 {
+        self.aid
+    }
+    
+    /// Get the ID of the task the Activity runs in
+    pub fn task_id(&self) -> i64 {
+        self.tid
+    }
+    
+    /// Get the session the Activity belongs to
+    pub fn gui(&self) -> &Gui {
+        &self.gui
+    }
+    
+    /// Get the last known lifecycle state of the Activity
+    pub fn state(&self) -> ActivityState {
+        self.gui.activity_state(self.aid).unwrap_or(ActivityState::Created)
+    }
+    
+    /// Get an [`Activity`] for calling widget methods on this thread
+    pub fn activity(&self) -> Result<Activity> {
+        Activity::attach(self.gui.clone(), self.aid, self.tid)
+    }
+    
+    /// Send a message and read response
+    pub fn send_read(&self, msg: &Value) -> Result<Value> {
+        check_alive(&self.gui, self.aid)?;
+        self.gui.send_read(msg)
+    }
+    
+    /// Send a message without waiting for response
+    pub fn send(&self, msg: &Value) -> Result<()> {
+        check_alive(&self.gui, self.aid)?;
+        self.gui.send(msg)
+    }
+}
+
+/// Refuse requests for an Activity that has been destroyed
+fn check_alive(gui: &Gui, aid: i64) -> Result<()> {
+    match gui.activity_state(aid) {
+        Some(ActivityState::Destroyed) => Err(GuiError::ActivityDestroyed(aid)),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use std::thread;
+
+    use serde_json::json;
+
+    use super::*;
+    use crate::testing::MockService;
+
+    #[test]
+    fn handles_update_widgets_from_other_threads() -> Result<()> {
+        let (mock, mut activity) = MockService::activity(false)?;
+        let status = activity.create_text_view("Working", None)?;
+
+        let handle = activity.handle();
+        let worker = thread::spawn(move || -> Result<()> {
+            let mut activity = handle.activity()?;
+            status.set_text(&mut activity, "Done")
+        });
+        worker.join().unwrap()?;
+
+        let set = &mock.calls_to("setText")[0];
+        assert_eq!(set["aid"], json!(activity.id()));
+        assert_eq!(set["text"], "Done");
+        Ok(())
+    }
+
+    #[test]
+    fn handles_share_the_activity_and_task() -> Result<()> {
+        let (_mock, activity) = MockService::activity(false)?;
+        let handle = activity.handle().clone();
+        assert_eq!((handle.id(), handle.task_id()), (activity.id(), activity.task_id()));
+
+        let attached = handle.activity()?;
+        assert_eq!(attached.id(), activity.id());
+        Ok(())
+    }
+
+    #[test]
+    fn builder_options_are_sent_with_new_activity() -> Result<()> {
+        let (mock, gui) = MockService::gui()?;
+        ActivityBuilder::new().dialog(true).pip(true).intercept_back_button(true).build_in(&gui)?;
+        ActivityBuilder::new().lockscreen(true).task(0).build_in(&gui)?;
+
+        let launched = mock.calls_to("newActivity");
+        assert_eq!(launched[0]["dialog"], json!(true));
+        assert_eq!(launched[0]["pip"], json!(true));
+        assert_eq!(launched[0]["intercept"], json!(true));
+        // Dialogs stay open when touched outside unless told otherwise
+        assert_eq!(launched[0]["canceloutside"], json!(false));
+        assert_eq!(launched[1]["lockscreen"], json!(true));
+        assert_eq!(launched[1]["canceloutside"], json!(true));
+        assert_eq!(launched[1]["tid"], json!(0));
+        Ok(())
+    }
+
+    #[test]
+    fn window_settings_use_protocol_names() -> Result<()> {
+        let (mock, mut activity) = MockService::activity(false)?;
+        activity.set_orientation(Orientation::ReverseLandscape)?;
+        activity.keep_screen_on(true)?;
+        activity.set_secure(true)?;
+        activity.set_pip_params(16, 9)?;
+        activity.set_input_mode(SoftInputMode::Resize)?;
+        activity.set_title("Title")?;
+
+        assert_eq!(mock.calls_to("setOrientation")[0]["orientation"], "reverseLandscape");
+        assert_eq!(mock.calls_to("keepScreenOn")[0]["on"], json!(true));
+        assert_eq!(mock.calls_to("setSecure")[0]["secure"], json!(true));
+        let pip = &mock.calls_to("setPiPParams")[0];
+        assert_eq!((pip["num"].clone(), pip["den"].clone()), (json!(16), json!(9)));
+        assert_eq!(mock.calls_to("setInputMode")[0]["mode"], "resize");
+        assert_eq!(mock.calls_to("setTaskDescription")[0]["label"], "Title");
+        assert!(mock.calls_to("setTheme").is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn configuration_is_decoded_and_cached() -> Result<()> {
+        let (mock, mut activity) = MockService::activity(false)?;
+        let configuration = activity.configuration()?;
+        assert_eq!(configuration.orientation.as_deref(), Some("portrait"));
+        assert_eq!(configuration.screen_width, Some(400));
+        assert_eq!(configuration.density, 2.0);
+
+        activity.display_metrics()?;
+        assert_eq!(mock.calls_to("getConfiguration").len(), 1);
+
+        mock.set_response("getConfiguration", json!({"dark_mode": true}));
+        assert!(matches!(
+            activity.configuration().unwrap_err().root(),
+            GuiError::InvalidResponse { .. }
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn background_reader_delivers_events_in_order() -> Result<()> {
+        let (mock, activity) = MockService::activity(false)?;
+        let events = activity.gui().spawn_event_reader()?;
+
+        for id in 0..3 {
+            mock.send_event(&json!({"type": "click", "value": {"aid": activity.id(), "id": id}}))?;
+        }
+        let ids: Vec<_> = (0..3).map(|_| events.recv().unwrap().unwrap().view_id()).collect();
+        assert_eq!(ids, [Some(0), Some(1), Some(2)]);
         Ok(())
     }
 }
@@ -0,0 +1,403 @@
+//! Async API over tokio
+//!
+//! [`AsyncConnection`] speaks the same length-prefixed JSON protocol as
+//! [`Connection`], but over `tokio::net::UnixStream`, so requests can be
+//! awaited alongside other futures instead of wrapping blocking calls in
+//! `spawn_blocking`. Events are available as a [`Stream`] through
+//! [`AsyncConnection::events`].
+//!
+//! Establishing the connection still goes through the blocking
+//! [`Connector`]s; [`AsyncConnection::connect`] runs that on tokio's
+//! blocking pool. Recording and tracing work as on a blocking connection.
+//!
+//! Requests are written and answered on a spawned task that owns the main
+//! socket, so they are cancel-safe: dropping a `send_read` future, e.g. in
+//! `select!` or on a timeout, doesn't leave an unread reply on the socket
+//! for the next request to pick up.
+//!
+//! This module is only available with the `async` feature and must be used
+//! inside a tokio runtime.
+//!
+//! ## Example
+//!
+//! ```rust,no_run
+//! use serde_json::json;
+//! use termux_gui::asynchronous::AsyncConnection;
+//! use termux_gui::Result;
+//!
+//! async fn run() -> Result<()> {
+//!     let mut conn = AsyncConnection::connect().await?;
+//!
+//!     let response = conn.send_read(&json!({
+//!         "method": "newActivity",
+//!         "params": {"dialog": true}
+//!     })).await?;
+//!     let aid = response[0].as_i64().unwrap_or_default();
+//!
+//!     conn.send_read(&json!({
+//!         "method": "createTextView",
+//!         "params": {"aid": aid, "text": "Hello from tokio"}
+//!     })).await?;
+//!
+//!     let mut events = conn.events()?;
+//!     while let Some(event) = events.recv().await {
+//!         if event?.is_final_destroy() {
+//!             break;
+//!         }
+//!     }
+//!     Ok(())
+//! }
+//! ```
+
+use std::io;
+use std::os::unix::net::UnixStream as StdUnixStream;
+use std::panic;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use futures_core::Stream;
+use serde_json::Value;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::UnixStream;
+use tokio::sync::{mpsc, oneshot};
+use tokio::task::JoinHandle;
+
+use crate::connection::Connection;
+use crate::error::{GuiError, Result};
+use crate::event::Event;
+use crate::protocol;
+use crate::record::{EntryKind, Recorder};
+use crate::trace::{debug, CallTrace};
+use crate::transport::{self, Connector};
+
+/// Send a JSON message to an async stream
+pub async fn send_message<W: AsyncWrite + Unpin>(stream: &mut W, msg: &Value) -> Result<()> {
+    let json_bytes = msg.to_string().into_bytes();
+    stream.write_all(&(json_bytes.len() as u32).to_be_bytes()).await?;
+    stream.write_all(&json_bytes).await?;
+    stream.flush().await?;
+    Ok(())
+}
+
+/// Read a JSON message from an async stream
+pub async fn read_message<R: AsyncRead + Unpin>(stream: &mut R) -> Result<Value> {
+    let mut len_buf = [0u8; 4];
+    stream.read_exact(&mut len_buf).await?;
+    let len = u32::from_be_bytes(len_buf) as usize;
+
+    let mut buf = vec![0u8; len];
+    stream.read_exact(&mut buf).await?;
+
+    let value = serde_json::from_slice(&buf)?;
+    Ok(value)
+}
+
+/// Async connection to Termux GUI service
+pub struct AsyncConnection {
+    requests: mpsc::UnboundedSender<Call>,
+    event_stream: Option<UnixStream>,
+    recorder: Option<Recorder>,
+}
+
+/// A request handed to the task owning the main socket
+struct Call {
+    msg: Value,
+    read_reply: bool,
+    /// Gets the reply, or `Value::Null` once a `send` is written
+    reply: oneshot::Sender<Result<Value>>,
+}
+
+impl AsyncConnection {
+    /// Connect with the default connector, like `Connection::new`
+    pub async fn connect() -> Result<Self> {
+        Self::from_connection(connect_blocking(|| transport::default_connector().connect()).await?)
+    }
+
+    /// Connect with a custom [`Connector`]
+    pub async fn connect_with<C>(connector: C) -> Result<Self>
+    where
+        C: Connector + Send + 'static,
+    {
+        Self::from_connection(connect_blocking(move || connector.connect()).await?)
+    }
+
+    /// Convert an established blocking connection
+    ///
+    /// Keeps the connection's recorder, if any. Spawns the task that owns
+    /// the main socket, so this must be called inside a tokio runtime.
+    pub fn from_connection(conn: Connection) -> Result<Self> {
+        let (main_stream, event_stream, recorder) = conn.into_parts();
+        let main_stream = into_tokio(main_stream)?;
+        let (requests, calls) = mpsc::unbounded_channel();
+        tokio::spawn(serve_calls(main_stream, calls, recorder.clone()));
+
+        Ok(AsyncConnection {
+            requests,
+            event_stream: Some(into_tokio(event_stream)?),
+            recorder,
+        })
+    }
+
+    /// Send a message without waiting for response
+    ///
+    /// Checked against the [protocol table](crate::protocol) like
+    /// [`Connection::send`].
+    pub async fn send(&mut self, msg: &Value) -> Result<()> {
+        let trace = CallTrace::start("send", msg);
+        let result = trace
+            .instrument(async {
+                protocol::check_request(msg, false)?;
+                self.call(msg, false).await.map(drop)
+            })
+            .await
+            .map_err(|e| e.in_call(msg));
+        trace.finish(&result);
+        result
+    }
+
+    /// Send a message and read the response
+    ///
+    /// Checked against the [protocol table](crate::protocol) like
+    /// [`Connection::send_read`].
+    pub async fn send_read(&mut self, msg: &Value) -> Result<Value> {
+        let trace = CallTrace::start("send_read", msg);
+        let result = trace
+            .instrument(async {
+                let method = protocol::check_request(msg, true)?;
+                let response = self.call(msg, true).await?;
+                protocol::check_reply(method, &response)?;
+                Ok::<_, GuiError>(response)
+            })
+            .await
+            .map_err(|e| e.in_call(msg));
+        trace.finish(&result);
+        result
+    }
+
+    /// Wait for the next event and decode it
+    ///
+    /// Fails once the event socket has been handed to [`events`](Self::events).
+    pub async fn read_event(&mut self) -> Result<Event> {
+        let stream = self.event_stream.as_mut().ok_or_else(events_taken)?;
+        read_recorded_event(stream, self.recorder.as_ref()).await
+    }
+
+    /// Turn the event socket into a [`Stream`] of events
+    ///
+    /// Events are read on a spawned task, so they keep arriving while this
+    /// connection is busy with requests. Can only be called once.
+    pub fn events(&mut self) -> Result<EventStream> {
+        let mut stream = self.event_stream.take().ok_or_else(events_taken)?;
+        let recorder = self.recorder.clone();
+        let (tx, rx) = mpsc::unbounded_channel();
+
+        let task = tokio::spawn(async move {
+            loop {
+                let event = read_recorded_event(&mut stream, recorder.as_ref()).await;
+                let failed = event.is_err();
+                if tx.send(event).is_err() || failed {
+                    return;
+                }
+            }
+        });
+
+        Ok(EventStream { rx, task })
+    }
+
+    /// Hand `msg` to the socket task and wait for its answer
+    ///
+    /// Once queued the request is written and its reply read even if this
+    /// future is dropped.
+    async fn call(&self, msg: &Value, read_reply: bool) -> Result<Value> {
+        let (reply, answer) = oneshot::channel();
+        let call = Call { msg: msg.clone(), read_reply, reply };
+        self.requests.send(call).map_err(|_| socket_task_stopped())?;
+        answer.await.map_err(|_| socket_task_stopped())?
+    }
+}
+
+/// Write requests and read their replies, one at a time, in queue order
+///
+/// Stops after the first I/O error, as the socket may be out of sync, or
+/// once the connection is dropped.
+async fn serve_calls(
+    mut stream: UnixStream,
+    mut calls: mpsc::UnboundedReceiver<Call>,
+    recorder: Option<Recorder>,
+) {
+    let record = |kind, msg: &Value| {
+        if let Some(recorder) = &recorder {
+            recorder.record(kind, msg);
+        }
+    };
+
+    while let Some(call) = calls.recv().await {
+        let kind = if call.read_reply { EntryKind::SendRead } else { EntryKind::Send };
+        record(kind, &call.msg);
+        let mut result = send_message(&mut stream, &call.msg).await.map(|()| Value::Null);
+        if call.read_reply && result.is_ok() {
+            result = read_message(&mut stream).await;
+            if let Ok(response) = &result {
+                record(EntryKind::Response, response);
+            }
+        }
+
+        let failed = result.is_err();
+        // The caller may have stopped waiting; the reply is consumed anyway
+        let _ = call.reply.send(result);
+        if failed {
+            debug!("main socket failed, stopping the request task");
+            return;
+        }
+    }
+}
+
+/// Run a blocking connector on tokio's blocking pool
+///
+/// A panic in the connector is resumed in the caller.
+async fn connect_blocking<F>(connect: F) -> Result<Connection>
+where
+    F: FnOnce() -> Result<Connection> + Send + 'static,
+{
+    match tokio::task::spawn_blocking(connect).await {
+        Ok(result) => result,
+        Err(e) if e.is_panic() => panic::resume_unwind(e.into_panic()),
+        Err(e) => Err(io::Error::from(e).into()),
+    }
+}
+
+/// Stream of events from an [`AsyncConnection`]
+///
+/// Yields `Err` once if reading fails, then ends. Dropping the stream stops
+/// the reader task.
+pub struct EventStream {
+    rx: mpsc::UnboundedReceiver<Result<Event>>,
+    task: JoinHandle<()>,
+}
+
+impl EventStream {
+    /// Wait for the next event, or `None` once the stream has ended
+    pub async fn recv(&mut self) -> Option<Result<Event>> {
+        self.rx.recv().await
+    }
+}
+
+impl Stream for EventStream {
+    type Item = Result<Event>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.rx.poll_recv(cx)
+    }
+}
+
+impl Drop for EventStream {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+fn into_tokio(stream: StdUnixStream) -> Result<UnixStream> {
+    stream.set_nonblocking(true)?;
+    Ok(UnixStream::from_std(stream)?)
+}
+
+fn socket_task_stopped() -> GuiError {
+    io::Error::new(io::ErrorKind::BrokenPipe, "the main socket failed on an earlier request").into()
+}
+
+fn events_taken() -> GuiError {
+    GuiError::InvalidOperation("Event socket was already turned into an EventStream".to_string())
+}
+
+async fn read_recorded_event(stream: &mut UnixStream, recorder: Option<&Recorder>) -> Result<Event> {
+    let msg = read_message(stream).await?;
+    if let Some(recorder) = recorder {
+        recorder.record(EntryKind::Event, &msg);
+    }
+    let event = Event::from_json(&msg)?;
+    debug!(?event, "event");
+    Ok(event)
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use std::future::Future;
+    use std::task::Waker;
+
+    use serde_json::json;
+
+    use super::*;
+    use crate::testing::MockService;
+
+    fn block_on<F: Future>(future: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_io()
+            .build()
+            .unwrap()
+            .block_on(future)
+    }
+
+    async fn create_text(conn: &mut AsyncConnection, aid: i64, text: &str) -> Result<i64> {
+        let id = conn
+            .send_read(&json!({"method": "createTextView", "params": {"aid": aid, "text": text}}))
+            .await?;
+        Ok(id.as_i64().unwrap())
+    }
+
+    fn get_text(aid: i64, id: i64) -> Value {
+        json!({"method": "getText", "params": {"aid": aid, "id": id}})
+    }
+
+    #[test]
+    fn requests_and_events_work_like_on_a_blocking_connection() -> Result<()> {
+        let (mock, conn) = MockService::start()?;
+        block_on(async {
+            let mut conn = AsyncConnection::from_connection(conn)?;
+            let ids = conn.send_read(&json!({"method": "newActivity", "params": {}})).await?;
+            let aid = ids[0].as_i64().unwrap();
+            let id = create_text(&mut conn, aid, "hello").await?;
+
+            conn.send(&json!({"method": "setText", "params": {"aid": aid, "id": id, "text": "bye"}}))
+                .await?;
+            assert_eq!(conn.send_read(&get_text(aid, id)).await?, "bye");
+
+            mock.send_event(&json!({"type": "click", "value": {"aid": aid, "id": id}}))?;
+            let mut events = conn.events()?;
+            assert_eq!(events.recv().await.unwrap()?.view_id(), Some(id));
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn dropping_a_request_keeps_the_socket_in_sync() -> Result<()> {
+        let (_mock, conn) = MockService::start()?;
+        block_on(async {
+            let mut conn = AsyncConnection::from_connection(conn)?;
+            let first = create_text(&mut conn, 0, "first").await?;
+            let second = create_text(&mut conn, 0, "second").await?;
+
+            // Queue a request, then give up on it before it is answered
+            let request = get_text(0, first);
+            let mut dropped = Box::pin(conn.send_read(&request));
+            let mut cx = Context::from_waker(Waker::noop());
+            assert!(dropped.as_mut().poll(&mut cx).is_pending());
+            drop(dropped);
+
+            assert_eq!(conn.send_read(&get_text(0, second)).await?, "second");
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn requests_are_checked_before_sending() -> Result<()> {
+        let (mock, conn) = MockService::start()?;
+        block_on(async {
+            let mut conn = AsyncConnection::from_connection(conn)?;
+            // `setText` has no reply, so waiting for one would hang
+            let result = conn.send_read(&json!({"method": "setText", "params": {}})).await;
+            assert!(result.is_err());
+            Ok::<_, GuiError>(())
+        })?;
+        assert!(mock.calls().is_empty());
+        Ok(())
+    }
+}
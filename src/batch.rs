@@ -0,0 +1,668 @@
+//! Batched requests
+//!
+//! Every `create*` call waits for the service to answer with the new view
+//! ID, so building a screen of 50 widgets takes 50 round trips. A [`Batch`]
+//! queues create and set calls and sends them in one go: requests are
+//! written to the main socket without waiting, and the responses are read
+//! afterwards, in order.
+//!
+//! Creating a widget in a batch returns a [`Pending`] placeholder, which
+//! can be used as the parent of other widgets or the target of set calls
+//! in the same batch. When a request needs the ID behind a placeholder,
+//! the batch first reads the responses up to that one, so a tree of
+//! widgets costs one round trip per level instead of one per widget.
+//!
+//! After [`run`](Batch::run), get the real widgets from the returned
+//! [`BatchResult`].
+//!
+//! ## Example
+//!
+//! ```rust,no_run
+//! use termux_gui::{Activity, Color, Result};
+//!
+//! fn main() -> Result<()> {
+//!     let mut activity = Activity::new(false)?;
+//!
+//!     let mut batch = activity.batch();
+//!     let layout = batch.create_linear_layout(None);
+//!     let title = batch.create_text_view("Settings", layout);
+//!     batch.set_text_size(title, 24);
+//!     let rows: Vec<_> = (0..20)
+//!         .map(|i| batch.create_switch(&format!("Option {}", i), layout))
+//!         .collect();
+//!     batch.set_background_color(rows[0], Color::GRAY);
+//!     let created = batch.run()?;
+//!
+//!     let title = created.get(title)?;
+//!     title.set_text(&mut activity, "Settings (20)")?;
+//!     Ok(())
+//! }
+//! ```
+
+use std::collections::VecDeque;
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicU64, Ordering};
+use serde_json::{json, Map, Value};
+use crate::activity::Activity;
+use crate::builder::{create_params, sealed::Create};
+use crate::color::Color;
+use crate::connection::Pipeline;
+use crate::components::{
+    Button, Checkbox, EditText, FrameLayout, GridLayout, HorizontalScrollView, ImageView,
+    LinearLayout, NestedScrollView, ProgressBar, RadioButton, RadioGroup, Space, Spinner,
+    Switch, SwipeRefreshLayout, TabLayout, TextView, ToggleButton, WebView,
+};
+use crate::dimension::Dimension;
+use crate::error::{GuiError, Result};
+use crate::view::{self, View, Visibility};
+use crate::widget::Widget;
+
+/// Maximum number of responses left unread before the batch reads one
+///
+/// Keeps the socket buffers from filling up on both sides.
+const MAX_IN_FLIGHT: usize = 64;
+
+/// Source of unique batch IDs, so placeholders can't be used across batches
+static NEXT_BATCH: AtomicU64 = AtomicU64::new(0);
+
+/// A widget that will be created when its batch runs
+///
+/// Copy it freely to refer to the widget within the batch; get the widget
+/// with [`BatchResult::get`] afterwards.
+pub struct Pending<W> {
+    batch: u64,
+    index: usize,
+    widget: PhantomData<fn() -> W>,
+}
+
+impl<W> Clone for Pending<W> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<W> Copy for Pending<W> {}
+
+impl<W> std::fmt::Debug for Pending<W> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Pending")
+            .field("batch", &self.batch)
+            .field("index", &self.index)
+            .finish()
+    }
+}
+
+/// A view in a batch: an existing one or a [`Pending`] one
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ViewRef(Target);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Target {
+    Id(i64),
+    Pending { batch: u64, index: usize },
+}
+
+impl From<i64> for ViewRef {
+    fn from(id: i64) -> Self {
+        ViewRef(Target::Id(id))
+    }
+}
+
+impl<W> From<Pending<W>> for ViewRef {
+    fn from(pending: Pending<W>) -> Self {
+        ViewRef(Target::Pending { batch: pending.batch, index: pending.index })
+    }
+}
+
+impl<W: Widget> From<&W> for ViewRef {
+    fn from(widget: &W) -> Self {
+        ViewRef(Target::Id(widget.id()))
+    }
+}
+
+/// The parent of a widget created in a batch
+///
+/// Converts from `None`, a view ID, a [`Pending`] widget or a reference to
+/// an existing widget.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Parent(Option<ViewRef>);
+
+impl From<Option<i64>> for Parent {
+    fn from(parent: Option<i64>) -> Self {
+        Parent(parent.map(ViewRef::from))
+    }
+}
+
+impl<T: Into<ViewRef>> From<T> for Parent {
+    fn from(parent: T) -> Self {
+        Parent(Some(parent.into()))
+    }
+}
+
+/// A queued request
+struct Op {
+    method: &'static str,
+    params: Map<String, Value>,
+    /// Params to fill in with the ID of an earlier create, by op index
+    refs: Vec<(&'static str, usize)>,
+    /// Whether the request is a create, which the service answers with an ID
+    create: bool,
+}
+
+/// Create and set calls queued to be sent together
+///
+/// Created by [`Activity::batch`]. Nothing is sent until
+/// [`run`](Self::run); dropping the batch discards the queued calls.
+pub struct Batch<'a> {
+    activity: &'a mut Activity,
+    id: u64,
+    ops: Vec<Op>,
+    error: Option<GuiError>,
+}
+
+impl<'a> Batch<'a> {
+    pub(crate) fn new(activity: &'a mut Activity) -> Self {
+        Batch {
+            activity,
+            id: NEXT_BATCH.fetch_add(1, Ordering::Relaxed),
+            ops: Vec::new(),
+            error: None,
+        }
+    }
+
+    /// Get the number of queued calls
+    pub fn len(&self) -> usize {
+        self.ops.len()
+    }
+
+    /// Check whether no calls are queued
+    pub fn is_empty(&self) -> bool {
+        self.ops.is_empty()
+    }
+
+    /// Queue a `create*` call for a `W` with the given creation parameters
+    ///
+    /// `params` are the protocol parameters besides `aid` and `parent`,
+    /// e.g. `json!({"text": "OK"})` for a Button. Parameters left out get
+    /// the same defaults as the component's constructor.
+    pub fn create<W: Create>(&mut self, params: Value, parent: impl Into<Parent>) -> Pending<W> {
+        let params = create_params::<W>(params);
+        let mut op = Op {
+            method: W::METHOD,
+            params,
+            refs: Vec::new(),
+            create: true,
+        };
+        if let Parent(Some(parent)) = parent.into() {
+            self.set_ref(&mut op, "parent", parent);
+        }
+        self.ops.push(op);
+        Pending {
+            batch: self.id,
+            index: self.ops.len() - 1,
+            widget: PhantomData,
+        }
+    }
+
+    /// Queue a call without a response on the view `view`
+    ///
+    /// `params` are the protocol parameters besides `aid` and `id`.
+    pub fn call(&mut self, method: &'static str, view: impl Into<ViewRef>, params: Value) {
+        let params = match params {
+            Value::Object(map) => map,
+            _ => Map::new(),
+        };
+        let mut op = Op {
+            method,
+            params,
+            refs: Vec::new(),
+            create: false,
+        };
+        self.set_ref(&mut op, "id", view.into());
+        self.ops.push(op);
+    }
+
+    fn set_ref(&mut self, op: &mut Op, key: &'static str, view: ViewRef) {
+        match view.0 {
+            Target::Id(id) => {
+                op.params.insert(key.to_string(), json!(id));
+            }
+            Target::Pending { batch, index } if batch == self.id => op.refs.push((key, index)),
+            Target::Pending { .. } => {
+                self.error.get_or_insert_with(|| {
+                    GuiError::InvalidOperation(format!(
+                        "{} refers to a widget pending in another batch",
+                        op.method
+                    ))
+                });
+            }
+        }
+    }
+
+    /// Record the first error of a setter, reported by [`run`](Self::run)
+    fn fail(&mut self, error: GuiError) {
+        self.error.get_or_insert(error);
+    }
+
+    /// Send all queued calls and collect the IDs of the created widgets
+    ///
+    /// Fails without sending anything if a call was queued with invalid
+    /// arguments. If a request fails midway, the calls before it have
+    /// already been applied.
+    pub fn run(self) -> Result<BatchResult> {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+        self.activity.check_alive()?;
+
+        let aid = self.activity.id();
+        let gui = self.activity.gui().clone();
+        let mut conn = gui.conn();
+        let mut pipeline = conn.pipeline();
+
+        let mut ids: Vec<Option<i64>> = vec![None; self.ops.len()];
+        let mut waiting = VecDeque::new();
+        let mut created = Vec::with_capacity(self.ops.len());
+        for (index, op) in self.ops.into_iter().enumerate() {
+            let mut params = op.params;
+            params.insert("aid".to_string(), json!(aid));
+            for (key, target) in op.refs {
+                // Only wait for the responses up to the one we need
+                while ids.get(target).is_some_and(Option::is_none) && !waiting.is_empty() {
+                    read_id(&mut pipeline, &mut ids, &mut waiting)?;
+                }
+                let id = ids.get(target).copied().flatten().ok_or_else(|| {
+                    GuiError::InvalidOperation(format!("{} refers to a widget created after it", op.method))
+                })?;
+                params.insert(key.to_string(), json!(id));
+            }
+
+            let msg = json!({
+                "method": op.method,
+                "params": params
+            });
+            if op.create {
+                pipeline.send_read(&msg)?;
+                created.push((index, msg["params"].clone()));
+                waiting.push_back((index, msg));
+                if waiting.len() >= MAX_IN_FLIGHT {
+                    read_id(&mut pipeline, &mut ids, &mut waiting)?;
+                }
+            } else {
+                pipeline.send(&msg)?;
+            }
+        }
+        while !waiting.is_empty() {
+            read_id(&mut pipeline, &mut ids, &mut waiting)?;
+        }
+
+        let mut views = vec![None; ids.len()];
+        for (index, params) in created {
+            views[index] = ids[index].map(|id| (id, params));
+        }
+        Ok(BatchResult { aid, batch: self.id, views })
+    }
+}
+
+/// Read the response to the oldest pending create
+fn read_id(pipeline: &mut Pipeline<'_>, ids: &mut [Option<i64>], waiting: &mut VecDeque<(usize, Value)>) -> Result<()> {
+    let (index, msg) = waiting.pop_front().ok_or_else(|| {
+        GuiError::InvalidOperation("no create is waiting for a response".to_string())
+    })?;
+    let response = pipeline.read()?;
+    let id = response
+        .as_i64()
+        .ok_or_else(|| GuiError::invalid_response(&msg, &response))?;
+    ids[index] = Some(id);
+    Ok(())
+}
+
+/// Setters for common properties, queued like [`Batch::call`]
+impl Batch<'_> {
+    /// Queue setting the text of a text widget
+    pub fn set_text(&mut self, view: impl Into<ViewRef>, text: &str) {
+        self.call("setText", view, json!({ "text": text }));
+    }
+
+    /// Queue setting the text size in sp
+    pub fn set_text_size(&mut self, view: impl Into<ViewRef>, size: i32) {
+        self.call("setTextSize", view, json!({ "size": size }));
+    }
+
+    /// Queue setting the text colour
+    pub fn set_text_color(&mut self, view: impl Into<ViewRef>, color: impl Into<Color>) {
+        self.call("setTextColor", view, json!({ "color": color.into().to_i32() }));
+    }
+
+    /// Queue setting the hint of an EditText
+    pub fn set_hint(&mut self, view: impl Into<ViewRef>, hint: &str) {
+        self.call("setHint", view, json!({ "hint": hint }));
+    }
+
+    /// Queue setting the checked state
+    pub fn set_checked(&mut self, view: impl Into<ViewRef>, checked: bool) {
+        self.call("setChecked", view, json!({ "checked": checked }));
+    }
+
+    /// Queue setting the progress of a ProgressBar (0-100)
+    pub fn set_progress(&mut self, view: impl Into<ViewRef>, progress: i32) {
+        self.call("setProgress", view, json!({ "progress": progress.clamp(0, 100) }));
+    }
+
+    /// Queue setting the background colour
+    pub fn set_background_color(&mut self, view: impl Into<ViewRef>, color: impl Into<Color>) {
+        self.call("setBackgroundColor", view, json!({ "color": color.into().to_i32() }));
+    }
+
+    /// Queue showing or hiding a view
+    pub fn set_visibility(&mut self, view: impl Into<ViewRef>, visibility: Visibility) {
+        self.call("setVisibility", view, json!({ "vis": visibility.as_i32() }));
+    }
+
+    /// Queue setting the width, see [`View::set_width`]
+    pub fn set_width(&mut self, view: impl Into<ViewRef>, width: impl Into<Dimension>) {
+        match view::size_param(self.activity, width.into()) {
+            Ok((width, px)) => self.call("setWidth", view, json!({ "width": width, "px": px })),
+            Err(e) => self.fail(e),
+        }
+    }
+
+    /// Queue setting the height, see [`View::set_height`]
+    pub fn set_height(&mut self, view: impl Into<ViewRef>, height: impl Into<Dimension>) {
+        match view::size_param(self.activity, height.into()) {
+            Ok((height, px)) => self.call("setHeight", view, json!({ "height": height, "px": px })),
+            Err(e) => self.fail(e),
+        }
+    }
+
+    /// Queue setting the margin on all sides
+    pub fn set_margin(&mut self, view: impl Into<ViewRef>, margin: impl Into<Dimension>) {
+        match view::dp_param(self.activity, margin.into(), "margin") {
+            Ok(margin) => self.call("setMargin", view, json!({ "margin": margin })),
+            Err(e) => self.fail(e),
+        }
+    }
+
+    /// Queue setting the padding on all sides
+    pub fn set_padding(&mut self, view: impl Into<ViewRef>, padding: impl Into<Dimension>) {
+        match view::dp_param(self.activity, padding.into(), "padding") {
+            Ok(padding) => self.call("setPadding", view, json!({ "padding": padding })),
+            Err(e) => self.fail(e),
+        }
+    }
+
+    /// Queue setting the weight inside a LinearLayout
+    pub fn set_linear_layout_params(&mut self, view: impl Into<ViewRef>, weight: i32) {
+        self.call("setLinearLayoutParams", view, json!({ "weight": weight }));
+    }
+}
+
+/// Create calls matching the `create_*` methods of [`Activity`]
+impl Batch<'_> {
+    /// Queue creating a vertical LinearLayout
+    pub fn create_linear_layout(&mut self, parent: impl Into<Parent>) -> Pending<LinearLayout> {
+        self.create(json!({}), parent)
+    }
+
+    /// Queue creating a horizontal LinearLayout
+    pub fn create_linear_layout_horizontal(&mut self, parent: impl Into<Parent>) -> Pending<LinearLayout> {
+        self.create(json!({ "vertical": false }), parent)
+    }
+
+    /// Queue creating a NestedScrollView
+    pub fn create_nested_scroll_view(&mut self, parent: impl Into<Parent>) -> Pending<NestedScrollView> {
+        self.create(json!({}), parent)
+    }
+
+    /// Queue creating a HorizontalScrollView
+    pub fn create_horizontal_scroll_view(&mut self, parent: impl Into<Parent>) -> Pending<HorizontalScrollView> {
+        self.create(json!({}), parent)
+    }
+
+    /// Queue creating a FrameLayout
+    pub fn create_frame_layout(&mut self, parent: impl Into<Parent>) -> Pending<FrameLayout> {
+        self.create(json!({}), parent)
+    }
+
+    /// Queue creating a GridLayout
+    pub fn create_grid_layout(&mut self, rows: i32, cols: i32, parent: impl Into<Parent>) -> Pending<GridLayout> {
+        self.create(json!({ "rows": rows, "cols": cols }), parent)
+    }
+
+    /// Queue creating a SwipeRefreshLayout
+    pub fn create_swipe_refresh_layout(&mut self, parent: impl Into<Parent>) -> Pending<SwipeRefreshLayout> {
+        self.create(json!({}), parent)
+    }
+
+    /// Queue creating a TabLayout
+    pub fn create_tab_layout(&mut self, parent: impl Into<Parent>) -> Pending<TabLayout> {
+        self.create(json!({}), parent)
+    }
+
+    /// Queue creating a TextView
+    pub fn create_text_view(&mut self, text: &str, parent: impl Into<Parent>) -> Pending<TextView> {
+        self.create(json!({ "text": text }), parent)
+    }
+
+    /// Queue creating a Button
+    pub fn create_button(&mut self, text: &str, parent: impl Into<Parent>) -> Pending<Button> {
+        self.create(json!({ "text": text }), parent)
+    }
+
+    /// Queue creating a single-line EditText
+    pub fn create_edit_text(&mut self, text: &str, parent: impl Into<Parent>) -> Pending<EditText> {
+        self.create(json!({ "text": text }), parent)
+    }
+
+    /// Queue creating a Checkbox
+    pub fn create_checkbox(&mut self, text: &str, parent: impl Into<Parent>) -> Pending<Checkbox> {
+        self.create(json!({ "text": text }), parent)
+    }
+
+    /// Queue creating a Switch
+    pub fn create_switch(&mut self, text: &str, parent: impl Into<Parent>) -> Pending<Switch> {
+        self.create(json!({ "text": text }), parent)
+    }
+
+    /// Queue creating a RadioButton
+    pub fn create_radio_button(&mut self, text: &str, parent: impl Into<Parent>) -> Pending<RadioButton> {
+        self.create(json!({ "text": text }), parent)
+    }
+
+    /// Queue creating a RadioGroup
+    pub fn create_radio_group(&mut self, parent: impl Into<Parent>) -> Pending<RadioGroup> {
+        self.create(json!({}), parent)
+    }
+
+    /// Queue creating a ToggleButton
+    pub fn create_toggle_button(&mut self, text: &str, parent: impl Into<Parent>) -> Pending<ToggleButton> {
+        self.create(json!({ "text": text }), parent)
+    }
+
+    /// Queue creating a Spinner
+    pub fn create_spinner(&mut self, parent: impl Into<Parent>) -> Pending<Spinner> {
+        self.create(json!({}), parent)
+    }
+
+    /// Queue creating an ImageView
+    pub fn create_image_view(&mut self, parent: impl Into<Parent>) -> Pending<ImageView> {
+        self.create(json!({}), parent)
+    }
+
+    /// Queue creating a ProgressBar
+    pub fn create_progress_bar(&mut self, parent: impl Into<Parent>) -> Pending<ProgressBar> {
+        self.create(json!({}), parent)
+    }
+
+    /// Queue creating a Space
+    pub fn create_space(&mut self, parent: impl Into<Parent>) -> Pending<Space> {
+        self.create(json!({}), parent)
+    }
+
+    /// Queue creating a WebView
+    pub fn create_web_view(&mut self, parent: impl Into<Parent>) -> Pending<WebView> {
+        self.create(json!({}), parent)
+    }
+}
+
+/// The widgets created by a [`Batch`]
+#[derive(Debug, Clone)]
+pub struct BatchResult {
+    aid: i64,
+    batch: u64,
+    /// View ID and creation parameters, by op index
+    views: Vec<Option<(i64, Value)>>,
+}
+
+impl BatchResult {
+    /// Get the view ID of a widget created by the batch
+    pub fn id<W>(&self, pending: Pending<W>) -> Result<i64> {
+        self.view(pending).map(|(id, _)| *id)
+    }
+
+    /// Get a widget created by the batch
+    pub fn get<W: Create>(&self, pending: Pending<W>) -> Result<W> {
+        let (id, params) = self.view(pending)?;
+        Ok(W::from_parts(View::new(*id), self.aid, params))
+    }
+
+    fn view<W>(&self, pending: Pending<W>) -> Result<&(i64, Value)> {
+        if pending.batch != self.batch {
+            return Err(GuiError::InvalidOperation(
+                "widget is pending in another batch".to_string(),
+            ));
+        }
+        self.views
+            .get(pending.index)
+            .and_then(Option::as_ref)
+            .ok_or_else(|| GuiError::InvalidOperation("widget was not created".to_string()))
+    }
+}
+
+impl Activity {
+    /// Start queueing calls to send together, see [`Batch`]
+    pub fn batch(&mut self) -> Batch<'_> {
+        Batch::new(self)
+    }
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use super::*;
+    use crate::testing::MockService;
+
+    fn methods(mock: &MockService) -> Vec<String> {
+        mock.calls().iter().map(|call| call["method"].as_str().unwrap().to_string()).collect()
+    }
+
+    #[test]
+    fn placeholders_resolve_to_created_ids() -> Result<()> {
+        let (mock, mut activity) = MockService::activity(false)?;
+        let aid = activity.id();
+        let existing = activity.create_linear_layout(None)?;
+        mock.clear_calls();
+
+        let mut batch = activity.batch();
+        let layout = batch.create_linear_layout(&existing);
+        let title = batch.create_text_view("Title", layout);
+        batch.set_text_size(title, 24);
+        let ok = batch.create_button("OK", layout);
+        batch.set_checked(existing.id(), true);
+        assert_eq!(batch.len(), 5);
+        let created = batch.run()?;
+
+        assert_eq!(
+            methods(&mock),
+            ["createLinearLayout", "createTextView", "setTextSize", "createButton", "setChecked"]
+        );
+        let layout_id = created.id(layout)?;
+        assert_eq!(mock.calls_to("createLinearLayout")[0]["parent"], json!(existing.id()));
+        assert_eq!(mock.calls_to("createTextView")[0]["parent"], json!(layout_id));
+        assert_eq!(mock.calls_to("createButton")[0]["parent"], json!(layout_id));
+        assert_eq!(mock.calls_to("createButton")[0]["allcaps"], json!(false));
+        assert_eq!(mock.calls_to("setTextSize")[0], json!({ "aid": aid, "id": created.id(title)?, "size": 24 }));
+        assert_eq!(mock.calls_to("setChecked")[0]["id"], json!(existing.id()));
+
+        let button = created.get(ok)?;
+        assert_eq!((button.aid(), button.id()), (aid, created.id(ok)?));
+        Ok(())
+    }
+
+    #[test]
+    fn long_batches_keep_responses_in_order() -> Result<()> {
+        let (mock, mut activity) = MockService::activity(false)?;
+        let mut batch = activity.batch();
+        let layout = batch.create_linear_layout(None);
+        let rows: Vec<_> = (0..2 * MAX_IN_FLIGHT + 3)
+            .map(|i| batch.create_text_view(&format!("Row {}", i), layout))
+            .collect();
+        let last = *rows.last().unwrap();
+        batch.set_text(last, "Last");
+        let created = batch.run()?;
+
+        let ids: Vec<_> = rows.iter().map(|row| created.id(*row)).collect::<Result<_>>()?;
+        let created_ids: Vec<_> = (1..=rows.len() as i64).collect();
+        assert_eq!(ids, created_ids);
+        assert_eq!(mock.calls_to("setText")[0]["id"], json!(created.id(last)?));
+        Ok(())
+    }
+
+    #[test]
+    fn placeholders_from_another_batch_are_rejected() -> Result<()> {
+        let (mock, mut activity) = MockService::activity(false)?;
+        let mut first = activity.batch();
+        let layout = first.create_linear_layout(None);
+        let first = first.run()?;
+        mock.clear_calls();
+
+        let mut second = activity.batch();
+        second.create_text_view("Orphan", layout);
+        let error = second.run().unwrap_err();
+        assert!(matches!(error, GuiError::InvalidOperation(_)));
+        assert!(mock.calls().is_empty());
+
+        let mut third = activity.batch();
+        let space = third.create_space(None);
+        let third = third.run()?;
+        assert!(first.id(space).is_err());
+        assert!(third.id(space).is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_setter_arguments_fail_before_sending() -> Result<()> {
+        let (mock, mut activity) = MockService::activity(false)?;
+        mock.clear_calls();
+        let mut batch = activity.batch();
+        let text = batch.create_text_view("Text", None);
+        batch.set_margin(text, Dimension::MatchParent);
+        assert!(batch.run().is_err());
+        assert!(mock.calls().is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn non_integer_create_replies_stop_the_batch() -> Result<()> {
+        let (mock, mut activity) = MockService::activity(false)?;
+        mock.set_response("createButton", json!("nope"));
+        let mut batch = activity.batch();
+        let button = batch.create_button("OK", None);
+        batch.set_text(button, "Never sent");
+
+        let error = batch.run().unwrap_err();
+        assert!(matches!(error.root(), GuiError::InvalidResponse { method, .. } if method == "createButton"));
+        assert!(mock.calls_to("setText").is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn empty_batches_send_nothing() -> Result<()> {
+        let (mock, mut activity) = MockService::activity(false)?;
+        mock.clear_calls();
+        let batch = activity.batch();
+        assert!(batch.is_empty());
+        batch.run()?;
+        assert!(mock.calls().is_empty());
+        Ok(())
+    }
+}
@@ -0,0 +1,615 @@
+//! Reactive state bindings
+//!
+//! A [`Signal`] holds a piece of app state. Widgets bound to it are updated
+//! as soon as the value changes, so state can be changed in one place
+//! instead of calling `set_text`/`set_checked` everywhere it is shown.
+//!
+//! One-way bindings (`bind_*`) push the value to the widget. Two-way
+//! bindings (`bind_*_two_way`) also register a handler on an
+//! [`EventLoop`] that writes edits made by the user back into the signal,
+//! which in turn updates every other widget bound to it.
+//!
+//! Signals are cheap to clone (all clones share the value) and can be set
+//! from worker threads, since bindings send through an
+//! [`ActivityHandle`](crate::ActivityHandle). Bindings to an Activity that
+//! has been destroyed are dropped on the next change.
+//!
+//! ## Example
+//!
+//! ```rust,no_run
+//! use termux_gui::{Activity, EventLoop, Result, Signal, Visibility};
+//!
+//! fn main() -> Result<()> {
+//!     let mut activity = Activity::new(false)?;
+//!     let layout = activity.create_linear_layout(None)?;
+//!     let input = activity.create_edit_text("", Some(layout.id()))?;
+//!     let echo = activity.create_text_view("", Some(layout.id()))?;
+//!     let hint = activity.create_text_view("Type something", Some(layout.id()))?;
+//!
+//!     let name = Signal::new(String::new());
+//!     let mut events = EventLoop::new();
+//!
+//!     // Typing into `input` updates `name`, which updates `echo`
+//!     name.bind_text_two_way(&mut activity, &mut events, &input)?;
+//!     name.bind_text(&mut activity, &echo)?;
+//!
+//!     // Derived state: hide the hint once something was typed
+//!     name.map(|text| if text.is_empty() { Visibility::Visible } else { Visibility::Gone })
+//!         .bind_visibility(&mut activity, &hint)?;
+//!
+//!     events.run(&mut activity)
+//! }
+//! ```
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use crate::activity::{Activity, ActivityHandle};
+use crate::components::{EditText, ProgressBar, Spinner};
+use crate::error::{GuiError, Result};
+use crate::event_loop::EventLoop;
+use crate::raw;
+use crate::view::Visibility;
+use crate::widget::{CheckableWidget, TextWidget, Widget};
+
+type Subscriber<T> = Box<dyn FnMut(&T) -> Result<()> + Send>;
+
+/// Source of unique subscription IDs
+static NEXT_SUBSCRIPTION: AtomicU64 = AtomicU64::new(0);
+
+/// Identifies a subscriber, for [`Signal::unsubscribe`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Subscription(u64);
+
+struct SignalInner<T> {
+    value: T,
+    subscribers: Vec<(Subscription, Subscriber<T>)>,
+    /// Whether a `set` is notifying the subscribers, which it holds meanwhile
+    notifying: bool,
+    /// Set when the value changed during a notification, with the
+    /// subscriber to skip for the new value
+    changed: Option<Option<Subscription>>,
+    /// Subscriptions removed during a notification
+    removed: Vec<Subscription>,
+}
+
+/// An observable value
+///
+/// Clones share the same value and subscribers.
+pub struct Signal<T> {
+    inner: Arc<Mutex<SignalInner<T>>>,
+}
+
+impl<T> Clone for Signal<T> {
+    fn clone(&self) -> Self {
+        Signal { inner: Arc::clone(&self.inner) }
+    }
+}
+
+impl<T: Default + Clone + PartialEq + Send + 'static> Default for Signal<T> {
+    fn default() -> Self {
+        Signal::new(T::default())
+    }
+}
+
+impl<T: std::fmt::Debug> std::fmt::Debug for Signal<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("Signal").field(&lock(&self.inner).value).finish()
+    }
+}
+
+impl<T: Clone + PartialEq + Send + 'static> Signal<T> {
+    /// Create a signal holding `value`
+    pub fn new(value: T) -> Self {
+        Signal {
+            inner: Arc::new(Mutex::new(SignalInner {
+                value,
+                subscribers: Vec::new(),
+                notifying: false,
+                changed: None,
+                removed: Vec::new(),
+            })),
+        }
+    }
+
+    /// Get a copy of the current value
+    pub fn get(&self) -> T {
+        lock(&self.inner).value.clone()
+    }
+
+    /// Call `f` with the current value without copying it
+    pub fn with<R>(&self, f: impl FnOnce(&T) -> R) -> R {
+        f(&lock(&self.inner).value)
+    }
+
+    /// Change the value and notify subscribers if it differs
+    ///
+    /// Subscribers see one value at a time. If another `set` is notifying
+    /// them, e.g. on another thread, this returns at once and that call
+    /// passes the new value on after the current one.
+    ///
+    /// Returns the first error of a subscriber; the others are still
+    /// notified.
+    pub fn set(&self, value: T) -> Result<()> {
+        self.set_except(value, None)
+    }
+
+    /// Change the value in place and notify subscribers if it differs
+    pub fn update(&self, f: impl FnOnce(&mut T)) -> Result<()> {
+        let mut value = self.get();
+        f(&mut value);
+        self.set(value)
+    }
+
+    /// Call `f` with every new value
+    ///
+    /// `f` is not called with the current value.
+    pub fn subscribe<F>(&self, f: F) -> Subscription
+    where
+        F: FnMut(&T) -> Result<()> + Send + 'static,
+    {
+        let subscription = Subscription(NEXT_SUBSCRIPTION.fetch_add(1, Ordering::Relaxed));
+        lock(&self.inner).subscribers.push((subscription, Box::new(f)));
+        subscription
+    }
+
+    /// Stop notifying a subscriber, including bindings
+    pub fn unsubscribe(&self, subscription: Subscription) {
+        let mut inner = lock(&self.inner);
+        inner.subscribers.retain(|(s, _)| *s != subscription);
+        if inner.notifying {
+            inner.removed.push(subscription);
+        }
+    }
+
+    /// Create a signal that follows this one through `f`
+    pub fn map<U, F>(&self, f: F) -> Signal<U>
+    where
+        U: Clone + PartialEq + Send + 'static,
+        F: Fn(&T) -> U + Send + 'static,
+    {
+        let derived = Signal::new(self.with(&f));
+        let target = derived.clone();
+        self.subscribe(move |value| target.set(f(value)));
+        derived
+    }
+
+    /// Change the value, skipping the subscriber that reported the change
+    fn set_except(&self, value: T, skip: Option<Subscription>) -> Result<()> {
+        let (mut subscribers, mut value, mut skip) = {
+            let mut inner = lock(&self.inner);
+            if inner.value == value {
+                return Ok(());
+            }
+            inner.value = value.clone();
+            if inner.notifying {
+                // The running notification passes the new value on
+                inner.changed = Some(skip);
+                return Ok(());
+            }
+            inner.notifying = true;
+            // Subscribers run unlocked so they can read and set the signal
+            (std::mem::take(&mut inner.subscribers), value, skip)
+        };
+
+        let _guard = NotifyGuard(&self.inner);
+        let mut result = Ok(());
+        loop {
+            subscribers.retain_mut(|(subscription, subscriber)| {
+                if Some(*subscription) == skip {
+                    return true;
+                }
+                match subscriber(&value) {
+                    Ok(()) => true,
+                    Err(e) if matches!(e.root(), GuiError::ActivityDestroyed(_)) => false,
+                    Err(e) => {
+                        if result.is_ok() {
+                            result = Err(e);
+                        }
+                        true
+                    }
+                }
+            });
+
+            let mut inner = lock(&self.inner);
+            subscribers.append(&mut inner.subscribers);
+            let removed = std::mem::take(&mut inner.removed);
+            subscribers.retain(|(s, _)| !removed.contains(s));
+            match inner.changed.take() {
+                Some(next_skip) => {
+                    value = inner.value.clone();
+                    skip = next_skip;
+                }
+                None => {
+                    inner.subscribers = subscribers;
+                    inner.notifying = false;
+                    return result;
+                }
+            }
+        }
+    }
+
+    /// Send the current value now and on every change
+    fn bind_with<F>(&self, activity: &mut Activity, mut send: F) -> Result<Subscription>
+    where
+        F: FnMut(&mut ActivityHandle, &T) -> Result<()> + Send + 'static,
+    {
+        let mut handle = activity.handle();
+        self.with(|value| send(&mut handle, value))?;
+        Ok(self.subscribe(move |value| send(&mut handle, value)))
+    }
+}
+
+impl Signal<String> {
+    /// Show the value as the text of `widget`
+    pub fn bind_text(&self, activity: &mut Activity, widget: &impl TextWidget) -> Result<Subscription> {
+        let (aid, id) = (widget.aid(), widget.id());
+        self.bind_with(activity, move |handle, text| {
+            raw::set_text(handle, &raw::SetText { aid, id, text: text.clone() })
+        })
+    }
+
+    /// Keep the value and the text of an EditText in sync
+    pub fn bind_text_two_way<'h>(
+        &self,
+        activity: &mut Activity,
+        events: &mut EventLoop<'h>,
+        widget: &EditText,
+    ) -> Result<Subscription> {
+        let subscription = self.bind_text(activity, widget)?;
+        let signal = self.clone();
+        // Don't echo the text back to the field the user is typing in
+        events.on_text(widget, move |_ctx, text| signal.set_except(text.to_string(), Some(subscription)));
+        Ok(subscription)
+    }
+}
+
+impl Signal<bool> {
+    /// Show the value as the checked state of `widget`
+    pub fn bind_checked(&self, activity: &mut Activity, widget: &impl CheckableWidget) -> Result<Subscription> {
+        let (aid, id) = (widget.aid(), widget.id());
+        self.bind_with(activity, move |handle, checked| {
+            raw::set_checked(handle, &raw::SetChecked { aid, id, checked: *checked })
+        })
+    }
+
+    /// Keep the value and the checked state of `widget` in sync
+    pub fn bind_checked_two_way<'h>(
+        &self,
+        activity: &mut Activity,
+        events: &mut EventLoop<'h>,
+        widget: &impl CheckableWidget,
+    ) -> Result<Subscription> {
+        let subscription = self.bind_checked(activity, widget)?;
+        let signal = self.clone();
+        events.on_checked(widget, move |_ctx, checked| signal.set_except(checked, Some(subscription)));
+        Ok(subscription)
+    }
+}
+
+impl Signal<i32> {
+    /// Show the value as the progress of a ProgressBar (0-100)
+    pub fn bind_progress(&self, activity: &mut Activity, widget: &ProgressBar) -> Result<Subscription> {
+        let (aid, id) = (widget.aid(), widget.id());
+        self.bind_with(activity, move |handle, progress| {
+            let progress = (*progress).clamp(0, 100).into();
+            raw::set_progress(handle, &raw::SetProgress { aid, id, progress })
+        })
+    }
+
+    /// Show the value as the selected item index of a Spinner
+    pub fn bind_selection(&self, activity: &mut Activity, widget: &Spinner) -> Result<Subscription> {
+        let (aid, id) = (widget.aid(), widget.id());
+        self.bind_with(activity, move |handle, index| {
+            raw::select_item(handle, &raw::SelectItem { aid, id, item: (*index).into() })
+        })
+    }
+
+    /// Keep the value and the selected item of a Spinner in sync
+    ///
+    /// Spinners report the text of the selected item, so `items` must be
+    /// the list the Spinner was filled with.
+    pub fn bind_selection_two_way<'h>(
+        &self,
+        activity: &mut Activity,
+        events: &mut EventLoop<'h>,
+        widget: &Spinner,
+        items: &[&str],
+    ) -> Result<Subscription> {
+        let subscription = self.bind_selection(activity, widget)?;
+        let signal = self.clone();
+        let items: Vec<String> = items.iter().map(|item| item.to_string()).collect();
+        events.on_item_selected(widget, move |_ctx, selected| {
+            let index = match selected.as_text() {
+                Some(text) => items.iter().position(|item| item == text).map(|i| i as i64),
+                None => selected.as_index(),
+            };
+            match index {
+                Some(index) => signal.set_except(index as i32, Some(subscription)),
+                None => Ok(()),
+            }
+        });
+        Ok(subscription)
+    }
+}
+
+impl Signal<Visibility> {
+    /// Show the value as the visibility of `widget`
+    pub fn bind_visibility(&self, activity: &mut Activity, widget: &impl Widget) -> Result<Subscription> {
+        let (aid, id) = (widget.aid(), widget.id());
+        self.bind_with(activity, move |handle, visibility| {
+            raw::set_visibility(handle, &raw::SetVisibility { aid, id, vis: visibility.as_i32().into() })
+        })
+    }
+}
+
+/// Ends a notification left by a panicking subscriber, so later changes
+/// are still delivered to the subscribers that remain
+struct NotifyGuard<'a, T>(&'a Mutex<SignalInner<T>>);
+
+impl<T> Drop for NotifyGuard<'_, T> {
+    fn drop(&mut self) {
+        if std::thread::panicking() {
+            let mut inner = lock(self.0);
+            inner.notifying = false;
+            inner.changed = None;
+            inner.removed.clear();
+        }
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicUsize;
+
+    use super::*;
+
+    #[test]
+    fn subscribers_see_changes_only() -> Result<()> {
+        let signal = Signal::new(1);
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let log = Arc::clone(&seen);
+        let subscription = signal.subscribe(move |value| {
+            lock(&log).push(*value);
+            Ok(())
+        });
+
+        signal.set(2)?;
+        signal.set(2)?;
+        signal.update(|value| *value += 1)?;
+        signal.unsubscribe(subscription);
+        signal.set(4)?;
+        assert_eq!(*lock(&seen), [2, 3]);
+        assert_eq!(signal.get(), 4);
+        Ok(())
+    }
+
+    #[test]
+    fn clones_share_the_value() -> Result<()> {
+        let signal = Signal::new(String::from("a"));
+        let clone = signal.clone();
+        clone.set("b".to_string())?;
+        assert_eq!(signal.get(), "b");
+        assert_eq!(signal.with(String::len), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn mapped_signals_follow_their_source() -> Result<()> {
+        let count = Signal::new(0);
+        let label = count.map(|n| format!("{} items", n));
+        assert_eq!(label.get(), "0 items");
+        count.set(3)?;
+        assert_eq!(label.get(), "3 items");
+        Ok(())
+    }
+
+    #[test]
+    fn subscriber_errors_are_reported_after_notifying_all() {
+        let signal = Signal::new(false);
+        let calls = Arc::new(AtomicUsize::new(0));
+        signal.subscribe(|_| Err(GuiError::InvalidOperation("first".into())));
+        let counter = Arc::clone(&calls);
+        signal.subscribe(move |_| {
+            counter.fetch_add(1, Ordering::Relaxed);
+            Ok(())
+        });
+
+        let error = signal.set(true).unwrap_err();
+        assert!(matches!(error, GuiError::InvalidOperation(message) if message == "first"));
+        assert_eq!(calls.load(Ordering::Relaxed), 1);
+        // Failing subscribers other than destroyed Activities are kept
+        assert!(signal.set(false).is_err());
+    }
+
+    #[test]
+    fn subscribers_may_read_the_signal() -> Result<()> {
+        let signal = Signal::new(0);
+        let reader = signal.clone();
+        let seen = Arc::new(Mutex::new(None));
+        let log = Arc::clone(&seen);
+        signal.subscribe(move |_| {
+            *lock(&log) = Some(reader.get());
+            Ok(())
+        });
+        signal.set(5)?;
+        assert_eq!(*lock(&seen), Some(5));
+        Ok(())
+    }
+
+    #[test]
+    fn a_set_during_a_notification_is_delivered() -> Result<()> {
+        use std::sync::mpsc;
+
+        let signal = Signal::new(0);
+        let shown = Arc::new(Mutex::new(Vec::new()));
+        let (started, wait_started) = mpsc::channel();
+        let (go, wait_go) = mpsc::channel::<()>();
+        let screen = Arc::clone(&shown);
+        signal.subscribe(move |value| {
+            if *value == 1 {
+                // Hold the first notification until the other thread has set
+                started.send(()).unwrap();
+                wait_go.recv().unwrap();
+            }
+            lock(&screen).push(*value);
+            Ok(())
+        });
+
+        let worker = {
+            let signal = signal.clone();
+            std::thread::spawn(move || signal.set(1))
+        };
+        wait_started.recv().unwrap();
+        signal.set(2)?;
+        go.send(()).unwrap();
+        worker.join().unwrap()?;
+
+        assert_eq!(*lock(&shown), [1, 2]);
+        assert_eq!(signal.get(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn subscribers_can_unsubscribe_during_a_notification() -> Result<()> {
+        let signal = Signal::new(0);
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&calls);
+        let own = Arc::new(Mutex::new(None));
+        let (this, source) = (Arc::clone(&own), signal.clone());
+        let subscription = signal.subscribe(move |_| {
+            counter.fetch_add(1, Ordering::Relaxed);
+            if let Some(subscription) = *lock(&this) {
+                source.unsubscribe(subscription);
+            }
+            Ok(())
+        });
+        *lock(&own) = Some(subscription);
+
+        signal.set(1)?;
+        signal.set(2)?;
+        assert_eq!(calls.load(Ordering::Relaxed), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn subscribers_can_set_the_signal() -> Result<()> {
+        let signal = Signal::new(0);
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let (log, source) = (Arc::clone(&seen), signal.clone());
+        signal.subscribe(move |value| {
+            lock(&log).push(*value);
+            // Round odd values up; delivered after this call returns
+            if value % 2 == 1 {
+                source.set(value + 1)?;
+            }
+            Ok(())
+        });
+
+        signal.set(3)?;
+        assert_eq!(*lock(&seen), [3, 4]);
+        assert_eq!(signal.get(), 4);
+        Ok(())
+    }
+
+    #[cfg(feature = "testing")]
+    mod bindings {
+        use serde_json::json;
+
+        use super::super::*;
+        use crate::event::{Event, SelectedItem};
+        use crate::testing::MockService;
+
+        #[test]
+        fn one_way_bindings_send_the_current_value_and_changes() -> Result<()> {
+            let (mock, mut activity) = MockService::activity(false)?;
+            let label = activity.create_text_view("", None)?;
+            let bar = activity.create_progress_bar(None)?;
+
+            let text = Signal::new(String::from("first"));
+            text.bind_text(&mut activity, &label)?;
+            text.set("second".to_string())?;
+            let progress = Signal::new(50);
+            progress.bind_progress(&mut activity, &bar)?;
+            progress.set(150)?;
+
+            let texts: Vec<_> = mock.calls_to("setText").iter().map(|p| p["text"].clone()).collect();
+            assert_eq!(texts, ["first", "second"]);
+            let values: Vec<_> = mock.calls_to("setProgress").iter().map(|p| p["progress"].clone()).collect();
+            assert_eq!(values, [json!(50), json!(100)]);
+            Ok(())
+        }
+
+        #[test]
+        fn two_way_text_updates_other_widgets_but_not_the_source() -> Result<()> {
+            let (mock, mut activity) = MockService::activity(false)?;
+            let input = activity.create_edit_text("", None)?;
+            let echo = activity.create_text_view("", None)?;
+            let name = Signal::new(String::new());
+            let mut events = EventLoop::new();
+            name.bind_text_two_way(&mut activity, &mut events, &input)?;
+            name.bind_text(&mut activity, &echo)?;
+            mock.clear_calls();
+
+            let aid = activity.id();
+            events.dispatch(&mut activity, &Event::Text { aid, id: input.id(), text: "Ada".into() })?;
+            assert_eq!(name.get(), "Ada");
+            assert_eq!(mock.calls_to("setText"), [json!({ "aid": aid, "id": echo.id(), "text": "Ada" })]);
+
+            // Changes from the program reach the field too
+            mock.clear_calls();
+            name.set("Bob".to_string())?;
+            assert_eq!(mock.calls_to("setText").len(), 2);
+            Ok(())
+        }
+
+        #[test]
+        fn two_way_checked_and_selection() -> Result<()> {
+            let (mock, mut activity) = MockService::activity(false)?;
+            let switch = activity.create_switch("On", None)?;
+            let spinner = activity.create_spinner(None)?;
+            let enabled = Signal::new(false);
+            let index = Signal::new(0);
+            let mut events = EventLoop::new();
+            enabled.bind_checked_two_way(&mut activity, &mut events, &switch)?;
+            index.bind_selection_two_way(&mut activity, &mut events, &spinner, &["a", "b", "c"])?;
+            mock.clear_calls();
+
+            let aid = activity.id();
+            events.dispatch(&mut activity, &Event::Click { aid, id: switch.id(), checked: Some(true) })?;
+            let selected = SelectedItem::Text("c".into());
+            events.dispatch(&mut activity, &Event::ItemSelected { aid, id: spinner.id(), selected })?;
+            let unknown = SelectedItem::Text("z".into());
+            events.dispatch(&mut activity, &Event::ItemSelected { aid, id: spinner.id(), selected: unknown })?;
+
+            assert!(enabled.get());
+            assert_eq!(index.get(), 2);
+            assert!(mock.calls_to("setChecked").is_empty());
+            assert!(mock.calls_to("selectItem").is_empty());
+            Ok(())
+        }
+
+        #[test]
+        fn bindings_to_destroyed_activities_are_dropped() -> Result<()> {
+            let (mock, mut activity) = MockService::activity(false)?;
+            let label = activity.create_text_view("", None)?;
+            let text = Signal::new(String::new());
+            text.bind_text(&mut activity, &label)?;
+
+            mock.send_event(&json!({
+                "type": "destroy",
+                "value": {"aid": activity.id(), "finishing": true}
+            }))?;
+            activity.next_event()?;
+            mock.clear_calls();
+
+            text.set("gone".to_string())?;
+            assert!(mock.calls_to("setText").is_empty());
+            assert!(lock(&text.inner).subscribers.is_empty());
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,718 @@
+//! Builder-style widget construction
+//!
+//! Every component can be created through a [`WidgetBuilder`], started from
+//! the matching method on [`Activity`] (`activity.button("OK")`,
+//! `activity.linear_layout()`, ...). Builders expose all creation
+//! parameters the protocol supports, and can also set the size, margin,
+//! padding and colours and register event handlers, so a widget is set up
+//! in one expression.
+//!
+//! Setters that only make sense for some components (e.g. `checked` or
+//! `input_type`) are only available on the builders of those components.
+//!
+//! ## Example
+//!
+//! ```rust,no_run
+//! use termux_gui::{Activity, Color, Dimension, EventLoop, Result};
+//!
+//! fn main() -> Result<()> {
+//!     let mut activity = Activity::new(false)?;
+//!     let mut events = EventLoop::new();
+//!
+//!     let row = activity.linear_layout().horizontal().build()?;
+//!     let name = activity.edit_text("")
+//!         .parent(&row)
+//!         .hint("Name")
+//!         .weight(1)
+//!         .build()?;
+//!     let _ok = activity.button("OK")
+//!         .parent(&row)
+//!         .width(Dimension::WrapContent)
+//!         .margin(8)
+//!         .text_color(Color::WHITE)
+//!         .events(&mut events)
+//!         .on_click(move |ctx| {
+//!             println!("hello {}", name.get_text(ctx.activity)?);
+//!             Ok(())
+//!         })
+//!         .build()?;
+//!
+//!     events.run(&mut activity)
+//! }
+//! ```
+
+use std::marker::PhantomData;
+use serde_json::{json, Map, Value};
+use crate::activity::Activity;
+use crate::color::Color;
+use crate::components::{
+    Button, Checkbox, EditText, FrameLayout, GridLayout, HorizontalScrollView, ImageView,
+    LinearLayout, NestedScrollView, ProgressBar, RadioButton, RadioGroup, Space, Spinner,
+    Switch, SwipeRefreshLayout, TabLayout, TextView, ToggleButton, WebView,
+};
+use crate::dimension::Dimension;
+use crate::error::{GuiError, Result};
+use crate::event::SelectedItem;
+use crate::event_loop::{EventContext, EventLoop};
+use crate::raw;
+use crate::view::{View, Visibility};
+use crate::widget::{TextWidget, Widget};
+
+pub(crate) mod sealed {
+    use serde_json::Value;
+    use crate::raw::Request;
+    use crate::view::View;
+    use crate::widget::Widget;
+
+    /// A component that can be created by a [`WidgetBuilder`](super::WidgetBuilder)
+    pub trait Create: Widget + Sized {
+        /// Parameters of the protocol method that creates the component
+        type Params: Request<Reply = i64> + Default;
+
+        /// Protocol method that creates the component
+        const METHOD: &'static str = <Self::Params as Request>::METHOD;
+
+        /// Creation parameters sent unless the caller sets them
+        ///
+        /// `aid` and `parent` are filled in on creation. Used by the
+        /// component's constructors, [`WidgetBuilder`](super::WidgetBuilder)
+        /// and [`Batch`](crate::Batch) alike.
+        fn default_params() -> Self::Params {
+            Self::Params::default()
+        }
+
+        /// Wrap a newly created view
+        fn from_parts(view: View, aid: i64, params: &Value) -> Self;
+    }
+}
+
+use sealed::Create;
+
+/// Implement [`Create`] for components with only `view` and `aid` fields
+///
+/// The [`raw`](crate::raw) parameter struct may be followed by default
+/// values, e.g. `Button => CreateButton { allcaps: Some(false) }`.
+macro_rules! impl_create {
+    ($($ty:ident => $params:ident $({ $($field:ident: $value:expr),* $(,)? })?),* $(,)?) => {
+        $(
+            impl $crate::builder::sealed::Create for $ty {
+                type Params = $crate::raw::$params;
+
+                $(
+                    fn default_params() -> $crate::raw::$params {
+                        $crate::raw::$params {
+                            $($field: $value,)*
+                            ..Default::default()
+                        }
+                    }
+                )?
+
+                fn from_parts(view: $crate::view::View, aid: i64, _params: &::serde_json::Value) -> Self {
+                    $ty { view, aid }
+                }
+            }
+        )*
+    };
+}
+
+pub(crate) use impl_create;
+
+/// A registration run once the widget is created
+type PendingHandler<'h, W> = Box<dyn FnOnce(&mut EventLoop<'h>, &W) + 'h>;
+
+/// Builder for a component of type `W`
+///
+/// Created by the `Activity` methods named after the component, e.g.
+/// [`Activity::button`]. Nothing is sent until [`build`](Self::build).
+pub struct WidgetBuilder<'a, 'h, W> {
+    activity: &'a mut Activity,
+    params: Map<String, Value>,
+    width: Option<Dimension>,
+    height: Option<Dimension>,
+    margin: Option<Dimension>,
+    padding: Option<Dimension>,
+    weight: Option<i32>,
+    background: Option<Color>,
+    text_size: Option<i32>,
+    text_color: Option<Color>,
+    hint: Option<String>,
+    events: Option<&'a mut EventLoop<'h>>,
+    handlers: Vec<PendingHandler<'h, W>>,
+    widget: PhantomData<W>,
+}
+
+impl<'a, 'h, W: Create> WidgetBuilder<'a, 'h, W> {
+    pub(crate) fn new(activity: &'a mut Activity, params: Value) -> Self {
+        let params = create_params::<W>(params);
+        WidgetBuilder {
+            activity,
+            params,
+            width: None,
+            height: None,
+            margin: None,
+            padding: None,
+            weight: None,
+            background: None,
+            text_size: None,
+            text_color: None,
+            hint: None,
+            events: None,
+            handlers: Vec::new(),
+            widget: PhantomData,
+        }
+    }
+
+    fn param(mut self, key: &str, value: impl Into<Value>) -> Self {
+        self.params.insert(key.to_string(), value.into());
+        self
+    }
+
+    fn handler(mut self, register: impl FnOnce(&mut EventLoop<'h>, &W) + 'h) -> Self {
+        self.handlers.push(Box::new(register));
+        self
+    }
+
+    /// Add the widget to a layout
+    pub fn parent(self, parent: &impl Widget) -> Self {
+        self.parent_id(parent.id())
+    }
+
+    /// Add the widget to the layout with ID `parent`
+    pub fn parent_id(self, parent: i64) -> Self {
+        self.param("parent", parent)
+    }
+
+    /// Set the initial visibility
+    pub fn visibility(self, visibility: Visibility) -> Self {
+        self.param("visibility", visibility.as_i32())
+    }
+
+    /// Set the width, see [`View::set_width`]
+    pub fn width(mut self, width: impl Into<Dimension>) -> Self {
+        self.width = Some(width.into());
+        self
+    }
+
+    /// Set the height, see [`View::set_height`]
+    pub fn height(mut self, height: impl Into<Dimension>) -> Self {
+        self.height = Some(height.into());
+        self
+    }
+
+    /// Set the margin on all sides
+    pub fn margin(mut self, margin: impl Into<Dimension>) -> Self {
+        self.margin = Some(margin.into());
+        self
+    }
+
+    /// Set the padding on all sides
+    pub fn padding(mut self, padding: impl Into<Dimension>) -> Self {
+        self.padding = Some(padding.into());
+        self
+    }
+
+    /// Set the weight inside a LinearLayout
+    pub fn weight(mut self, weight: i32) -> Self {
+        self.weight = Some(weight);
+        self
+    }
+
+    /// Set the background colour
+    pub fn background_color(mut self, color: impl Into<Color>) -> Self {
+        self.background = Some(color.into());
+        self
+    }
+
+    /// Register the `on_*` handlers of this builder on `events`
+    ///
+    /// Must be called before [`build`](Self::build) if any handler is set.
+    pub fn events(mut self, events: &'a mut EventLoop<'h>) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    /// Call `handler` when the widget is clicked
+    pub fn on_click<F>(self, handler: F) -> Self
+    where
+        F: FnMut(&mut EventContext<'_>) -> Result<()> + 'h,
+    {
+        self.handler(move |events, widget| {
+            events.on_click(widget, handler);
+        })
+    }
+
+    /// Call `handler` when the widget is long-clicked
+    pub fn on_long_click<F>(self, handler: F) -> Self
+    where
+        F: FnMut(&mut EventContext<'_>) -> Result<()> + 'h,
+    {
+        self.handler(move |events, widget| {
+            events.on_long_click(widget, handler);
+        })
+    }
+
+    /// Call `handler` with the focus state when the widget gains or loses focus
+    pub fn on_focus_change<F>(self, handler: F) -> Self
+    where
+        F: FnMut(&mut EventContext<'_>, bool) -> Result<()> + 'h,
+    {
+        self.handler(move |events, widget| {
+            events.on_focus_change(widget, handler);
+        })
+    }
+
+    /// Create the widget and apply the remaining settings
+    pub fn build(self) -> Result<W> {
+        if !self.handlers.is_empty() && self.events.is_none() {
+            return Err(GuiError::InvalidOperation(format!(
+                "{} has event handlers but no EventLoop, call events() first",
+                W::METHOD
+            )));
+        }
+
+        let aid = self.activity.id();
+        let mut params = Value::Object(self.params);
+        params["aid"] = json!(aid);
+
+        let msg = json!({
+            "method": W::METHOD,
+            "params": params
+        });
+        let id = self.activity.create_view(&msg)?;
+        let widget = W::from_parts(View::new(id), aid, &params);
+
+        let activity = self.activity;
+        let view = widget.view();
+        if let Some(width) = self.width {
+            view.set_width(activity, width)?;
+        }
+        if let Some(height) = self.height {
+            view.set_height(activity, height)?;
+        }
+        if let Some(margin) = self.margin {
+            view.set_margin(activity, margin)?;
+        }
+        if let Some(padding) = self.padding {
+            view.set_padding(activity, padding)?;
+        }
+        if let Some(weight) = self.weight {
+            view.set_linear_layout_params(activity, weight, None)?;
+        }
+        if let Some(color) = self.background {
+            view.set_background_color(activity, color)?;
+        }
+        if let Some(size) = self.text_size {
+            raw::set_text_size(activity, &raw::SetTextSize { aid, id, size: size.into() })?;
+        }
+        if let Some(color) = self.text_color {
+            raw::set_text_color(activity, &raw::SetTextColor {
+                aid,
+                id,
+                color: color.to_i32().into(),
+            })?;
+        }
+        if let Some(hint) = self.hint {
+            raw::set_hint(activity, &raw::SetHint { aid, id, hint })?;
+        }
+
+        if let Some(events) = self.events {
+            for register in self.handlers {
+                register(events, &widget);
+            }
+        }
+        Ok(widget)
+    }
+}
+
+impl<W: Create + TextWidget> WidgetBuilder<'_, '_, W> {
+    /// Set the text size in sp
+    pub fn text_size(mut self, size: i32) -> Self {
+        self.text_size = Some(size);
+        self
+    }
+
+    /// Set the text colour
+    pub fn text_color(mut self, color: impl Into<Color>) -> Self {
+        self.text_color = Some(color.into());
+        self
+    }
+
+    /// Let the user select and copy the text
+    pub fn selectable_text(self, selectable: bool) -> Self {
+        self.param("selectableText", selectable)
+    }
+
+    /// Make links in the text clickable
+    pub fn clickable_links(self, clickable: bool) -> Self {
+        self.param("clickableLinks", clickable)
+    }
+}
+
+impl WidgetBuilder<'_, '_, Button> {
+    /// Show the text in all caps, as Android buttons do by default
+    pub fn all_caps(self, all_caps: bool) -> Self {
+        self.param("allcaps", all_caps)
+    }
+}
+
+impl<'h> WidgetBuilder<'_, 'h, EditText> {
+    /// Allow only a single line of text
+    pub fn single_line(self, single_line: bool) -> Self {
+        self.param("singleline", single_line)
+    }
+
+    /// Show the line under the text
+    pub fn line(self, line: bool) -> Self {
+        self.param("line", line)
+    }
+
+    /// Block the soft keyboard, for input from custom buttons
+    pub fn block_input(self, block: bool) -> Self {
+        self.param("blockinput", block)
+    }
+
+    /// Set the input type, e.g. `"text"`, `"number"`, `"textPassword"` or
+    /// `"textMultiLine"`
+    pub fn input_type(self, input_type: &str) -> Self {
+        self.param("type", input_type)
+    }
+
+    /// Set the hint shown while the field is empty
+    pub fn hint(mut self, hint: &str) -> Self {
+        self.hint = Some(hint.to_string());
+        self
+    }
+
+    /// Call `handler` with the new text when it changes
+    pub fn on_text<F>(self, handler: F) -> Self
+    where
+        F: FnMut(&mut EventContext<'_>, &str) -> Result<()> + 'h,
+    {
+        self.handler(move |events, widget| {
+            events.on_text(widget, handler);
+        })
+    }
+}
+
+/// Setters for Checkbox, Switch, RadioButton and ToggleButton
+macro_rules! checkable_builder {
+    ($($ty:ty),*) => {
+        $(
+            impl<'h> WidgetBuilder<'_, 'h, $ty> {
+                /// Set the initial checked state
+                pub fn checked(self, checked: bool) -> Self {
+                    self.param("checked", checked)
+                }
+
+                /// Call `handler` with the new state when the widget is toggled
+                pub fn on_checked<F>(self, handler: F) -> Self
+                where
+                    F: FnMut(&mut EventContext<'_>, bool) -> Result<()> + 'h,
+                {
+                    self.handler(move |events, widget| {
+                        events.on_checked(widget, handler);
+                    })
+                }
+            }
+        )*
+    };
+}
+
+checkable_builder!(Checkbox, Switch, RadioButton, ToggleButton);
+
+impl WidgetBuilder<'_, '_, LinearLayout> {
+    /// Arrange children vertically (the default) or horizontally
+    pub fn vertical(self, vertical: bool) -> Self {
+        self.param("vertical", vertical)
+    }
+
+    /// Arrange children horizontally
+    pub fn horizontal(self) -> Self {
+        self.vertical(false)
+    }
+}
+
+/// Setters for NestedScrollView and HorizontalScrollView
+macro_rules! scroll_builder {
+    ($($ty:ty),*) => {
+        $(
+            impl WidgetBuilder<'_, '_, $ty> {
+                /// Let the child fill the viewport
+                pub fn fill_viewport(self, fill: bool) -> Self {
+                    self.param("fillviewport", fill)
+                }
+
+                /// Snap to the closest child after scrolling
+                pub fn snapping(self, snapping: bool) -> Self {
+                    self.param("snapping", snapping)
+                }
+
+                /// Hide the scroll bar
+                pub fn no_bar(self, no_bar: bool) -> Self {
+                    self.param("nobar", no_bar)
+                }
+            }
+        )*
+    };
+}
+
+scroll_builder!(NestedScrollView, HorizontalScrollView);
+
+impl<'h> WidgetBuilder<'_, 'h, RadioGroup> {
+    /// Call `handler` with the id of the checked RadioButton when the
+    /// selection changes
+    pub fn on_selected<F>(self, handler: F) -> Self
+    where
+        F: FnMut(&mut EventContext<'_>, i64) -> Result<()> + 'h,
+    {
+        self.handler(move |events, widget| {
+            events.on_selected(widget, handler);
+        })
+    }
+}
+
+/// Item selection handler for Spinner and TabLayout
+macro_rules! item_selected_builder {
+    ($($ty:ty),*) => {
+        $(
+            impl<'h> WidgetBuilder<'_, 'h, $ty> {
+                /// Call `handler` with the selected item
+                pub fn on_item_selected<F>(self, handler: F) -> Self
+                where
+                    F: FnMut(&mut EventContext<'_>, &SelectedItem) -> Result<()> + 'h,
+                {
+                    self.handler(move |events, widget| {
+                        events.on_item_selected(widget, handler);
+                    })
+                }
+            }
+        )*
+    };
+}
+
+item_selected_builder!(Spinner, TabLayout);
+
+impl<'h> WidgetBuilder<'_, 'h, SwipeRefreshLayout> {
+    /// Call `handler` when the layout is pulled down
+    pub fn on_refresh<F>(self, handler: F) -> Self
+    where
+        F: FnMut(&mut EventContext<'_>) -> Result<()> + 'h,
+    {
+        self.handler(move |events, widget| {
+            events.on_refresh(widget, handler);
+        })
+    }
+}
+
+/// `W`'s default creation parameters, overridden by `params`
+pub(crate) fn create_params<W: Create>(params: Value) -> Map<String, Value> {
+    let mut merged = match serde_json::to_value(W::default_params()) {
+        Ok(Value::Object(map)) => map,
+        _ => Map::new(),
+    };
+    merged.remove("aid");
+    if let Value::Object(params) = params {
+        merged.extend(params);
+    }
+    merged
+}
+
+impl Activity {
+    /// Start building a TextView
+    pub fn text_view<'h>(&mut self, text: &str) -> WidgetBuilder<'_, 'h, TextView> {
+        WidgetBuilder::new(self, json!({ "text": text }))
+    }
+
+    /// Start building a Button
+    pub fn button<'h>(&mut self, text: &str) -> WidgetBuilder<'_, 'h, Button> {
+        WidgetBuilder::new(self, json!({ "text": text }))
+    }
+
+    /// Start building a single-line EditText
+    pub fn edit_text<'h>(&mut self, text: &str) -> WidgetBuilder<'_, 'h, EditText> {
+        WidgetBuilder::new(self, json!({ "text": text }))
+    }
+
+    /// Start building a Checkbox
+    pub fn checkbox<'h>(&mut self, text: &str) -> WidgetBuilder<'_, 'h, Checkbox> {
+        WidgetBuilder::new(self, json!({ "text": text }))
+    }
+
+    /// Start building a Switch
+    pub fn switch<'h>(&mut self, text: &str) -> WidgetBuilder<'_, 'h, Switch> {
+        WidgetBuilder::new(self, json!({ "text": text }))
+    }
+
+    /// Start building a RadioButton
+    pub fn radio_button<'h>(&mut self, text: &str) -> WidgetBuilder<'_, 'h, RadioButton> {
+        WidgetBuilder::new(self, json!({ "text": text }))
+    }
+
+    /// Start building a ToggleButton
+    pub fn toggle_button<'h>(&mut self, text: &str) -> WidgetBuilder<'_, 'h, ToggleButton> {
+        WidgetBuilder::new(self, json!({ "text": text }))
+    }
+
+    /// Start building a vertical LinearLayout
+    pub fn linear_layout<'h>(&mut self) -> WidgetBuilder<'_, 'h, LinearLayout> {
+        WidgetBuilder::new(self, json!({}))
+    }
+
+    /// Start building a NestedScrollView
+    pub fn nested_scroll_view<'h>(&mut self) -> WidgetBuilder<'_, 'h, NestedScrollView> {
+        WidgetBuilder::new(self, json!({}))
+    }
+
+    /// Start building a HorizontalScrollView
+    pub fn horizontal_scroll_view<'h>(&mut self) -> WidgetBuilder<'_, 'h, HorizontalScrollView> {
+        WidgetBuilder::new(self, json!({}))
+    }
+
+    /// Start building a GridLayout
+    pub fn grid_layout<'h>(&mut self, rows: i32, cols: i32) -> WidgetBuilder<'_, 'h, GridLayout> {
+        WidgetBuilder::new(self, json!({ "rows": rows, "cols": cols }))
+    }
+
+    /// Start building a FrameLayout
+    pub fn frame_layout<'h>(&mut self) -> WidgetBuilder<'_, 'h, FrameLayout> {
+        WidgetBuilder::new(self, json!({}))
+    }
+
+    /// Start building a SwipeRefreshLayout
+    pub fn swipe_refresh_layout<'h>(&mut self) -> WidgetBuilder<'_, 'h, SwipeRefreshLayout> {
+        WidgetBuilder::new(self, json!({}))
+    }
+
+    /// Start building a TabLayout
+    pub fn tab_layout<'h>(&mut self) -> WidgetBuilder<'_, 'h, TabLayout> {
+        WidgetBuilder::new(self, json!({}))
+    }
+
+    /// Start building a RadioGroup
+    pub fn radio_group<'h>(&mut self) -> WidgetBuilder<'_, 'h, RadioGroup> {
+        WidgetBuilder::new(self, json!({}))
+    }
+
+    /// Start building a Spinner
+    pub fn spinner<'h>(&mut self) -> WidgetBuilder<'_, 'h, Spinner> {
+        WidgetBuilder::new(self, json!({}))
+    }
+
+    /// Start building an ImageView
+    pub fn image_view<'h>(&mut self) -> WidgetBuilder<'_, 'h, ImageView> {
+        WidgetBuilder::new(self, json!({}))
+    }
+
+    /// Start building a ProgressBar
+    pub fn progress_bar<'h>(&mut self) -> WidgetBuilder<'_, 'h, ProgressBar> {
+        WidgetBuilder::new(self, json!({}))
+    }
+
+    /// Start building a Space
+    pub fn space<'h>(&mut self) -> WidgetBuilder<'_, 'h, Space> {
+        WidgetBuilder::new(self, json!({}))
+    }
+
+    /// Start building a WebView
+    pub fn web_view<'h>(&mut self) -> WidgetBuilder<'_, 'h, WebView> {
+        WidgetBuilder::new(self, json!({}))
+    }
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use std::cell::{Cell, RefCell};
+
+    use serde_json::json;
+
+    use super::*;
+    use crate::event::Event;
+    use crate::testing::MockService;
+
+    #[test]
+    fn default_params_are_overridden_by_setters() -> Result<()> {
+        let (mock, mut activity) = MockService::activity(false)?;
+        let layout = activity.linear_layout().horizontal().build()?;
+        activity.button("Plain").parent(&layout).build()?;
+        activity.button("Loud").all_caps(true).build()?;
+        activity.edit_text("").input_type("number").single_line(false).build()?;
+
+        let aid = activity.id();
+        let buttons = mock.calls_to("createButton");
+        assert_eq!(buttons[0]["allcaps"], json!(false));
+        assert_eq!(buttons[0]["parent"], json!(layout.id()));
+        assert_eq!(buttons[0]["aid"], json!(aid));
+        assert_eq!(buttons[1]["allcaps"], json!(true));
+        assert_eq!(mock.calls_to("createLinearLayout")[0]["vertical"], json!(false));
+        let edit = &mock.calls_to("createEditText")[0];
+        assert_eq!((edit["type"].clone(), edit["singleline"].clone()), (json!("number"), json!(false)));
+        Ok(())
+    }
+
+    #[test]
+    fn settings_are_applied_after_creation() -> Result<()> {
+        let (mock, mut activity) = MockService::activity(false)?;
+        let input = activity
+            .edit_text("")
+            .width(Dimension::MatchParent)
+            .margin(4)
+            .weight(2)
+            .text_size(18)
+            .text_color(Color::WHITE)
+            .hint("Name")
+            .build()?;
+
+        let id = json!(input.id());
+        assert_eq!(mock.calls_to("setWidth")[0]["id"], id);
+        assert_eq!(mock.calls_to("setWidth")[0]["width"], json!("MATCH_PARENT"));
+        assert_eq!(mock.calls_to("setMargin")[0]["margin"], json!(4));
+        assert_eq!(mock.calls_to("setLinearLayoutParams")[0]["weight"], json!(2));
+        assert_eq!(mock.calls_to("setTextSize")[0]["size"], json!(18));
+        assert_eq!(mock.calls_to("setTextColor")[0]["color"], json!(Color::WHITE.to_i32()));
+        assert_eq!(mock.calls_to("setHint")[0]["hint"], json!("Name"));
+        assert!(mock.calls_to("setHeight").is_empty());
+        assert!(mock.calls_to("setPadding").is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn handlers_need_an_event_loop() -> Result<()> {
+        let (mock, mut activity) = MockService::activity(false)?;
+        let result = activity.button("OK").on_click(|_| Ok(())).build();
+
+        assert!(matches!(result, Err(GuiError::InvalidOperation(_))));
+        assert!(mock.calls_to("createButton").is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn handlers_are_registered_on_the_event_loop() -> Result<()> {
+        let (_mock, mut activity) = MockService::activity(false)?;
+        let clicks = Cell::new(0);
+        let text = RefCell::new(String::new());
+        let mut events = EventLoop::new();
+        let button = activity
+            .button("OK")
+            .events(&mut events)
+            .on_click(|_| {
+                clicks.set(clicks.get() + 1);
+                Ok(())
+            })
+            .build()?;
+        let input = activity
+            .edit_text("")
+            .events(&mut events)
+            .on_text(|_, value| {
+                *text.borrow_mut() = value.to_string();
+                Ok(())
+            })
+            .build()?;
+
+        let aid = activity.id();
+        events.dispatch(&mut activity, &Event::Click { aid, id: button.id(), checked: None })?;
+        events.dispatch(&mut activity, &Event::Text { aid, id: input.id(), text: "hi".into() })?;
+        assert_eq!(clicks.get(), 1);
+        assert_eq!(*text.borrow(), "hi");
+        Ok(())
+    }
+}
@@ -0,0 +1,447 @@
+//! Colours
+//!
+//! Android colours are 32-bit ARGB values. [`Color`] wraps one and can be
+//! built from components, hex strings, CSS colour names or HSL. Every
+//! method that takes a colour accepts `impl Into<Color>`, so plain ARGB
+//! integers keep working.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use termux_gui::Color;
+//!
+//! # fn main() -> termux_gui::Result<()> {
+//! let red = Color::rgb(255, 0, 0);
+//! assert_eq!(red, "#FF0000".parse()?);
+//! assert_eq!(red, "red".parse()?);
+//! assert_eq!(red, Color::hsl(0.0, 1.0, 0.5));
+//! assert_eq!(red, Color::from(0xFFFF0000u32));
+//!
+//! // Half-transparent red on white is pink
+//! let pink = red.with_alpha(128).over(Color::WHITE);
+//! assert_eq!(pink.to_hex(), "#FFFF7F7F");
+//! # Ok(())
+//! # }
+//! ```
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::error::{GuiError, Result};
+
+/// An ARGB colour
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Color(u32);
+
+impl Color {
+    /// Fully transparent black
+    pub const TRANSPARENT: Color = Color(0x00000000);
+    /// Opaque black
+    pub const BLACK: Color = Color(0xFF000000);
+    /// Opaque white
+    pub const WHITE: Color = Color(0xFFFFFFFF);
+    /// Opaque red
+    pub const RED: Color = Color(0xFFFF0000);
+    /// Opaque green (`#00FF00`, CSS `lime`)
+    pub const GREEN: Color = Color(0xFF00FF00);
+    /// Opaque blue
+    pub const BLUE: Color = Color(0xFF0000FF);
+    /// Opaque gray (CSS `gray`)
+    pub const GRAY: Color = Color(0xFF808080);
+
+    /// Create a colour from a packed `0xAARRGGBB` value
+    pub const fn from_argb_u32(argb: u32) -> Self {
+        Color(argb)
+    }
+
+    /// Create a colour from alpha, red, green and blue components
+    pub const fn argb(a: u8, r: u8, g: u8, b: u8) -> Self {
+        Color((a as u32) << 24 | (r as u32) << 16 | (g as u32) << 8 | b as u32)
+    }
+
+    /// Create an opaque colour from red, green and blue components
+    pub const fn rgb(r: u8, g: u8, b: u8) -> Self {
+        Self::argb(0xFF, r, g, b)
+    }
+
+    /// Create an opaque colour from hue (degrees), saturation and lightness (0.0 to 1.0)
+    pub fn hsl(h: f32, s: f32, l: f32) -> Self {
+        Self::hsla(h, s, l, 1.0)
+    }
+
+    /// Create a colour from hue (degrees), saturation, lightness and alpha (0.0 to 1.0)
+    pub fn hsla(h: f32, s: f32, l: f32, a: f32) -> Self {
+        let h = h.rem_euclid(360.0) / 60.0;
+        let s = s.clamp(0.0, 1.0);
+        let l = l.clamp(0.0, 1.0);
+
+        let c = (1.0 - (2.0 * l - 1.0).abs()) * s;
+        let x = c * (1.0 - (h % 2.0 - 1.0).abs());
+        let (r, g, b) = match h as u32 {
+            0 => (c, x, 0.0),
+            1 => (x, c, 0.0),
+            2 => (0.0, c, x),
+            3 => (0.0, x, c),
+            4 => (x, 0.0, c),
+            _ => (c, 0.0, x),
+        };
+        let m = l - c / 2.0;
+
+        Self::argb(unit_to_u8(a), unit_to_u8(r + m), unit_to_u8(g + m), unit_to_u8(b + m))
+    }
+
+    /// Parse `#RRGGBB`, `#AARRGGBB` or a CSS colour name
+    ///
+    /// Same as `str::parse`.
+    pub fn parse(s: &str) -> Result<Self> {
+        s.parse()
+    }
+
+    /// Get the alpha component
+    pub const fn a(self) -> u8 {
+        (self.0 >> 24) as u8
+    }
+
+    /// Get the red component
+    pub const fn r(self) -> u8 {
+        (self.0 >> 16) as u8
+    }
+
+    /// Get the green component
+    pub const fn g(self) -> u8 {
+        (self.0 >> 8) as u8
+    }
+
+    /// Get the blue component
+    pub const fn b(self) -> u8 {
+        self.0 as u8
+    }
+
+    /// Get the packed `0xAARRGGBB` value
+    pub const fn to_argb_u32(self) -> u32 {
+        self.0
+    }
+
+    /// Get the signed ARGB value used by the protocol
+    pub const fn to_i32(self) -> i32 {
+        self.0 as i32
+    }
+
+    /// Format as `#AARRGGBB`
+    pub fn to_hex(self) -> String {
+        format!("#{:08X}", self.0)
+    }
+
+    /// Get the same colour with a different alpha
+    pub const fn with_alpha(self, a: u8) -> Self {
+        Color(self.0 & 0x00FFFFFF | (a as u32) << 24)
+    }
+
+    /// Interpolate between two colours, including alpha
+    ///
+    /// `t = 0.0` gives `self` and `t = 1.0` gives `other`.
+    pub fn mix(self, other: Color, t: f32) -> Self {
+        let t = t.clamp(0.0, 1.0);
+        let lerp = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * t).round() as u8;
+        Self::argb(
+            lerp(self.a(), other.a()),
+            lerp(self.r(), other.r()),
+            lerp(self.g(), other.g()),
+            lerp(self.b(), other.b()),
+        )
+    }
+
+    /// Composite this colour over `background` ("source over" blending)
+    pub fn over(self, background: Color) -> Self {
+        let src_a = self.a() as f32 / 255.0;
+        let dst_a = background.a() as f32 / 255.0;
+        let out_a = src_a + dst_a * (1.0 - src_a);
+        if out_a == 0.0 {
+            return Color::TRANSPARENT;
+        }
+
+        let blend = |src: u8, dst: u8| {
+            let c = (src as f32 * src_a + dst as f32 * dst_a * (1.0 - src_a)) / out_a;
+            c.round().clamp(0.0, 255.0) as u8
+        };
+        Self::argb(
+            unit_to_u8(out_a),
+            blend(self.r(), background.r()),
+            blend(self.g(), background.g()),
+            blend(self.b(), background.b()),
+        )
+    }
+}
+
+fn unit_to_u8(v: f32) -> u8 {
+    (v.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+impl From<u32> for Color {
+    /// Interpret the value as `0xAARRGGBB`
+    fn from(argb: u32) -> Self {
+        Color(argb)
+    }
+}
+
+impl From<i32> for Color {
+    /// Interpret the value as ARGB, as in `0xFFFF0000u32 as i32`
+    fn from(argb: i32) -> Self {
+        Color(argb as u32)
+    }
+}
+
+impl From<Color> for i32 {
+    fn from(color: Color) -> Self {
+        color.to_i32()
+    }
+}
+
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{:08X}", self.0)
+    }
+}
+
+impl FromStr for Color {
+    type Err = GuiError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim();
+        let invalid = || GuiError::InvalidColor(s.to_string());
+
+        if let Some(hex) = s.strip_prefix('#') {
+            if !hex.bytes().all(|c| c.is_ascii_hexdigit()) {
+                return Err(invalid());
+            }
+            let value = u32::from_str_radix(hex, 16).map_err(|_| invalid())?;
+            return match hex.len() {
+                6 => Ok(Color(0xFF000000 | value)),
+                8 => Ok(Color(value)),
+                _ => Err(invalid()),
+            };
+        }
+
+        let name = s.to_ascii_lowercase();
+        if name == "transparent" {
+            return Ok(Color::TRANSPARENT);
+        }
+        CSS_COLORS
+            .binary_search_by_key(&name.as_str(), |&(name, _)| name)
+            .map(|i| Color(0xFF000000 | CSS_COLORS[i].1))
+            .map_err(|_| invalid())
+    }
+}
+
+/// CSS named colours as `0xRRGGBB`, sorted by name
+const CSS_COLORS: &[(&str, u32)] = &[
+    ("aliceblue", 0xF0F8FF),
+    ("antiquewhite", 0xFAEBD7),
+    ("aqua", 0x00FFFF),
+    ("aquamarine", 0x7FFFD4),
+    ("azure", 0xF0FFFF),
+    ("beige", 0xF5F5DC),
+    ("bisque", 0xFFE4C4),
+    ("black", 0x000000),
+    ("blanchedalmond", 0xFFEBCD),
+    ("blue", 0x0000FF),
+    ("blueviolet", 0x8A2BE2),
+    ("brown", 0xA52A2A),
+    ("burlywood", 0xDEB887),
+    ("cadetblue", 0x5F9EA0),
+    ("chartreuse", 0x7FFF00),
+    ("chocolate", 0xD2691E),
+    ("coral", 0xFF7F50),
+    ("cornflowerblue", 0x6495ED),
+    ("cornsilk", 0xFFF8DC),
+    ("crimson", 0xDC143C),
+    ("cyan", 0x00FFFF),
+    ("darkblue", 0x00008B),
+    ("darkcyan", 0x008B8B),
+    ("darkgoldenrod", 0xB8860B),
+    ("darkgray", 0xA9A9A9),
+    ("darkgreen", 0x006400),
+    ("darkgrey", 0xA9A9A9),
+    ("darkkhaki", 0xBDB76B),
+    ("darkmagenta", 0x8B008B),
+    ("darkolivegreen", 0x556B2F),
+    ("darkorange", 0xFF8C00),
+    ("darkorchid", 0x9932CC),
+    ("darkred", 0x8B0000),
+    ("darksalmon", 0xE9967A),
+    ("darkseagreen", 0x8FBC8F),
+    ("darkslateblue", 0x483D8B),
+    ("darkslategray", 0x2F4F4F),
+    ("darkslategrey", 0x2F4F4F),
+    ("darkturquoise", 0x00CED1),
+    ("darkviolet", 0x9400D3),
+    ("deeppink", 0xFF1493),
+    ("deepskyblue", 0x00BFFF),
+    ("dimgray", 0x696969),
+    ("dimgrey", 0x696969),
+    ("dodgerblue", 0x1E90FF),
+    ("firebrick", 0xB22222),
+    ("floralwhite", 0xFFFAF0),
+    ("forestgreen", 0x228B22),
+    ("fuchsia", 0xFF00FF),
+    ("gainsboro", 0xDCDCDC),
+    ("ghostwhite", 0xF8F8FF),
+    ("gold", 0xFFD700),
+    ("goldenrod", 0xDAA520),
+    ("gray", 0x808080),
+    ("green", 0x008000),
+    ("greenyellow", 0xADFF2F),
+    ("grey", 0x808080),
+    ("honeydew", 0xF0FFF0),
+    ("hotpink", 0xFF69B4),
+    ("indianred", 0xCD5C5C),
+    ("indigo", 0x4B0082),
+    ("ivory", 0xFFFFF0),
+    ("khaki", 0xF0E68C),
+    ("lavender", 0xE6E6FA),
+    ("lavenderblush", 0xFFF0F5),
+    ("lawngreen", 0x7CFC00),
+    ("lemonchiffon", 0xFFFACD),
+    ("lightblue", 0xADD8E6),
+    ("lightcoral", 0xF08080),
+    ("lightcyan", 0xE0FFFF),
+    ("lightgoldenrodyellow", 0xFAFAD2),
+    ("lightgray", 0xD3D3D3),
+    ("lightgreen", 0x90EE90),
+    ("lightgrey", 0xD3D3D3),
+    ("lightpink", 0xFFB6C1),
+    ("lightsalmon", 0xFFA07A),
+    ("lightseagreen", 0x20B2AA),
+    ("lightskyblue", 0x87CEFA),
+    ("lightslategray", 0x778899),
+    ("lightslategrey", 0x778899),
+    ("lightsteelblue", 0xB0C4DE),
+    ("lightyellow", 0xFFFFE0),
+    ("lime", 0x00FF00),
+    ("limegreen", 0x32CD32),
+    ("linen", 0xFAF0E6),
+    ("magenta", 0xFF00FF),
+    ("maroon", 0x800000),
+    ("mediumaquamarine", 0x66CDAA),
+    ("mediumblue", 0x0000CD),
+    ("mediumorchid", 0xBA55D3),
+    ("mediumpurple", 0x9370DB),
+    ("mediumseagreen", 0x3CB371),
+    ("mediumslateblue", 0x7B68EE),
+    ("mediumspringgreen", 0x00FA9A),
+    ("mediumturquoise", 0x48D1CC),
+    ("mediumvioletred", 0xC71585),
+    ("midnightblue", 0x191970),
+    ("mintcream", 0xF5FFFA),
+    ("mistyrose", 0xFFE4E1),
+    ("moccasin", 0xFFE4B5),
+    ("navajowhite", 0xFFDEAD),
+    ("navy", 0x000080),
+    ("oldlace", 0xFDF5E6),
+    ("olive", 0x808000),
+    ("olivedrab", 0x6B8E23),
+    ("orange", 0xFFA500),
+    ("orangered", 0xFF4500),
+    ("orchid", 0xDA70D6),
+    ("palegoldenrod", 0xEEE8AA),
+    ("palegreen", 0x98FB98),
+    ("paleturquoise", 0xAFEEEE),
+    ("palevioletred", 0xDB7093),
+    ("papayawhip", 0xFFEFD5),
+    ("peachpuff", 0xFFDAB9),
+    ("peru", 0xCD853F),
+    ("pink", 0xFFC0CB),
+    ("plum", 0xDDA0DD),
+    ("powderblue", 0xB0E0E6),
+    ("purple", 0x800080),
+    ("rebeccapurple", 0x663399),
+    ("red", 0xFF0000),
+    ("rosybrown", 0xBC8F8F),
+    ("royalblue", 0x4169E1),
+    ("saddlebrown", 0x8B4513),
+    ("salmon", 0xFA8072),
+    ("sandybrown", 0xF4A460),
+    ("seagreen", 0x2E8B57),
+    ("seashell", 0xFFF5EE),
+    ("sienna", 0xA0522D),
+    ("silver", 0xC0C0C0),
+    ("skyblue", 0x87CEEB),
+    ("slateblue", 0x6A5ACD),
+    ("slategray", 0x708090),
+    ("slategrey", 0x708090),
+    ("snow", 0xFFFAFA),
+    ("springgreen", 0x00FF7F),
+    ("steelblue", 0x4682B4),
+    ("tan", 0xD2B48C),
+    ("teal", 0x008080),
+    ("thistle", 0xD8BFD8),
+    ("tomato", 0xFF6347),
+    ("turquoise", 0x40E0D0),
+    ("violet", 0xEE82EE),
+    ("wheat", 0xF5DEB3),
+    ("white", 0xFFFFFF),
+    ("whitesmoke", 0xF5F5F5),
+    ("yellow", 0xFFFF00),
+    ("yellowgreen", 0x9ACD32),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_hex_with_and_without_alpha() {
+        assert_eq!(Color::parse("#FF8000").unwrap(), Color::rgb(0xFF, 0x80, 0x00));
+        assert_eq!(Color::parse("#80ff8000").unwrap(), Color::argb(0x80, 0xFF, 0x80, 0x00));
+        assert_eq!(" #000000 ".parse::<Color>().unwrap(), Color::BLACK);
+    }
+
+    #[test]
+    fn parses_css_names_case_insensitively() {
+        assert_eq!(Color::parse("cornflowerblue").unwrap(), Color::rgb(0x64, 0x95, 0xED));
+        assert_eq!(Color::parse("White").unwrap(), Color::WHITE);
+        assert_eq!(Color::parse("transparent").unwrap(), Color::TRANSPARENT);
+    }
+
+    #[test]
+    fn rejects_malformed_colours() {
+        for input in ["", "#", "#FFF", "#GG0000", "#+FFFFF", "#1234567", "notacolour"] {
+            assert!(
+                matches!(Color::parse(input), Err(GuiError::InvalidColor(_))),
+                "accepted {:?}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn css_names_are_sorted_for_binary_search() {
+        assert!(CSS_COLORS.windows(2).all(|pair| pair[0].0 < pair[1].0));
+    }
+
+    #[test]
+    fn converts_to_protocol_and_hex() {
+        assert_eq!(Color::RED.to_i32(), 0xFFFF0000u32 as i32);
+        assert_eq!(Color::from(Color::RED.to_i32()), Color::RED);
+        assert_eq!(Color::rgb(1, 2, 3).to_hex(), "#FF010203");
+        assert_eq!(Color::parse(&Color::GRAY.to_hex()).unwrap(), Color::GRAY);
+        assert_eq!(Color::BLUE.with_alpha(0x40).a(), 0x40);
+    }
+
+    #[test]
+    fn hsl_matches_known_colours() {
+        assert_eq!(Color::hsl(0.0, 1.0, 0.5), Color::RED);
+        assert_eq!(Color::hsl(120.0, 1.0, 0.5), Color::GREEN);
+        assert_eq!(Color::hsl(600.0, 1.0, 0.5), Color::BLUE);
+        assert_eq!(Color::hsla(0.0, 0.0, 1.0, 0.0), Color::WHITE.with_alpha(0));
+    }
+
+    #[test]
+    fn mixes_and_composites() {
+        assert_eq!(Color::BLACK.mix(Color::WHITE, 0.5), Color::rgb(128, 128, 128));
+        assert_eq!(Color::BLACK.mix(Color::WHITE, 2.0), Color::WHITE);
+        assert_eq!(Color::RED.with_alpha(0).over(Color::BLUE), Color::BLUE);
+        assert_eq!(Color::RED.over(Color::BLUE), Color::RED);
+        assert_eq!(Color::TRANSPARENT.over(Color::TRANSPARENT), Color::TRANSPARENT);
+    }
+}
@@ -1,8 +1,10 @@
 //! Button component
 
-use serde_json::json;
 use crate::activity::Activity;
+use crate::raw;
 use crate::view::View;
+use crate::builder::{impl_create, sealed::Create};
+use crate::widget::{impl_widget, TextWidget};
 use crate::error::Result;
 
 /// A Button that can be clicked
@@ -14,27 +16,13 @@ pub struct Button {
 impl Button {
     /// Create a new Button
     pub fn new(activity: &mut Activity, text: &str, parent: Option<i64>) -> Result<Self> {
-        let mut params = json!({
-            "aid": activity.id(),
-            "text": text,
-            "allcaps": false
-        });
-        
-        // Only set parent if explicitly provided
-        if let Some(parent_id) = parent {
-            params["parent"] = json!(parent_id);
-        }
-        
-        eprintln!("[DEBUG] Button::new() - sending createButton...");
-        let response = activity.send_read(&json!({
-            "method": "createButton",
-            "params": params
-        }))?;
-        eprintln!("[DEBUG] Button::new() - got response: {:?}", response);
-        
-        let id = response
-            .as_i64()
-            .ok_or_else(|| crate::error::GuiError::InvalidResponse("Invalid id".to_string()))?;
+        let aid = activity.id();
+        let id = raw::create_button(activity, &raw::CreateButton {
+            aid,
+            parent,
+            text: Some(text.to_string()),
+            ..Self::default_params()
+        })?;
         
         Ok(Button {
             view: View::new(id),
@@ -54,14 +42,11 @@ impl Button {
     
     /// Set the button text
     pub fn set_text(&self, activity: &mut Activity, text: &str) -> Result<()> {
-        activity.send(&json!({
-            "method": "setText",
-            "params": {
-                "aid": self.aid,
-                "id": self.view.id(),
-                "text": text
-            }
-        }))?;
-        Ok(())
+        TextWidget::set_text(self, activity, text)
     }
 }
+
+impl_widget!(Button);
+impl_create!(Button => CreateButton { allcaps: Some(false) });
+
+impl TextWidget for Button {}
@@ -1,8 +1,10 @@
 //! Checkbox component
 
-use serde_json::json;
 use crate::activity::Activity;
+use crate::raw;
 use crate::view::View;
+use crate::builder::{impl_create, sealed::Create};
+use crate::widget::{impl_widget, CheckableWidget, TextWidget};
 use crate::error::Result;
 
 /// A Checkbox can be checked or unchecked
@@ -19,25 +21,14 @@ impl Checkbox {
     
     /// Create a new Checkbox with specified checked state
     pub fn new_with_checked(activity: &mut Activity, text: &str, parent: Option<i64>, checked: bool) -> Result<Self> {
-        let mut params = json!({
-            "aid": activity.id(),
-            "text": text,
-            "checked": checked
-        });
-        
-        // Only set parent if explicitly provided
-        if let Some(parent_id) = parent {
-            params["parent"] = json!(parent_id);
-        }
-        
-        let response = activity.send_read(&json!({
-            "method": "createCheckbox",
-            "params": params
-        }))?;
-        
-        let id = response
-            .as_i64()
-            .ok_or_else(|| crate::error::GuiError::InvalidResponse("Invalid id".to_string()))?;
+        let aid = activity.id();
+        let id = raw::create_checkbox(activity, &raw::CreateCheckbox {
+            aid,
+            parent,
+            text: Some(text.to_string()),
+            checked: Some(checked),
+            ..Self::default_params()
+        })?;
         
         Ok(Checkbox {
             view: View::new(id),
@@ -57,27 +48,18 @@ impl Checkbox {
     
     /// Set the checkbox text
     pub fn set_text(&self, activity: &mut Activity, text: &str) -> Result<()> {
-        activity.send(&json!({
-            "method": "setText",
-            "params": {
-                "aid": self.aid,
-                "id": self.view.id(),
-                "text": text
-            }
-        }))?;
-        Ok(())
+        TextWidget::set_text(self, activity, text)
     }
     
     /// Set checked state
     pub fn set_checked(&self, activity: &mut Activity, checked: bool) -> Result<()> {
-        activity.send(&json!({
-            "method": "setChecked",
-            "params": {
-                "aid": self.aid,
-                "id": self.view.id(),
-                "checked": checked
-            }
-        }))?;
-        Ok(())
+        CheckableWidget::set_checked(self, activity, checked)
     }
 }
+
+impl_widget!(Checkbox);
+impl_create!(Checkbox => CreateCheckbox { checked: Some(false) });
+
+impl TextWidget for Checkbox {}
+
+impl CheckableWidget for Checkbox {}
@@ -1,8 +1,10 @@
 //! EditText component
 
-use serde_json::json;
 use crate::activity::Activity;
+use crate::raw;
 use crate::view::View;
+use crate::builder::{impl_create, sealed::Create};
+use crate::widget::{impl_widget, TextWidget};
 use crate::error::Result;
 
 /// An EditText allows text input
@@ -30,28 +32,15 @@ impl EditText {
         singleline: bool,
         input_type: &str
     ) -> Result<Self> {
-        let mut params = json!({
-            "aid": activity.id(),
-            "text": text,
-            "singleline": singleline,
-            "line": true,
-            "blockinput": false,
-            "type": input_type
-        });
-        
-        // Only set parent if explicitly provided
-        if let Some(parent_id) = parent {
-            params["parent"] = json!(parent_id);
-        }
-        
-        let response = activity.send_read(&json!({
-            "method": "createEditText",
-            "params": params
-        }))?;
-        
-        let id = response
-            .as_i64()
-            .ok_or_else(|| crate::error::GuiError::InvalidResponse("Invalid id".to_string()))?;
+        let aid = activity.id();
+        let id = raw::create_edit_text(activity, &raw::CreateEditText {
+            aid,
+            parent,
+            text: Some(text.to_string()),
+            singleline: Some(singleline),
+            r#type: Some(input_type.to_string()),
+            ..Self::default_params()
+        })?;
         
         Ok(EditText {
             view: View::new(id),
@@ -71,43 +60,30 @@ impl EditText {
     
     /// Set the text content
     pub fn set_text(&self, activity: &mut Activity, text: &str) -> Result<()> {
-        activity.send(&json!({
-            "method": "setText",
-            "params": {
-                "aid": self.aid,
-                "id": self.view.id(),
-                "text": text
-            }
-        }))?;
-        Ok(())
+        TextWidget::set_text(self, activity, text)
     }
     
     /// Set hint text
     pub fn set_hint(&self, activity: &mut Activity, hint: &str) -> Result<()> {
-        activity.send(&json!({
-            "method": "setHint",
-            "params": {
-                "aid": self.aid,
-                "id": self.view.id(),
-                "hint": hint
-            }
-        }))?;
-        Ok(())
+        raw::set_hint(activity, &raw::SetHint {
+            aid: self.aid,
+            id: self.view.id(),
+            hint: hint.to_string(),
+        })
     }
     
     /// Get the text content
     pub fn get_text(&self, activity: &mut Activity) -> Result<String> {
-        let response = activity.send_read(&json!({
-            "method": "getText",
-            "params": {
-                "aid": self.aid,
-                "id": self.view.id()
-            }
-        }))?;
-        
-        Ok(response
-            .as_str()
-            .unwrap_or("")
-            .to_string())
+        TextWidget::get_text(self, activity)
     }
 }
+
+impl_widget!(EditText);
+impl_create!(EditText => CreateEditText {
+    singleline: Some(true),
+    line: Some(true),
+    blockinput: Some(false),
+    r#type: Some("text".to_string()),
+});
+
+impl TextWidget for EditText {}
@@ -1,8 +1,10 @@
 //! ImageView component
 
-use serde_json::json;
 use crate::activity::Activity;
+use crate::raw;
 use crate::view::View;
+use crate::builder::{impl_create, sealed::Create};
+use crate::widget::impl_widget;
 use crate::error::Result;
 
 /// An ImageView displays images
@@ -14,23 +16,12 @@ pub struct ImageView {
 impl ImageView {
     /// Create a new ImageView
     pub fn new(activity: &mut Activity, parent: Option<i64>) -> Result<Self> {
-        let mut params = json!({
-            "aid": activity.id()
-        });
-        
-        // Only set parent if explicitly provided
-        if let Some(parent_id) = parent {
-            params["parent"] = json!(parent_id);
-        }
-        
-        let response = activity.send_read(&json!({
-            "method": "createImageView",
-            "params": params
-        }))?;
-        
-        let id = response
-            .as_i64()
-            .ok_or_else(|| crate::error::GuiError::InvalidResponse("Invalid id".to_string()))?;
+        let aid = activity.id();
+        let id = raw::create_image_view(activity, &raw::CreateImageView {
+            aid,
+            parent,
+            ..Self::default_params()
+        })?;
         
         Ok(ImageView {
             view: View::new(id),
@@ -53,28 +44,23 @@ impl ImageView {
     /// The image should be base64 encoded PNG or JPEG data.
     /// You can use the `base64` crate to encode image bytes.
     pub fn set_image(&self, activity: &mut Activity, img_base64: &str) -> Result<()> {
-        activity.send(&json!({
-            "method": "setImage",
-            "params": {
-                "aid": self.aid,
-                "id": self.view.id(),
-                "img": img_base64
-            }
-        }))?;
-        Ok(())
+        raw::set_image(activity, &raw::SetImage {
+            aid: self.aid,
+            id: self.view.id(),
+            img: img_base64.to_string(),
+        })
     }
     
     /// Refresh the ImageView
     /// 
     /// This redraws the ImageView. Needed when using shared buffers.
     pub fn refresh(&self, activity: &mut Activity) -> Result<()> {
-        activity.send(&json!({
-            "method": "refreshImageView",
-            "params": {
-                "aid": self.aid,
-                "id": self.view.id()
-            }
-        }))?;
-        Ok(())
+        raw::refresh_image_view(activity, &raw::RefreshImageView {
+            aid: self.aid,
+            id: self.view.id(),
+        })
     }
 }
+
+impl_widget!(ImageView);
+impl_create!(ImageView => CreateImageView);
@@ -1,8 +1,10 @@
 //! Layout components
 
-use serde_json::json;
 use crate::activity::Activity;
+use crate::raw;
 use crate::view::View;
+use crate::builder::{impl_create, sealed::Create};
+use crate::widget::impl_widget;
 use crate::error::Result;
 
 /// A LinearLayout arranges views linearly
@@ -23,24 +25,13 @@ impl LinearLayout {
     /// # Arguments
     /// * `vertical` - If true, arranges children vertically; if false, horizontally
     pub fn new_with_orientation(activity: &mut Activity, parent: Option<i64>, vertical: bool) -> Result<Self> {
-        let mut params = json!({
-            "aid": activity.id(),
-            "vertical": vertical
-        });
-        
-        // Only set parent if explicitly provided
-        if let Some(parent_id) = parent {
-            params["parent"] = json!(parent_id);
-        }
-        
-        let response = activity.send_read(&json!({
-            "method": "createLinearLayout",
-            "params": params
-        }))?;
-        
-        let id = response
-            .as_i64()
-            .ok_or_else(|| crate::error::GuiError::InvalidResponse("Invalid id".to_string()))?;
+        let aid = activity.id();
+        let id = raw::create_linear_layout(activity, &raw::CreateLinearLayout {
+            aid,
+            parent,
+            vertical: Some(vertical),
+            ..Self::default_params()
+        })?;
         
         Ok(LinearLayout {
             view: View::new(id),
@@ -69,25 +60,12 @@ pub struct NestedScrollView {
 impl NestedScrollView {
     /// Create a new NestedScrollView
     pub fn new(activity: &mut Activity, parent: Option<i64>) -> Result<Self> {
-        let mut params = json!({
-            "aid": activity.id(),
-            "nobar": false,
-            "snapping": false
-        });
-        
-        // Only set parent if explicitly provided
-        if let Some(parent_id) = parent {
-            params["parent"] = json!(parent_id);
-        }
-        
-        let response = activity.send_read(&json!({
-            "method": "createNestedScrollView",
-            "params": params
-        }))?;
-        
-        let id = response
-            .as_i64()
-            .ok_or_else(|| crate::error::GuiError::InvalidResponse("Invalid id".to_string()))?;
+        let aid = activity.id();
+        let id = raw::create_nested_scroll_view(activity, &raw::CreateNestedScrollView {
+            aid,
+            parent,
+            ..Self::default_params()
+        })?;
         
         Ok(NestedScrollView {
             view: View::new(id),
@@ -119,23 +97,12 @@ impl FrameLayout {
     /// Children are drawn in the order they are added, with the last child on top.
     /// FrameLayout is useful for overlaying views or creating simple stacked layouts.
     pub fn new(activity: &mut Activity, parent: Option<i64>) -> Result<Self> {
-        let mut params = json!({
-            "aid": activity.id()
-        });
-        
-        // Only set parent if explicitly provided
-        if let Some(parent_id) = parent {
-            params["parent"] = json!(parent_id);
-        }
-        
-        let response = activity.send_read(&json!({
-            "method": "createFrameLayout",
-            "params": params
-        }))?;
-        
-        let id = response
-            .as_i64()
-            .ok_or_else(|| crate::error::GuiError::InvalidResponse("Invalid id".to_string()))?;
+        let aid = activity.id();
+        let id = raw::create_frame_layout(activity, &raw::CreateFrameLayout {
+            aid,
+            parent,
+            ..Self::default_params()
+        })?;
         
         Ok(FrameLayout {
             view: View::new(id),
@@ -172,25 +139,14 @@ impl GridLayout {
     /// * `rows` - Number of rows in the grid
     /// * `cols` - Number of columns in the grid
     pub fn new(activity: &mut Activity, rows: i32, cols: i32, parent: Option<i64>) -> Result<Self> {
-        let mut params = json!({
-            "aid": activity.id(),
-            "rows": rows,
-            "cols": cols
-        });
-        
-        // Only set parent if explicitly provided
-        if let Some(parent_id) = parent {
-            params["parent"] = json!(parent_id);
-        }
-        
-        let response = activity.send_read(&json!({
-            "method": "createGridLayout",
-            "params": params
-        }))?;
-        
-        let id = response
-            .as_i64()
-            .ok_or_else(|| crate::error::GuiError::InvalidResponse("Invalid id".to_string()))?;
+        let aid = activity.id();
+        let id = raw::create_grid_layout(activity, &raw::CreateGridLayout {
+            aid,
+            parent,
+            rows: Some(rows.into()),
+            cols: Some(cols.into()),
+            ..Self::default_params()
+        })?;
         
         Ok(GridLayout {
             view: View::new(id),
@@ -252,26 +208,12 @@ pub struct HorizontalScrollView {
 impl HorizontalScrollView {
     /// Create a new HorizontalScrollView
     pub fn new(activity: &mut Activity, parent: Option<i64>) -> Result<Self> {
-        let mut params = json!({
-            "aid": activity.id(),
-            "nobar": false,
-            "snapping": false,
-            "fillviewport": true  // Set to true to allow child views to fill the viewport
-        });
-        
-        // Only set parent if explicitly provided
-        if let Some(parent_id) = parent {
-            params["parent"] = json!(parent_id);
-        }
-        
-        let response = activity.send_read(&json!({
-            "method": "createHorizontalScrollView",
-            "params": params
-        }))?;
-        
-        let id = response
-            .as_i64()
-            .ok_or_else(|| crate::error::GuiError::InvalidResponse("Invalid id".to_string()))?;
+        let aid = activity.id();
+        let id = raw::create_horizontal_scroll_view(activity, &raw::CreateHorizontalScrollView {
+            aid,
+            parent,
+            ..Self::default_params()
+        })?;
         
         Ok(HorizontalScrollView {
             view: View::new(id),
@@ -282,25 +224,15 @@ impl HorizontalScrollView {
     /// Create a new HorizontalScrollView with custom parameters
     pub fn new_with_params(activity: &mut Activity, parent: Option<i64>, 
                           fillviewport: bool, snapping: bool, nobar: bool) -> Result<Self> {
-        let mut params = json!({
-            "aid": activity.id(),
-            "nobar": nobar,
-            "snapping": snapping,
-            "fillviewport": fillviewport
-        });
-        
-        if let Some(parent_id) = parent {
-            params["parent"] = json!(parent_id);
-        }
-        
-        let response = activity.send_read(&json!({
-            "method": "createHorizontalScrollView",
-            "params": params
-        }))?;
-        
-        let id = response
-            .as_i64()
-            .ok_or_else(|| crate::error::GuiError::InvalidResponse("Invalid id".to_string()))?;
+        let aid = activity.id();
+        let id = raw::create_horizontal_scroll_view(activity, &raw::CreateHorizontalScrollView {
+            aid,
+            parent,
+            nobar: Some(nobar),
+            snapping: Some(snapping),
+            fillviewport: Some(fillviewport),
+            ..Self::default_params()
+        })?;
         
         Ok(HorizontalScrollView {
             view: View::new(id),
@@ -320,22 +252,11 @@ impl HorizontalScrollView {
     
     /// Get the scroll position (x, y) in pixels
     pub fn get_scroll_position(&self, activity: &mut Activity) -> Result<(i32, i32)> {
-        let response = activity.send_read(&json!({
-            "method": "getScrollPosition",
-            "params": {
-                "aid": self.aid,
-                "id": self.view.id()
-            }
-        }))?;
-        
-        // Response is an array [x, y]
-        if let Some(arr) = response.as_array() {
-            let x = arr.get(0).and_then(|v| v.as_i64()).unwrap_or(0) as i32;
-            let y = arr.get(1).and_then(|v| v.as_i64()).unwrap_or(0) as i32;
-            Ok((x, y))
-        } else {
-            Ok((0, 0))
-        }
+        let (x, y) = raw::get_scroll_position(activity, &raw::GetScrollPosition {
+            aid: self.aid,
+            id: self.view.id(),
+        })?;
+        Ok((x as i32, y as i32))
     }
     
     /// Set the scroll position
@@ -345,17 +266,13 @@ impl HorizontalScrollView {
     /// * `y` - Vertical scroll position in pixels (usually 0 for HorizontalScrollView)
     /// * `smooth` - Whether to scroll smoothly or jump immediately
     pub fn set_scroll_position(&self, activity: &mut Activity, x: i32, y: i32, smooth: bool) -> Result<()> {
-        activity.send(&json!({
-            "method": "setScrollPosition",
-            "params": {
-                "aid": self.aid,
-                "id": self.view.id(),
-                "x": x,
-                "y": y,
-                "soft": smooth
-            }
-        }))?;
-        Ok(())
+        raw::set_scroll_position(activity, &raw::SetScrollPosition {
+            aid: self.aid,
+            id: self.view.id(),
+            x: x.into(),
+            y: y.into(),
+            soft: Some(smooth),
+        })
     }
 }
 
@@ -368,23 +285,12 @@ pub struct SwipeRefreshLayout {
 impl SwipeRefreshLayout {
     /// Create a new SwipeRefreshLayout
     pub fn new(activity: &mut Activity, parent: Option<i64>) -> Result<Self> {
-        let mut params = json!({
-            "aid": activity.id()
-        });
-        
-        // Only set parent if explicitly provided
-        if let Some(parent_id) = parent {
-            params["parent"] = json!(parent_id);
-        }
-        
-        let response = activity.send_read(&json!({
-            "method": "createSwipeRefreshLayout",
-            "params": params
-        }))?;
-        
-        let id = response
-            .as_i64()
-            .ok_or_else(|| crate::error::GuiError::InvalidResponse("Invalid id".to_string()))?;
+        let aid = activity.id();
+        let id = raw::create_swipe_refresh_layout(activity, &raw::CreateSwipeRefreshLayout {
+            aid,
+            parent,
+            ..Self::default_params()
+        })?;
         
         Ok(SwipeRefreshLayout {
             view: View::new(id),
@@ -406,15 +312,11 @@ impl SwipeRefreshLayout {
     /// 
     /// Call with false after refresh is complete to stop the animation
     pub fn set_refreshing(&self, activity: &mut Activity, refreshing: bool) -> Result<()> {
-        activity.send(&json!({
-            "method": "setRefreshing",
-            "params": {
-                "aid": self.aid,
-                "id": self.view.id(),
-                "refresh": refreshing
-            }
-        }))?;
-        Ok(())
+        raw::set_refreshing(activity, &raw::SetRefreshing {
+            aid: self.aid,
+            id: self.view.id(),
+            refresh: refreshing,
+        })
     }
 }
 
@@ -430,23 +332,12 @@ pub struct TabLayout {
 impl TabLayout {
     /// Create a new TabLayout
     pub fn new(activity: &mut Activity, parent: Option<i64>) -> Result<Self> {
-        let mut params = json!({
-            "aid": activity.id()
-        });
-        
-        // Only set parent if explicitly provided
-        if let Some(parent_id) = parent {
-            params["parent"] = json!(parent_id);
-        }
-        
-        let response = activity.send_read(&json!({
-            "method": "createTabLayout",
-            "params": params
-        }))?;
-        
-        let id = response
-            .as_i64()
-            .ok_or_else(|| crate::error::GuiError::InvalidResponse("Invalid id".to_string()))?;
+        let aid = activity.id();
+        let id = raw::create_tab_layout(activity, &raw::CreateTabLayout {
+            aid,
+            parent,
+            ..Self::default_params()
+        })?;
         
         Ok(TabLayout {
             view: View::new(id),
@@ -471,18 +362,18 @@ impl TabLayout {
     /// 
     /// # Example
     /// ```no_run
+    /// # use termux_gui::{Activity, TabLayout, Result};
+    /// # fn example(activity: &mut Activity, tab_layout: &TabLayout) -> Result<()> {
     /// tab_layout.set_list(activity, &["Page 1", "Page 2", "Page 3"])?;
+    /// # Ok(())
+    /// # }
     /// ```
     pub fn set_list(&self, activity: &mut Activity, tabs: &[&str]) -> Result<()> {
-        activity.send(&json!({
-            "method": "setList",
-            "params": {
-                "aid": self.aid,
-                "id": self.view.id(),
-                "list": tabs
-            }
-        }))?;
-        Ok(())
+        raw::set_list(activity, &raw::SetList {
+            aid: self.aid,
+            id: self.view.id(),
+            list: tabs.iter().map(|item| item.to_string()).collect(),
+        })
     }
     
     /// Programmatically select a tab
@@ -492,18 +383,46 @@ impl TabLayout {
     /// 
     /// # Example
     /// ```no_run
+    /// # use termux_gui::{Activity, TabLayout, Result};
+    /// # fn example(activity: &mut Activity, tab_layout: &TabLayout) -> Result<()> {
     /// // Select the second tab (index 1)
     /// tab_layout.select_tab(activity, 1)?;
+    /// # Ok(())
+    /// # }
     /// ```
     pub fn select_tab(&self, activity: &mut Activity, index: usize) -> Result<()> {
-        activity.send(&json!({
-            "method": "selectTab",
-            "params": {
-                "aid": self.aid,
-                "id": self.view.id(),
-                "tab": index
-            }
-        }))?;
-        Ok(())
+        raw::select_tab(activity, &raw::SelectTab {
+            aid: self.aid,
+            id: self.view.id(),
+            tab: index as i64,
+        })
+    }
+}
+
+impl_widget!(LinearLayout, NestedScrollView, FrameLayout, GridLayout, HorizontalScrollView, SwipeRefreshLayout, TabLayout);
+impl_create!(
+    LinearLayout => CreateLinearLayout { vertical: Some(true) },
+    NestedScrollView => CreateNestedScrollView { nobar: Some(false), snapping: Some(false) },
+    FrameLayout => CreateFrameLayout,
+    // Let child views fill the viewport
+    HorizontalScrollView => CreateHorizontalScrollView {
+        nobar: Some(false),
+        snapping: Some(false),
+        fillviewport: Some(true),
+    },
+    SwipeRefreshLayout => CreateSwipeRefreshLayout,
+    TabLayout => CreateTabLayout,
+);
+
+impl crate::builder::sealed::Create for GridLayout {
+    type Params = raw::CreateGridLayout;
+
+    fn from_parts(view: View, aid: i64, params: &serde_json::Value) -> Self {
+        GridLayout {
+            view,
+            aid,
+            rows: params["rows"].as_i64().unwrap_or_default() as i32,
+            cols: params["cols"].as_i64().unwrap_or_default() as i32,
+        }
     }
 }
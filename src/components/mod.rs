@@ -27,3 +27,234 @@ pub use progress_bar::ProgressBar;
 pub use toggle_button::ToggleButton;
 pub use space::Space;
 pub use web_view::WebView;
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::error::Result;
+    use crate::testing::MockService;
+
+    #[test]
+    fn text_view() -> Result<()> {
+        let (mock, mut activity) = MockService::activity(false)?;
+        let text = TextView::new(&mut activity, "Hello", None)?;
+        text.set_text(&mut activity, "Bye")?;
+        text.set_text_size(&mut activity, 18)?;
+        text.set_text_color(&mut activity, 0xFF00FF00u32)?;
+
+        let created = &mock.calls_to("createTextView")[0];
+        assert_eq!(created["aid"], json!(activity.id()));
+        assert_eq!(created["text"], "Hello");
+        assert_eq!(mock.calls_to("setText")[0]["text"], "Bye");
+        assert_eq!(mock.calls_to("setTextSize")[0]["size"], json!(18));
+        assert_eq!(mock.calls_to("setTextColor")[0]["color"], json!(0xFF00FF00u32 as i32));
+        assert_eq!(text.get_text(&mut activity)?, "Bye");
+        Ok(())
+    }
+
+    #[test]
+    fn button() -> Result<()> {
+        let (mock, mut activity) = MockService::activity(false)?;
+        let layout = LinearLayout::new(&mut activity, None)?;
+        let button = Button::new(&mut activity, "OK", Some(layout.id()))?;
+
+        let created = &mock.calls_to("createButton")[0];
+        assert_eq!(created["parent"], json!(layout.id()));
+        assert_eq!(created["text"], "OK");
+        assert_eq!(created["allcaps"], json!(false));
+        assert_ne!(button.id(), layout.id());
+        Ok(())
+    }
+
+    #[test]
+    fn edit_text() -> Result<()> {
+        let (mock, mut activity) = MockService::activity(false)?;
+        EditText::new(&mut activity, "one", None)?;
+        EditText::new_multiline(&mut activity, "two", None)?;
+        let number = EditText::new_with_options(&mut activity, "3", None, true, "number")?;
+        number.set_hint(&mut activity, "Count")?;
+
+        let created = mock.calls_to("createEditText");
+        assert_eq!(created[0]["singleline"], json!(true));
+        assert_eq!(created[0]["type"], "text");
+        assert_eq!(created[1]["singleline"], json!(false));
+        assert_eq!(created[2]["type"], "number");
+        assert_eq!(mock.calls_to("setHint")[0]["hint"], "Count");
+        assert_eq!(number.get_text(&mut activity)?, "3");
+        Ok(())
+    }
+
+    #[test]
+    fn checkables() -> Result<()> {
+        let (mock, mut activity) = MockService::activity(false)?;
+        let checkbox = Checkbox::new(&mut activity, "a", None)?;
+        Switch::new_with_checked(&mut activity, "b", None, true)?;
+        RadioButton::new(&mut activity, "c", None)?;
+        ToggleButton::new_with_checked(&mut activity, "d", None, true)?;
+        checkbox.set_checked(&mut activity, true)?;
+
+        assert_eq!(mock.calls_to("createCheckbox")[0]["checked"], json!(false));
+        assert_eq!(mock.calls_to("createSwitch")[0]["checked"], json!(true));
+        assert_eq!(mock.calls_to("createRadioButton")[0]["checked"], json!(false));
+        assert_eq!(mock.calls_to("createToggleButton")[0]["checked"], json!(true));
+
+        let set = &mock.calls_to("setChecked")[0];
+        assert_eq!(set["id"], json!(checkbox.id()));
+        assert_eq!(set["checked"], json!(true));
+        Ok(())
+    }
+
+    #[test]
+    fn radio_group() -> Result<()> {
+        let (mock, mut activity) = MockService::activity(false)?;
+        let group = RadioGroup::new(&mut activity, None)?;
+        RadioButton::new(&mut activity, "a", Some(group.id()))?;
+
+        assert_eq!(mock.calls_to("createRadioGroup").len(), 1);
+        assert_eq!(mock.calls_to("createRadioButton")[0]["parent"], json!(group.id()));
+        Ok(())
+    }
+
+    #[test]
+    fn spinner() -> Result<()> {
+        let (mock, mut activity) = MockService::activity(false)?;
+        let spinner = Spinner::new(&mut activity, None)?;
+        spinner.set_list(&mut activity, &["a", "b"])?;
+        spinner.select_item(&mut activity, 1)?;
+        spinner.refresh(&mut activity)?;
+
+        assert_eq!(mock.calls_to("setList")[0]["list"], json!(["a", "b"]));
+        assert_eq!(mock.calls_to("selectItem")[0]["item"], json!(1));
+        assert_eq!(mock.calls_to("refreshSpinner")[0]["id"], json!(spinner.id()));
+        Ok(())
+    }
+
+    #[test]
+    fn image_view() -> Result<()> {
+        let (mock, mut activity) = MockService::activity(false)?;
+        let image = ImageView::new(&mut activity, None)?;
+        image.set_image(&mut activity, "aGVsbG8=")?;
+        image.refresh(&mut activity)?;
+
+        assert_eq!(mock.calls_to("setImage")[0]["img"], "aGVsbG8=");
+        assert_eq!(mock.calls_to("refreshImageView")[0]["id"], json!(image.id()));
+        Ok(())
+    }
+
+    #[test]
+    fn progress_bar() -> Result<()> {
+        let (mock, mut activity) = MockService::activity(false)?;
+        let progress = ProgressBar::new(&mut activity, None)?;
+        progress.set_progress(&mut activity, 42)?;
+
+        let set = &mock.calls_to("setProgress")[0];
+        assert_eq!(set["id"], json!(progress.id()));
+        assert_eq!(set["progress"], json!(42));
+        Ok(())
+    }
+
+    #[test]
+    fn space() -> Result<()> {
+        let (mock, mut activity) = MockService::activity(false)?;
+        let space = Space::new(&mut activity, None)?;
+
+        assert_eq!(mock.calls_to("createSpace")[0]["aid"], json!(activity.id()));
+        assert!(space.id() >= 0);
+        Ok(())
+    }
+
+    #[test]
+    fn linear_layout() -> Result<()> {
+        let (mock, mut activity) = MockService::activity(false)?;
+        LinearLayout::new(&mut activity, None)?;
+        LinearLayout::new_with_orientation(&mut activity, None, false)?;
+
+        let created = mock.calls_to("createLinearLayout");
+        assert_eq!(created[0]["vertical"], json!(true));
+        assert_eq!(created[1]["vertical"], json!(false));
+        Ok(())
+    }
+
+    #[test]
+    fn frame_and_grid_layout() -> Result<()> {
+        let (mock, mut activity) = MockService::activity(false)?;
+        let frame = FrameLayout::new(&mut activity, None)?;
+        GridLayout::new(&mut activity, 2, 3, Some(frame.id()))?;
+
+        let created = &mock.calls_to("createGridLayout")[0];
+        assert_eq!(created["parent"], json!(frame.id()));
+        assert_eq!((created["rows"].clone(), created["cols"].clone()), (json!(2), json!(3)));
+        Ok(())
+    }
+
+    #[test]
+    fn scroll_views() -> Result<()> {
+        let (mock, mut activity) = MockService::activity(false)?;
+        NestedScrollView::new(&mut activity, None)?;
+        let scroll = HorizontalScrollView::new(&mut activity, None)?;
+        HorizontalScrollView::new_with_params(&mut activity, None, false, true, true)?;
+        scroll.set_scroll_position(&mut activity, 10, 0, true)?;
+        mock.set_response("getScrollPosition", json!([10, 0]));
+
+        let nested = &mock.calls_to("createNestedScrollView")[0];
+        assert_eq!((nested["nobar"].clone(), nested["snapping"].clone()), (json!(false), json!(false)));
+        let horizontal = mock.calls_to("createHorizontalScrollView");
+        assert_eq!(horizontal[0]["fillviewport"], json!(true));
+        assert_eq!(horizontal[1]["fillviewport"], json!(false));
+        assert_eq!(horizontal[1]["nobar"], json!(true));
+
+        let set = &mock.calls_to("setScrollPosition")[0];
+        assert_eq!((set["x"].clone(), set["soft"].clone()), (json!(10), json!(true)));
+        assert_eq!(scroll.get_scroll_position(&mut activity)?, (10, 0));
+        Ok(())
+    }
+
+    #[test]
+    fn swipe_refresh_layout() -> Result<()> {
+        let (mock, mut activity) = MockService::activity(false)?;
+        let refresh = SwipeRefreshLayout::new(&mut activity, None)?;
+        refresh.set_refreshing(&mut activity, false)?;
+
+        let set = &mock.calls_to("setRefreshing")[0];
+        assert_eq!(set["id"], json!(refresh.id()));
+        assert_eq!(set["refresh"], json!(false));
+        Ok(())
+    }
+
+    #[test]
+    fn tab_layout() -> Result<()> {
+        let (mock, mut activity) = MockService::activity(false)?;
+        let tabs = TabLayout::new(&mut activity, None)?;
+        tabs.set_list(&mut activity, &["One", "Two"])?;
+        tabs.select_tab(&mut activity, 1)?;
+
+        assert_eq!(mock.calls_to("setList")[0]["list"], json!(["One", "Two"]));
+        assert_eq!(mock.calls_to("selectTab")[0]["tab"], json!(1));
+        Ok(())
+    }
+
+    #[test]
+    fn web_view() -> Result<()> {
+        let (mock, mut activity) = MockService::activity(false)?;
+        let web = WebView::new(&mut activity, None)?;
+        web.load_uri(&mut activity, "https://example.com")?;
+        web.set_data(&mut activity, "<p>hi</p>")?;
+        web.evaluate_js(&mut activity, "1 + 1")?;
+        web.go_back(&mut activity)?;
+
+        assert_eq!(mock.calls_to("loadURI")[0]["uri"], "https://example.com");
+        let data = &mock.calls_to("setData")[0];
+        assert_eq!(data["doc"], "PHA+aGk8L3A+");
+        assert_eq!(data["base64"], json!(true));
+        assert_eq!(mock.calls_to("evaluateJS")[0]["code"], "1 + 1");
+        assert_eq!(mock.calls_to("goBack").len(), 1);
+
+        // The mock answers with what was asked for; the user could deny it
+        assert!(web.allow_javascript(&mut activity, true)?);
+        mock.set_response("allowJavascript", json!(false));
+        assert!(!web.allow_javascript(&mut activity, true)?);
+        Ok(())
+    }
+}
@@ -1,8 +1,10 @@
 //! ProgressBar component
 
-use serde_json::json;
 use crate::activity::Activity;
+use crate::raw;
 use crate::view::View;
+use crate::builder::{impl_create, sealed::Create};
+use crate::widget::impl_widget;
 use crate::error::Result;
 
 /// A ProgressBar displays progress from 0 to 100
@@ -14,23 +16,12 @@ pub struct ProgressBar {
 impl ProgressBar {
     /// Create a new ProgressBar
     pub fn new(activity: &mut Activity, parent: Option<i64>) -> Result<Self> {
-        let mut params = json!({
-            "aid": activity.id()
-        });
-        
-        // Only set parent if explicitly provided
-        if let Some(parent_id) = parent {
-            params["parent"] = json!(parent_id);
-        }
-        
-        let response = activity.send_read(&json!({
-            "method": "createProgressBar",
-            "params": params
-        }))?;
-        
-        let id = response
-            .as_i64()
-            .ok_or_else(|| crate::error::GuiError::InvalidResponse("Invalid id".to_string()))?;
+        let aid = activity.id();
+        let id = raw::create_progress_bar(activity, &raw::CreateProgressBar {
+            aid,
+            parent,
+            ..Self::default_params()
+        })?;
         
         Ok(ProgressBar {
             view: View::new(id),
@@ -55,14 +46,13 @@ impl ProgressBar {
         // Clamp progress to valid range
         let progress = progress.clamp(0, 100);
         
-        activity.send(&json!({
-            "method": "setProgress",
-            "params": {
-                "aid": self.aid,
-                "id": self.view.id(),
-                "progress": progress
-            }
-        }))?;
-        Ok(())
+        raw::set_progress(activity, &raw::SetProgress {
+            aid: self.aid,
+            id: self.view.id(),
+            progress: progress.into(),
+        })
     }
 }
+
+impl_widget!(ProgressBar);
+impl_create!(ProgressBar => CreateProgressBar);
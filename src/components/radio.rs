@@ -1,8 +1,10 @@
 //! Radio button components
 
-use serde_json::json;
 use crate::activity::Activity;
+use crate::raw;
 use crate::view::View;
+use crate::builder::{impl_create, sealed::Create};
+use crate::widget::{impl_widget, CheckableWidget, TextWidget};
 use crate::error::Result;
 
 /// A RadioButton in a group
@@ -19,25 +21,14 @@ impl RadioButton {
     
     /// Create a new RadioButton with specified checked state
     pub fn new_with_checked(activity: &mut Activity, text: &str, parent: Option<i64>, checked: bool) -> Result<Self> {
-        let mut params = json!({
-            "aid": activity.id(),
-            "text": text,
-            "checked": checked
-        });
-        
-        // Only set parent if explicitly provided
-        if let Some(parent_id) = parent {
-            params["parent"] = json!(parent_id);
-        }
-        
-        let response = activity.send_read(&json!({
-            "method": "createRadioButton",
-            "params": params
-        }))?;
-        
-        let id = response
-            .as_i64()
-            .ok_or_else(|| crate::error::GuiError::InvalidResponse("Invalid id".to_string()))?;
+        let aid = activity.id();
+        let id = raw::create_radio_button(activity, &raw::CreateRadioButton {
+            aid,
+            parent,
+            text: Some(text.to_string()),
+            checked: Some(checked),
+            ..Self::default_params()
+        })?;
         
         Ok(RadioButton {
             view: View::new(id),
@@ -57,28 +48,12 @@ impl RadioButton {
     
     /// Set the radio button text
     pub fn set_text(&self, activity: &mut Activity, text: &str) -> Result<()> {
-        activity.send(&json!({
-            "method": "setText",
-            "params": {
-                "aid": self.aid,
-                "id": self.view.id(),
-                "text": text
-            }
-        }))?;
-        Ok(())
+        TextWidget::set_text(self, activity, text)
     }
     
     /// Set checked state
     pub fn set_checked(&self, activity: &mut Activity, checked: bool) -> Result<()> {
-        activity.send(&json!({
-            "method": "setChecked",
-            "params": {
-                "aid": self.aid,
-                "id": self.view.id(),
-                "checked": checked
-            }
-        }))?;
-        Ok(())
+        CheckableWidget::set_checked(self, activity, checked)
     }
 }
 
@@ -92,23 +67,12 @@ pub struct RadioGroup {
 impl RadioGroup {
     /// Create a new RadioGroup
     pub fn new(activity: &mut Activity, parent: Option<i64>) -> Result<Self> {
-        let mut params = json!({
-            "aid": activity.id()
-        });
-        
-        // Only set parent if explicitly provided
-        if let Some(parent_id) = parent {
-            params["parent"] = json!(parent_id);
-        }
-        
-        let response = activity.send_read(&json!({
-            "method": "createRadioGroup",
-            "params": params
-        }))?;
-        
-        let id = response
-            .as_i64()
-            .ok_or_else(|| crate::error::GuiError::InvalidResponse("Invalid id".to_string()))?;
+        let aid = activity.id();
+        let id = raw::create_radio_group(activity, &raw::CreateRadioGroup {
+            aid,
+            parent,
+            ..Self::default_params()
+        })?;
         
         Ok(RadioGroup {
             view: View::new(id),
@@ -126,3 +90,13 @@ impl RadioGroup {
         &self.view
     }
 }
+
+impl_widget!(RadioButton, RadioGroup);
+impl_create!(
+    RadioButton => CreateRadioButton { checked: Some(false) },
+    RadioGroup => CreateRadioGroup,
+);
+
+impl TextWidget for RadioButton {}
+
+impl CheckableWidget for RadioButton {}
@@ -1,8 +1,10 @@
 //! Space component
 
-use serde_json::json;
 use crate::activity::Activity;
+use crate::raw;
 use crate::view::View;
+use crate::builder::{impl_create, sealed::Create};
+use crate::widget::impl_widget;
 use crate::error::Result;
 
 /// A Space creates empty space in layouts
@@ -11,29 +13,19 @@ use crate::error::Result;
 /// You can control its size using width, height, and layout params.
 pub struct Space {
     view: View,
+    #[allow(dead_code)]
     aid: i64,
 }
 
 impl Space {
     /// Create a new Space
     pub fn new(activity: &mut Activity, parent: Option<i64>) -> Result<Self> {
-        let mut params = json!({
-            "aid": activity.id()
-        });
-        
-        // Only set parent if explicitly provided
-        if let Some(parent_id) = parent {
-            params["parent"] = json!(parent_id);
-        }
-        
-        let response = activity.send_read(&json!({
-            "method": "createSpace",
-            "params": params
-        }))?;
-        
-        let id = response
-            .as_i64()
-            .ok_or_else(|| crate::error::GuiError::InvalidResponse("Invalid id".to_string()))?;
+        let aid = activity.id();
+        let id = raw::create_space(activity, &raw::CreateSpace {
+            aid,
+            parent,
+            ..Self::default_params()
+        })?;
         
         Ok(Space {
             view: View::new(id),
@@ -51,3 +43,6 @@ impl Space {
         &self.view
     }
 }
+
+impl_widget!(Space);
+impl_create!(Space => CreateSpace);
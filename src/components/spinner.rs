@@ -10,8 +10,7 @@
 //! ## Example
 //!
 //! ```rust,no_run
-//! use termux_gui::{Activity, Result};
-//! use termux_gui::connection::read_message;
+//! use termux_gui::{Activity, Event, Result};
 //!
 //! # fn main() -> Result<()> {
 //! let mut activity = Activity::new(false)?;
@@ -22,22 +21,25 @@
 //!
 //! // Event handling - use string matching, not index!
 //! loop {
-//!     let event = read_message(activity.event_stream())?;
-//!     if event["type"] == "itemselected" {
-//!         let selected_text = event["value"]["selected"].as_str().unwrap_or("");
-//!         match selected_text {
-//!             "Option 1" => println!("First option selected"),
-//!             "Option 2" => println!("Second option selected"),
-//!             _ => {}
+//!     let event = activity.next_event()?;
+//!     if let Event::ItemSelected { id, selected, .. } = &event {
+//!         if *id == spinner.id() {
+//!             match selected.as_text().unwrap_or("") {
+//!                 "Option 1" => println!("First option selected"),
+//!                 "Option 2" => println!("Second option selected"),
+//!                 _ => {}
+//!             }
 //!         }
 //!     }
 //! }
 //! # }
 //! ```
 
-use serde_json::json;
 use crate::activity::Activity;
+use crate::raw;
 use crate::view::View;
+use crate::builder::{impl_create, sealed::Create};
+use crate::widget::impl_widget;
 use crate::error::Result;
 
 /// A Spinner is a dropdown list
@@ -52,23 +54,12 @@ pub struct Spinner {
 impl Spinner {
     /// Create a new Spinner
     pub fn new(activity: &mut Activity, parent: Option<i64>) -> Result<Self> {
-        let mut params = json!({
-            "aid": activity.id()
-        });
-        
-        // Only set parent if explicitly provided
-        if let Some(parent_id) = parent {
-            params["parent"] = json!(parent_id);
-        }
-        
-        let response = activity.send_read(&json!({
-            "method": "createSpinner",
-            "params": params
-        }))?;
-        
-        let id = response
-            .as_i64()
-            .ok_or_else(|| crate::error::GuiError::InvalidResponse("Invalid id".to_string()))?;
+        let aid = activity.id();
+        let id = raw::create_spinner(activity, &raw::CreateSpinner {
+            aid,
+            parent,
+            ..Self::default_params()
+        })?;
         
         Ok(Spinner {
             view: View::new(id),
@@ -88,39 +79,27 @@ impl Spinner {
     
     /// Set the list of options
     pub fn set_list(&self, activity: &mut Activity, items: &[&str]) -> Result<()> {
-        activity.send(&json!({
-            "method": "setList",
-            "params": {
-                "aid": self.aid,
-                "id": self.view.id(),
-                "list": items
-            }
-        }))?;
-        Ok(())
+        raw::set_list(activity, &raw::SetList {
+            aid: self.aid,
+            id: self.view.id(),
+            list: items.iter().map(|item| item.to_string()).collect(),
+        })
     }
     
     /// Select an item by index
     pub fn select_item(&self, activity: &mut Activity, index: i32) -> Result<()> {
-        activity.send(&json!({
-            "method": "selectItem",
-            "params": {
-                "aid": self.aid,
-                "id": self.view.id(),
-                "item": index
-            }
-        }))?;
-        Ok(())
+        raw::select_item(activity, &raw::SelectItem {
+            aid: self.aid,
+            id: self.view.id(),
+            item: index.into(),
+        })
     }
     
     /// Refresh the spinner (needed after setList to ensure display is updated)
     pub fn refresh(&self, activity: &mut Activity) -> Result<()> {
-        activity.send(&json!({
-            "method": "refreshSpinner",
-            "params": {
-                "aid": self.aid,
-                "id": self.view.id()
-            }
-        }))?;
-        Ok(())
+        raw::refresh_spinner(activity, &raw::RefreshSpinner { aid: self.aid, id: self.view.id() })
     }
 }
+
+impl_widget!(Spinner);
+impl_create!(Spinner => CreateSpinner);
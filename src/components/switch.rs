@@ -1,8 +1,10 @@
 //! Switch component
 
-use serde_json::json;
 use crate::activity::Activity;
+use crate::raw;
 use crate::view::View;
+use crate::builder::{impl_create, sealed::Create};
+use crate::widget::{impl_widget, CheckableWidget, TextWidget};
 use crate::error::Result;
 
 /// A Switch can be toggled on or off
@@ -19,25 +21,14 @@ impl Switch {
     
     /// Create a new Switch with specified checked state
     pub fn new_with_checked(activity: &mut Activity, text: &str, parent: Option<i64>, checked: bool) -> Result<Self> {
-        let mut params = json!({
-            "aid": activity.id(),
-            "text": text,
-            "checked": checked
-        });
-        
-        // Only set parent if explicitly provided
-        if let Some(parent_id) = parent {
-            params["parent"] = json!(parent_id);
-        }
-        
-        let response = activity.send_read(&json!({
-            "method": "createSwitch",
-            "params": params
-        }))?;
-        
-        let id = response
-            .as_i64()
-            .ok_or_else(|| crate::error::GuiError::InvalidResponse("Invalid id".to_string()))?;
+        let aid = activity.id();
+        let id = raw::create_switch(activity, &raw::CreateSwitch {
+            aid,
+            parent,
+            text: Some(text.to_string()),
+            checked: Some(checked),
+            ..Self::default_params()
+        })?;
         
         Ok(Switch {
             view: View::new(id),
@@ -57,27 +48,18 @@ impl Switch {
     
     /// Set the switch text
     pub fn set_text(&self, activity: &mut Activity, text: &str) -> Result<()> {
-        activity.send(&json!({
-            "method": "setText",
-            "params": {
-                "aid": self.aid,
-                "id": self.view.id(),
-                "text": text
-            }
-        }))?;
-        Ok(())
+        TextWidget::set_text(self, activity, text)
     }
     
     /// Set checked state
     pub fn set_checked(&self, activity: &mut Activity, checked: bool) -> Result<()> {
-        activity.send(&json!({
-            "method": "setChecked",
-            "params": {
-                "aid": self.aid,
-                "id": self.view.id(),
-                "checked": checked
-            }
-        }))?;
-        Ok(())
+        CheckableWidget::set_checked(self, activity, checked)
     }
 }
+
+impl_widget!(Switch);
+impl_create!(Switch => CreateSwitch { checked: Some(false) });
+
+impl TextWidget for Switch {}
+
+impl CheckableWidget for Switch {}
@@ -1,8 +1,11 @@
 //! TextView component
 
-use serde_json::json;
 use crate::activity::Activity;
+use crate::raw;
+use crate::color::Color;
 use crate::view::View;
+use crate::builder::{impl_create, sealed::Create};
+use crate::widget::{impl_widget, TextWidget};
 use crate::error::Result;
 
 /// A TextView displays text to the user
@@ -14,26 +17,13 @@ pub struct TextView {
 impl TextView {
     /// Create a new TextView
     pub fn new(activity: &mut Activity, text: &str, parent: Option<i64>) -> Result<Self> {
-        let mut params = json!({
-            "aid": activity.id(),
-            "text": text
-        });
-        
-        // Only set parent if explicitly provided
-        if let Some(parent_id) = parent {
-            params["parent"] = json!(parent_id);
-        }
-        
-        eprintln!("[DEBUG] TextView::new() - sending createTextView...");
-        let response = activity.send_read(&json!({
-            "method": "createTextView",
-            "params": params
-        }))?;
-        eprintln!("[DEBUG] TextView::new() - got response: {:?}", response);
-        
-        let id = response
-            .as_i64()
-            .ok_or_else(|| crate::error::GuiError::InvalidResponse("Invalid id".to_string()))?;
+        let aid = activity.id();
+        let id = raw::create_text_view(activity, &raw::CreateTextView {
+            aid,
+            parent,
+            text: Some(text.to_string()),
+            ..Self::default_params()
+        })?;
         
         Ok(TextView {
             view: View::new(id),
@@ -53,53 +43,28 @@ impl TextView {
     
     /// Set the text content
     pub fn set_text(&self, activity: &mut Activity, text: &str) -> Result<()> {
-        activity.send(&json!({
-            "method": "setText",
-            "params": {
-                "aid": self.aid,
-                "id": self.view.id(),
-                "text": text
-            }
-        }))?;
-        Ok(())
+        TextWidget::set_text(self, activity, text)
     }
     
     /// Get the current text content
     pub fn get_text(&self, activity: &mut Activity) -> Result<String> {
-        let response = activity.send_read(&json!({
-            "method": "getText",
-            "params": {
-                "aid": self.aid,
-                "id": self.view.id()
-            }
-        }))?;
-        
-        Ok(response.as_str().unwrap_or("").to_string())
+        TextWidget::get_text(self, activity)
     }
     
     /// Set text size
     pub fn set_text_size(&self, activity: &mut Activity, size: i32) -> Result<()> {
-        activity.send(&json!({
-            "method": "setTextSize",
-            "params": {
-                "aid": self.aid,
-                "id": self.view.id(),
-                "size": size
-            }
-        }))?;
-        Ok(())
+        TextWidget::set_text_size(self, activity, size)
     }
     
-    /// Set text color (ARGB format)
-    pub fn set_text_color(&self, activity: &mut Activity, color: i32) -> Result<()> {
-        activity.send(&json!({
-            "method": "setTextColor",
-            "params": {
-                "aid": self.aid,
-                "id": self.view.id(),
-                "color": color
-            }
-        }))?;
-        Ok(())
+    /// Set text color
+    ///
+    /// Accepts a [`Color`] or an ARGB integer.
+    pub fn set_text_color(&self, activity: &mut Activity, color: impl Into<Color>) -> Result<()> {
+        TextWidget::set_text_color(self, activity, color)
     }
 }
+
+impl_widget!(TextView);
+impl_create!(TextView => CreateTextView);
+
+impl TextWidget for TextView {}
@@ -1,8 +1,10 @@
 //! ToggleButton component
 
-use serde_json::json;
 use crate::activity::Activity;
+use crate::raw;
 use crate::view::View;
+use crate::builder::{impl_create, sealed::Create};
+use crate::widget::{impl_widget, CheckableWidget, TextWidget};
 use crate::error::Result;
 
 /// A ToggleButton is a button that can be toggled on or off
@@ -21,25 +23,14 @@ impl ToggleButton {
     
     /// Create a new ToggleButton with specified checked state
     pub fn new_with_checked(activity: &mut Activity, text: &str, parent: Option<i64>, checked: bool) -> Result<Self> {
-        let mut params = json!({
-            "aid": activity.id(),
-            "text": text,
-            "checked": checked
-        });
-        
-        // Only set parent if explicitly provided
-        if let Some(parent_id) = parent {
-            params["parent"] = json!(parent_id);
-        }
-        
-        let response = activity.send_read(&json!({
-            "method": "createToggleButton",
-            "params": params
-        }))?;
-        
-        let id = response
-            .as_i64()
-            .ok_or_else(|| crate::error::GuiError::InvalidResponse("Invalid id".to_string()))?;
+        let aid = activity.id();
+        let id = raw::create_toggle_button(activity, &raw::CreateToggleButton {
+            aid,
+            parent,
+            text: Some(text.to_string()),
+            checked: Some(checked),
+            ..Self::default_params()
+        })?;
         
         Ok(ToggleButton {
             view: View::new(id),
@@ -59,27 +50,18 @@ impl ToggleButton {
     
     /// Set the toggle button text
     pub fn set_text(&self, activity: &mut Activity, text: &str) -> Result<()> {
-        activity.send(&json!({
-            "method": "setText",
-            "params": {
-                "aid": self.aid,
-                "id": self.view.id(),
-                "text": text
-            }
-        }))?;
-        Ok(())
+        TextWidget::set_text(self, activity, text)
     }
     
     /// Set checked state
     pub fn set_checked(&self, activity: &mut Activity, checked: bool) -> Result<()> {
-        activity.send(&json!({
-            "method": "setChecked",
-            "params": {
-                "aid": self.aid,
-                "id": self.view.id(),
-                "checked": checked
-            }
-        }))?;
-        Ok(())
+        CheckableWidget::set_checked(self, activity, checked)
     }
 }
+
+impl_widget!(ToggleButton);
+impl_create!(ToggleButton => CreateToggleButton { checked: Some(false) });
+
+impl TextWidget for ToggleButton {}
+
+impl CheckableWidget for ToggleButton {}
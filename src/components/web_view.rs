@@ -8,10 +8,10 @@
 //! When displaying HTML content with dynamic effects, **you must enable JavaScript first**:
 //!
 //! ```no_run
-//! use termux_gui_rust_demo::prelude::*;
+//! use termux_gui::{Activity, LinearLayout, WebView};
 //!
-//! let mut activity = Activity::new()?;
-//! let layout = LinearLayout::new(&mut activity, None, true)?;
+//! let mut activity = Activity::new(false)?;
+//! let layout = LinearLayout::new(&mut activity, None)?;
 //! let webview = WebView::new(&mut activity, Some(layout.id()))?;
 //!
 //! // ⚠️ Critical: Enable JavaScript first, or dynamic HTML content won't display
@@ -29,12 +29,14 @@
 //!
 //! // Or load an external webpage
 //! webview.load_uri(&mut activity, "https://www.google.com")?;
-//! # Ok::<(), termux_gui_rust_demo::error::GuiError>(())
+//! # Ok::<(), termux_gui::GuiError>(())
 //! ```
 
-use serde_json::json;
 use crate::activity::Activity;
+use crate::raw;
 use crate::view::View;
+use crate::builder::{impl_create, sealed::Create};
+use crate::widget::impl_widget;
 use crate::error::Result;
 
 /// WebView component for displaying web content
@@ -47,15 +49,15 @@ use crate::error::Result;
 ///
 /// 2. **HTML Content Display Order**:
 ///    ```no_run
-///    # use termux_gui_rust_demo::prelude::*;
-///    # let mut activity = Activity::new()?;
+///    # use termux_gui::{Activity, WebView};
+///    # let mut activity = Activity::new(false)?;
 ///    # let webview = WebView::new(&mut activity, None)?;
 ///    // Step 1: Enable JavaScript (if needed)
 ///    webview.allow_javascript(&mut activity, true)?;
 ///    
 ///    // Step 2: Set HTML content
 ///    webview.set_data(&mut activity, "<html>...</html>")?;
-///    # Ok::<(), termux_gui_rust_demo::error::GuiError>(())
+///    # Ok::<(), termux_gui::GuiError>(())
 ///    ```
 ///
 /// 3. **Loading External URLs**: Simply call `load_uri()` - no special order required
@@ -63,9 +65,9 @@ use crate::error::Result;
 /// # Examples
 ///
 /// ```no_run
-/// use termux_gui_rust_demo::prelude::*;
+/// use termux_gui::{Activity, WebView};
 ///
-/// let mut activity = Activity::new()?;
+/// let mut activity = Activity::new(false)?;
 /// let webview = WebView::new(&mut activity, None)?;
 ///
 /// // Method 1: Display HTML content (requires JavaScript to be enabled first)
@@ -74,7 +76,7 @@ use crate::error::Result;
 ///
 /// // Method 2: Load a webpage
 /// webview.load_uri(&mut activity, "https://www.example.com")?;
-/// # Ok::<(), termux_gui_rust_demo::error::GuiError>(())
+/// # Ok::<(), termux_gui::GuiError>(())
 /// ```
 pub struct WebView {
     view: View,
@@ -90,34 +92,19 @@ impl WebView {
     ///
     /// # Examples
     /// ```no_run
-    /// # use termux_gui_rust_demo::prelude::*;
-    /// # let mut activity = Activity::new()?;
+    /// # use termux_gui::{Activity, WebView};
+    /// # let mut activity = Activity::new(false)?;
     /// # let layout_id = 0;
     /// let webview = WebView::new(&mut activity, Some(layout_id))?;
-    /// # Ok::<(), termux_gui_rust_demo::error::GuiError>(())
+    /// # Ok::<(), termux_gui::GuiError>(())
     /// ```
     pub fn new(activity: &mut Activity, parent: Option<i64>) -> Result<Self> {
-        eprintln!("[DEBUG] WebView::new() - creating WebView...");
-        
-        let mut params = json!({
-            "aid": activity.id()
-        });
-        
-        if let Some(parent_id) = parent {
-            params["parent"] = json!(parent_id);
-        }
-        
-        eprintln!("[DEBUG] WebView::new() - sending createWebView...");
-        let response = activity.send_read(&json!({
-            "method": "createWebView",
-            "params": params
-        }))?;
-        
-        eprintln!("[DEBUG] WebView::new() - got response: {:?}", response);
-        
-        let id = response
-            .as_i64()
-            .ok_or_else(|| crate::error::GuiError::InvalidResponse("Invalid id".to_string()))?;
+        let aid = activity.id();
+        let id = raw::create_web_view(activity, &raw::CreateWebView {
+            aid,
+            parent,
+            ..Self::default_params()
+        })?;
         
         Ok(WebView {
             view: View::new(id),
@@ -143,22 +130,18 @@ impl WebView {
     ///
     /// # Examples
     /// ```no_run
-    /// # use termux_gui_rust_demo::prelude::*;
-    /// # let mut activity = Activity::new()?;
+    /// # use termux_gui::{Activity, WebView};
+    /// # let mut activity = Activity::new(false)?;
     /// # let webview = WebView::new(&mut activity, None)?;
     /// webview.load_uri(&mut activity, "https://www.google.com")?;
-    /// # Ok::<(), termux_gui_rust_demo::error::GuiError>(())
+    /// # Ok::<(), termux_gui::GuiError>(())
     /// ```
     pub fn load_uri(&self, activity: &mut Activity, uri: &str) -> Result<()> {
-        activity.send(&json!({
-            "method": "loadURI",
-            "params": {
-                "aid": self.aid,
-                "id": self.view.id(),
-                "uri": uri
-            }
-        }))?;
-        Ok(())
+        raw::load_uri(activity, &raw::LoadUri {
+            aid: self.aid,
+            id: self.view.id(),
+            uri: uri.to_string(),
+        })
     }
     
     /// Sets HTML content
@@ -172,30 +155,26 @@ impl WebView {
     ///
     /// # Examples
     /// ```no_run
-    /// # use termux_gui_rust_demo::prelude::*;
-    /// # let mut activity = Activity::new()?;
+    /// # use termux_gui::{Activity, WebView};
+    /// # let mut activity = Activity::new(false)?;
     /// # let webview = WebView::new(&mut activity, None)?;
     /// // If HTML contains JavaScript, enable it first
     /// webview.allow_javascript(&mut activity, true)?;
     /// 
     /// // Then set the HTML content
     /// webview.set_data(&mut activity, "<html><body><h1>Hello</h1></body></html>")?;
-    /// # Ok::<(), termux_gui_rust_demo::error::GuiError>(())
+    /// # Ok::<(), termux_gui::GuiError>(())
     /// ```
     pub fn set_data(&self, activity: &mut Activity, data: &str) -> Result<()> {
         // Use base64 encoding to support HTML content with non-ASCII characters
         let encoded = base64::Engine::encode(&base64::engine::general_purpose::STANDARD, data.as_bytes());
         
-        activity.send(&json!({
-            "method": "setData",
-            "params": {
-                "aid": self.aid,
-                "id": self.view.id(),
-                "doc": encoded,
-                "base64": true
-            }
-        }))?;
-        Ok(())
+        raw::set_data(activity, &raw::SetData {
+            aid: self.aid,
+            id: self.view.id(),
+            doc: encoded,
+            base64: Some(true),
+        })
     }
     
     /// Allows JavaScript execution
@@ -216,8 +195,8 @@ impl WebView {
     ///
     /// # Examples
     /// ```no_run
-    /// # use termux_gui_rust_demo::prelude::*;
-    /// # let mut activity = Activity::new()?;
+    /// # use termux_gui::{Activity, WebView};
+    /// # let mut activity = Activity::new(false)?;
     /// # let webview = WebView::new(&mut activity, None)?;
     /// // Enable JavaScript (requires user confirmation)
     /// let enabled = webview.allow_javascript(&mut activity, true)?;
@@ -226,19 +205,14 @@ impl WebView {
     ///     // Now you can set HTML with JavaScript
     ///     webview.set_data(&mut activity, "<html><body><script>alert('Hi!');</script></body></html>")?;
     /// }
-    /// # Ok::<(), termux_gui_rust_demo::error::GuiError>(())
+    /// # Ok::<(), termux_gui::GuiError>(())
     /// ```
     pub fn allow_javascript(&self, activity: &mut Activity, allow: bool) -> Result<bool> {
-        let response = activity.send_read(&json!({
-            "method": "allowJavascript",
-            "params": {
-                "aid": self.aid,
-                "id": self.view.id(),
-                "allow": allow
-            }
-        }))?;
-        
-        Ok(response.as_bool().unwrap_or(false))
+        raw::allow_javascript(activity, &raw::AllowJavascript {
+            aid: self.aid,
+            id: self.view.id(),
+            allow,
+        })
     }
     
     /// Allows loading content from content:// URIs
@@ -247,15 +221,11 @@ impl WebView {
     /// - `activity`: Reference to the Activity
     /// - `allow`: Whether to allow loading from content URIs
     pub fn allow_content_uri(&self, activity: &mut Activity, allow: bool) -> Result<()> {
-        activity.send(&json!({
-            "method": "allowContentURI",
-            "params": {
-                "aid": self.aid,
-                "id": self.view.id(),
-                "allow": allow
-            }
-        }))?;
-        Ok(())
+        raw::allow_content_uri(activity, &raw::AllowContentUri {
+            aid: self.aid,
+            id: self.view.id(),
+            allow,
+        })
     }
     
     /// Allows navigation to different sites
@@ -264,15 +234,11 @@ impl WebView {
     /// - `activity`: Reference to the Activity
     /// - `allow`: Whether to allow users and JavaScript to navigate to different sites
     pub fn allow_navigation(&self, activity: &mut Activity, allow: bool) -> Result<()> {
-        activity.send(&json!({
-            "method": "allowNavigation",
-            "params": {
-                "aid": self.aid,
-                "id": self.view.id(),
-                "allow": allow
-            }
-        }))?;
-        Ok(())
+        raw::allow_navigation(activity, &raw::AllowNavigation {
+            aid: self.aid,
+            id: self.view.id(),
+            allow,
+        })
     }
     
     /// Executes JavaScript code in the WebView
@@ -286,49 +252,34 @@ impl WebView {
     ///
     /// # Examples
     /// ```no_run
-    /// # use termux_gui_rust_demo::prelude::*;
-    /// # let mut activity = Activity::new()?;
+    /// # use termux_gui::{Activity, WebView};
+    /// # let mut activity = Activity::new(false)?;
     /// # let webview = WebView::new(&mut activity, None)?;
     /// // Step 1: Enable JavaScript
     /// webview.allow_javascript(&mut activity, true)?;
     ///
     /// // Step 2: Execute JavaScript code
     /// webview.evaluate_js(&mut activity, "document.body.style.background = 'red';")?;
-    /// # Ok::<(), termux_gui_rust_demo::error::GuiError>(())
+    /// # Ok::<(), termux_gui::GuiError>(())
     /// ```
     pub fn evaluate_js(&self, activity: &mut Activity, code: &str) -> Result<()> {
-        activity.send(&json!({
-            "method": "evaluateJS",
-            "params": {
-                "aid": self.aid,
-                "id": self.view.id(),
-                "code": code
-            }
-        }))?;
-        Ok(())
+        raw::evaluate_js(activity, &raw::EvaluateJs {
+            aid: self.aid,
+            id: self.view.id(),
+            code: code.to_string(),
+        })
     }
     
     /// Goes back to the previous page in history
     pub fn go_back(&self, activity: &mut Activity) -> Result<()> {
-        activity.send(&json!({
-            "method": "goBack",
-            "params": {
-                "aid": self.aid,
-                "id": self.view.id()
-            }
-        }))?;
-        Ok(())
+        raw::go_back(activity, &raw::GoBack { aid: self.aid, id: self.view.id() })
     }
     
     /// Goes forward to the next page in history
     pub fn go_forward(&self, activity: &mut Activity) -> Result<()> {
-        activity.send(&json!({
-            "method": "goForward",
-            "params": {
-                "aid": self.aid,
-                "id": self.view.id()
-            }
-        }))?;
-        Ok(())
+        raw::go_forward(activity, &raw::GoForward { aid: self.aid, id: self.view.id() })
     }
 }
+
+impl_widget!(WebView);
+impl_create!(WebView => CreateWebView);
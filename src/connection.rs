@@ -1,13 +1,20 @@
 //! Low-level socket connection management
 
 use std::os::unix::net::{UnixListener, UnixStream};
-use std::io::{Read, Write};
-use std::process::Command;
+use std::collections::VecDeque;
+use std::io::{ErrorKind, Read, Write};
+use std::os::unix::io::AsRawFd;
+use std::time::Duration;
 use serde_json::Value;
 use rand::{thread_rng, Rng};
 use rand::distributions::Alphanumeric;
 
 use crate::error::{GuiError, Result};
+use crate::event::Event;
+use crate::protocol::{self, Method};
+use crate::record::{EntryKind, Recorder};
+use crate::trace::{debug, CallTrace};
+use crate::transport::{self, Connector};
 
 /// Generate a random address for abstract namespace sockets
 pub fn generate_random_address() -> String {
@@ -53,8 +60,43 @@ pub fn bind_abstract_socket(name: &str) -> Result<UnixListener> {
     }
 }
 
+/// Connect to an abstract namespace Unix socket
+pub fn connect_abstract_socket(name: &str) -> Result<UnixStream> {
+    unsafe {
+        use std::os::unix::io::FromRawFd;
+        
+        let fd = libc::socket(libc::AF_UNIX, libc::SOCK_STREAM, 0);
+        if fd < 0 {
+            return Err(GuiError::Io(std::io::Error::last_os_error()));
+        }
+        
+        let mut addr_bytes = vec![0u8];
+        addr_bytes.extend_from_slice(name.as_bytes());
+        
+        let mut addr: libc::sockaddr_un = std::mem::zeroed();
+        if addr_bytes.len() > addr.sun_path.len() {
+            libc::close(fd);
+            return Err(GuiError::InvalidOperation(format!("socket name too long: {}", name)));
+        }
+        addr.sun_family = libc::AF_UNIX as u16;
+        for (i, &byte) in addr_bytes.iter().enumerate() {
+            addr.sun_path[i] = byte as _;
+        }
+        
+        let addr_len = (std::mem::size_of::<libc::sa_family_t>() + addr_bytes.len()) as libc::socklen_t;
+        
+        if libc::connect(fd, &addr as *const _ as *const libc::sockaddr, addr_len) < 0 {
+            let err = std::io::Error::last_os_error();
+            libc::close(fd);
+            return Err(GuiError::Io(err));
+        }
+        
+        Ok(UnixStream::from_raw_fd(fd))
+    }
+}
+
 /// Send a JSON message to a stream
-pub fn send_message(stream: &mut UnixStream, msg: &Value) -> Result<()> {
+pub fn send_message<W: Write>(stream: &mut W, msg: &Value) -> Result<()> {
     let json_bytes = msg.to_string().into_bytes();
     stream.write_all(&(json_bytes.len() as u32).to_be_bytes())?;
     stream.write_all(&json_bytes)?;
@@ -63,7 +105,7 @@ pub fn send_message(stream: &mut UnixStream, msg: &Value) -> Result<()> {
 }
 
 /// Read a JSON message from a stream
-pub fn read_message(stream: &mut UnixStream) -> Result<Value> {
+pub fn read_message<R: Read>(stream: &mut R) -> Result<Value> {
     let mut len_buf = [0u8; 4];
     stream.read_exact(&mut len_buf)?;
     let len = u32::from_be_bytes(len_buf) as usize;
@@ -75,115 +117,322 @@ pub fn read_message(stream: &mut UnixStream) -> Result<Value> {
     Ok(value)
 }
 
+/// Protocol version requested in the handshake (JSON messages)
+pub const PROTOCOL_VERSION: u8 = 0x01;
+
+/// Accept a connection, giving up after `timeout`
+pub fn accept_timeout(listener: &UnixListener, timeout: Option<Duration>) -> Result<UnixStream> {
+    if let Some(timeout) = timeout {
+        let mut pfd = libc::pollfd {
+            fd: listener.as_raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        let millis = timeout.as_millis().min(libc::c_int::MAX as u128) as libc::c_int;
+        
+        let ready = loop {
+            let ret = unsafe { libc::poll(&mut pfd, 1, millis) };
+            if ret >= 0 {
+                break ret;
+            }
+            let err = std::io::Error::last_os_error();
+            if err.kind() != ErrorKind::Interrupted {
+                return Err(GuiError::Io(err));
+            }
+        };
+        
+        if ready == 0 {
+            return Err(GuiError::HandshakeTimeout(timeout));
+        }
+    }
+    
+    let (stream, _) = listener.accept()?;
+    Ok(stream)
+}
+
+/// Perform the protocol handshake on a freshly connected main stream
+///
+/// The client announces the protocol version and the service answers with
+/// a single byte, 0 meaning the version is supported.
+pub fn handshake(main_stream: &mut UnixStream, timeout: Option<Duration>) -> Result<()> {
+    main_stream.set_read_timeout(timeout)?;
+    main_stream.write_all(&[PROTOCOL_VERSION])?;
+    
+    let mut answer = [0u8; 1];
+    let result = main_stream.read_exact(&mut answer);
+    main_stream.set_read_timeout(None)?;
+    
+    match result {
+        Ok(()) => {}
+        Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {
+            return Err(GuiError::HandshakeTimeout(timeout.unwrap_or_default()));
+        }
+        Err(e) => return Err(GuiError::Io(e)),
+    }
+    
+    if answer[0] != 0 {
+        return Err(GuiError::ProtocolVersionMismatch {
+            requested: PROTOCOL_VERSION,
+            answer: answer[0],
+        });
+    }
+    Ok(())
+}
+
 /// Send a message and read the response
 pub fn send_and_read(stream: &mut UnixStream, msg: &Value) -> Result<Value> {
-    eprintln!("[DEBUG] send_and_read: sending...");
     send_message(stream, msg)?;
-    eprintln!("[DEBUG] send_and_read: reading response...");
-    let response = read_message(stream)?;
-    eprintln!("[DEBUG] send_and_read: got response!");
-    Ok(response)
+    read_message(stream)
 }
 
 /// Connection to Termux GUI service
 pub struct Connection {
     pub main_stream: UnixStream,
     pub event_stream: UnixStream,
+    recorder: Option<Recorder>,
 }
 
 impl Connection {
     /// Create a new connection to Termux GUI service
+    ///
+    /// Uses [`default_connector`](crate::transport::default_connector): the
+    /// sockets named in `TERMUX_GUI_MAIN_SOCKET`/`TERMUX_GUI_EVENT_SOCKET` if
+    /// both are set, otherwise an `am broadcast` to the Termux:GUI plugin.
     pub fn new() -> Result<Self> {
-        eprintln!("[DEBUG] Generating addresses...");
-        let addr_main = generate_random_address();
-        let addr_event = generate_random_address();
-        
-        eprintln!("[DEBUG] Binding sockets...");
-        let main_listener = bind_abstract_socket(&addr_main)?;
-        let event_listener = bind_abstract_socket(&addr_event)?;
-        
-        eprintln!("[DEBUG] Sending broadcast...");
-        // Try termux-am first, fall back to am
-        let output = Command::new("termux-am")
-            .args(&[
-                "broadcast",
-                "-n",
-                "com.termux.gui/.GUIReceiver",
-                "--es",
-                "mainSocket",
-                &addr_main,
-                "--es",
-                "eventSocket",
-                &addr_event,
-            ])
-            .output();
-        
-        match output {
-            Ok(out) if !out.status.success() => {
-                eprintln!("[DEBUG] termux-am failed, trying am...");
-                Command::new("am")
-                    .args(&[
-                        "broadcast",
-                        "-n",
-                        "com.termux.gui/.GUIReceiver",
-                        "--es",
-                        "mainSocket",
-                        &addr_main,
-                        "--es",
-                        "eventSocket",
-                        &addr_event,
-                    ])
-                    .output()
-                    .map_err(|_| GuiError::ConnectionFailed)?;
-            }
-            Err(_) => {
-                eprintln!("[DEBUG] termux-am not found, trying am...");
-                Command::new("am")
-                    .args(&[
-                        "broadcast",
-                        "-n",
-                        "com.termux.gui/.GUIReceiver",
-                        "--es",
-                        "mainSocket",
-                        &addr_main,
-                        "--es",
-                        "eventSocket",
-                        &addr_event,
-                    ])
-                    .output()
-                    .map_err(|_| GuiError::ConnectionFailed)?;
-            }
-            _ => {}
-        }
-        
-        eprintln!("[DEBUG] Accepting connections...");
-        let (mut main_stream, _) = main_listener.accept()?;
-        let (event_stream, _) = event_listener.accept()?;
-        
-        eprintln!("[DEBUG] Handshake...");
-        // Protocol handshake
-        main_stream.write_all(&[0x01])?;
-        main_stream.read_exact(&mut [0u8; 1])?;
-        
-        eprintln!("[DEBUG] Connection established!");
-        Ok(Connection {
+        transport::default_connector().connect()
+    }
+    
+    /// Create a connection with a custom [`Connector`]
+    pub fn connect_with<C: Connector + ?Sized>(connector: &C) -> Result<Self> {
+        connector.connect()
+    }
+    
+    /// Wrap two already connected streams
+    ///
+    /// No handshake is performed; use [`handshake`] first if the service
+    /// expects one.
+    pub fn from_streams(main_stream: UnixStream, event_stream: UnixStream) -> Self {
+        Connection {
             main_stream,
             event_stream,
-        })
+            recorder: None,
+        }
+    }
+    
+    /// Record all traffic on this connection
+    ///
+    /// See the [`record`](crate::record) module for the format and how to
+    /// replay it.
+    pub fn with_recorder(mut self, recorder: Recorder) -> Self {
+        self.recorder = Some(recorder);
+        self
+    }
+    
+    fn record(&self, kind: EntryKind, msg: &Value) {
+        if let Some(recorder) = &self.recorder {
+            recorder.record(kind, msg);
+        }
     }
     
     /// Send a message without waiting for response
+    ///
+    /// Fails with `GuiError::Protocol` if the [protocol table](crate::protocol)
+    /// says the method returns a value.
     pub fn send(&mut self, msg: &Value) -> Result<()> {
-        send_message(&mut self.main_stream, msg)
+        let trace = CallTrace::start("send", msg);
+        let result = trace
+            .in_scope(|| {
+                protocol::check_request(msg, false)?;
+                self.record(EntryKind::Send, msg);
+                send_message(&mut self.main_stream, msg)
+            })
+            .map_err(|e| e.in_call(msg));
+        trace.finish(&result);
+        result
     }
     
     /// Send a message and read the response
+    ///
+    /// Fails with `GuiError::Protocol` if the [protocol table](crate::protocol)
+    /// says the method doesn't return anything, as the read would block.
+    /// Fails with `GuiError::InvalidResponse` if the reply doesn't fit the
+    /// method, and in debug builds with `GuiError::Desynchronized` if a
+    /// reply was already waiting.
     pub fn send_read(&mut self, msg: &Value) -> Result<Value> {
-        send_and_read(&mut self.main_stream, msg)
+        let trace = CallTrace::start("send_read", msg);
+        let result = trace
+            .in_scope(|| -> Result<_> {
+                let method = protocol::check_request(msg, true)?;
+                check_in_sync(&self.main_stream, msg)?;
+                self.record(EntryKind::SendRead, msg);
+                let response = send_and_read(&mut self.main_stream, msg)?;
+                self.record(EntryKind::Response, &response);
+                protocol::check_reply(method, &response)?;
+                Ok(response)
+            })
+            .map_err(|e| e.in_call(msg));
+        trace.finish(&result);
+        result
+    }
+    
+    /// Write several requests before reading their responses
+    ///
+    /// See [`Pipeline`].
+    pub fn pipeline(&mut self) -> Pipeline<'_> {
+        Pipeline {
+            conn: self,
+            pending: VecDeque::new(),
+        }
     }
     
     /// Get a mutable reference to the event stream
     pub fn event_stream(&mut self) -> &mut UnixStream {
         &mut self.event_stream
     }
+    
+    /// Block until the next event arrives and decode it
+    pub fn read_event(&mut self) -> Result<Event> {
+        read_recorded_event(&mut self.event_stream, self.recorder.as_ref())
+    }
+    
+    /// Split into the main stream, the event stream and the recorder
+    #[cfg_attr(not(feature = "async"), allow(dead_code))]
+    pub(crate) fn into_parts(self) -> (UnixStream, UnixStream, Option<Recorder>) {
+        (self.main_stream, self.event_stream, self.recorder)
+    }
+    
+    /// Get an independent reader for the event socket
+    pub(crate) fn event_reader(&self) -> Result<EventReader> {
+        Ok(EventReader {
+            stream: self.event_stream.try_clone()?,
+            recorder: self.recorder.clone(),
+        })
+    }
+}
+
+/// Requests written to the main socket whose responses are read later
+///
+/// The service answers requests in the order it receives them, so
+/// responses come back in the order of [`send_read`](Self::send_read)
+/// calls. Writing many requests before reading saves a round trip per
+/// request. Responses that were not read are drained when the pipeline is
+/// dropped, so the connection stays in sync.
+pub struct Pipeline<'c> {
+    conn: &'c mut Connection,
+    pending: VecDeque<(Option<&'static Method>, Value, CallTrace)>,
+}
+
+impl Pipeline<'_> {
+    /// Send a request that has no response
+    pub fn send(&mut self, msg: &Value) -> Result<()> {
+        self.conn.send(msg)
+    }
+    
+    /// Send a request whose response will be read with [`read`](Self::read)
+    pub fn send_read(&mut self, msg: &Value) -> Result<()> {
+        let trace = CallTrace::start("send_read", msg);
+        let sent = trace.in_scope(|| -> Result<_> {
+            let method = protocol::check_request(msg, true)?;
+            if self.pending.is_empty() {
+                check_in_sync(&self.conn.main_stream, msg)?;
+            }
+            self.conn.record(EntryKind::SendRead, msg);
+            send_message(&mut self.conn.main_stream, msg)?;
+            Ok(method)
+        });
+        match sent {
+            Ok(method) => {
+                self.pending.push_back((method, msg.clone(), trace));
+                Ok(())
+            }
+            Err(e) => {
+                let result = Err(e.in_call(msg));
+                trace.finish::<()>(&result);
+                result
+            }
+        }
+    }
+    
+    /// Read the response to the oldest request that wasn't answered yet
+    pub fn read(&mut self) -> Result<Value> {
+        let (method, msg, trace) = self.pending.pop_front().ok_or_else(|| {
+            GuiError::InvalidOperation("no request is waiting for a response".to_string())
+        })?;
+        let result = trace
+            .in_scope(|| -> Result<_> {
+                let response = read_message(&mut self.conn.main_stream)?;
+                self.conn.record(EntryKind::Response, &response);
+                protocol::check_reply(method, &response)?;
+                Ok(response)
+            })
+            .map_err(|e| e.in_call(&msg));
+        trace.finish(&result);
+        result
+    }
+    
+    /// Get the number of responses that haven't been read yet
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+}
+
+impl Drop for Pipeline<'_> {
+    fn drop(&mut self) {
+        while !self.pending.is_empty() {
+            if self.read().is_err() {
+                break;
+            }
+        }
+    }
+}
+
+/// Fail if a reply is waiting before a request was sent (debug builds only)
+///
+/// A reply nobody asked for means an earlier method was sent with `send`
+/// although the service answers it, and would be taken as the reply to
+/// this request.
+#[cfg_attr(not(debug_assertions), allow(unused_variables))]
+fn check_in_sync(stream: &UnixStream, msg: &Value) -> Result<()> {
+    #[cfg(debug_assertions)]
+    {
+        let mut byte = 0u8;
+        let ret = unsafe {
+            libc::recv(
+                stream.as_raw_fd(),
+                &mut byte as *mut u8 as *mut libc::c_void,
+                1,
+                libc::MSG_PEEK | libc::MSG_DONTWAIT,
+            )
+        };
+        if ret > 0 {
+            return Err(GuiError::Desynchronized(format!(
+                "unread reply on the main socket before {}",
+                msg["method"]
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Reads events from a clone of a connection's event socket
+pub(crate) struct EventReader {
+    stream: UnixStream,
+    recorder: Option<Recorder>,
+}
+
+impl EventReader {
+    /// Block until the next event arrives and decode it
+    pub(crate) fn read(&mut self) -> Result<Event> {
+        read_recorded_event(&mut self.stream, self.recorder.as_ref())
+    }
+}
+
+fn read_recorded_event(stream: &mut UnixStream, recorder: Option<&Recorder>) -> Result<Event> {
+    let msg = read_message(stream)?;
+    if let Some(recorder) = recorder {
+        recorder.record(EntryKind::Event, &msg);
+    }
+    let event = Event::from_json(&msg)?;
+    debug!(?event, "event");
+    Ok(event)
 }
@@ -0,0 +1,721 @@
+//! Declarative UI trees
+//!
+//! Instead of creating widgets one call at a time, describe the screen as a
+//! tree of [`Node`]s and let [`Ui`] create it. When the state of the app
+//! changes, build a new tree and pass it to [`Ui::update`]: it is compared
+//! with the previous one and only the differences are sent (`setText`,
+//! `setChecked`, `deleteView`, `create*`, ...).
+//!
+//! Children are matched by [`key`](Node::key) if they have one, otherwise
+//! by position. Views can only be appended to a layout, so a new child in
+//! front of existing ones causes the following siblings to be recreated.
+//!
+//! Properties are compared with the previous tree, not with what is on
+//! screen: text typed into an EditText is kept until the tree sets a
+//! different text. Removing a property leaves the view as it is.
+//!
+//! ## Example
+//!
+//! ```rust,no_run
+//! use termux_gui::declarative::{Node, Ui};
+//! use termux_gui::{Activity, Event, Result};
+//!
+//! fn view(count: i32) -> Node {
+//!     Node::linear_layout()
+//!         .child(Node::text_view(format!("Clicked {} times", count)).text_size(24))
+//!         .child(Node::button("Click me").key("inc"))
+//!         .children((0..count).map(|i| Node::text_view(format!("Item {}", i))))
+//! }
+//!
+//! fn main() -> Result<()> {
+//!     let mut activity = Activity::new(false)?;
+//!     let mut count = 0;
+//!     let mut ui = Ui::mount(&mut activity, view(count), None)?;
+//!
+//!     loop {
+//!         match activity.next_event()? {
+//!             Event::Click { id, .. } if Some(id) == ui.id("inc") => {
+//!                 count += 1;
+//!                 // Sends one setText and one createTextView
+//!                 ui.update(&mut activity, view(count))?;
+//!             }
+//!             Event::Destroy { .. } => return Ok(()),
+//!             _ => {}
+//!         }
+//!     }
+//! }
+//! ```
+
+use std::collections::VecDeque;
+use crate::activity::Activity;
+use crate::builder::sealed::Create;
+use crate::builder::WidgetBuilder;
+use crate::color::Color;
+use crate::dimension::Dimension;
+use crate::error::{GuiError, Result};
+use crate::raw;
+use crate::view::{View, Visibility};
+use crate::widget::TextWidget;
+
+/// The component a [`Node`] creates
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NodeKind {
+    LinearLayout { vertical: bool },
+    FrameLayout,
+    NestedScrollView,
+    HorizontalScrollView,
+    RadioGroup,
+    TextView,
+    Button,
+    EditText,
+    Checkbox,
+    Switch,
+    RadioButton,
+    ToggleButton,
+    ProgressBar,
+    Space,
+}
+
+impl NodeKind {
+    /// Check whether nodes of this kind can have children
+    pub fn is_container(self) -> bool {
+        matches!(
+            self,
+            NodeKind::LinearLayout { .. }
+                | NodeKind::FrameLayout
+                | NodeKind::NestedScrollView
+                | NodeKind::HorizontalScrollView
+                | NodeKind::RadioGroup
+        )
+    }
+}
+
+/// Properties of a node, `None` meaning "not set"
+#[derive(Debug, Clone, Default, PartialEq)]
+struct Props {
+    text: Option<String>,
+    checked: Option<bool>,
+    progress: Option<i32>,
+    width: Option<Dimension>,
+    height: Option<Dimension>,
+    margin: Option<Dimension>,
+    padding: Option<Dimension>,
+    weight: Option<i32>,
+    text_size: Option<i32>,
+    text_color: Option<Color>,
+    background: Option<Color>,
+    visibility: Option<Visibility>,
+}
+
+impl Props {
+    /// Keep old values for properties the new tree no longer sets, since
+    /// the view still has them
+    fn merged_over(self, old: Props) -> Props {
+        Props {
+            text: self.text.or(old.text),
+            checked: self.checked.or(old.checked),
+            progress: self.progress.or(old.progress),
+            width: self.width.or(old.width),
+            height: self.height.or(old.height),
+            margin: self.margin.or(old.margin),
+            padding: self.padding.or(old.padding),
+            weight: self.weight.or(old.weight),
+            text_size: self.text_size.or(old.text_size),
+            text_color: self.text_color.or(old.text_color),
+            background: self.background.or(old.background),
+            visibility: self.visibility.or(old.visibility),
+        }
+    }
+}
+
+/// Description of a widget and its children
+#[derive(Debug, Clone, PartialEq)]
+pub struct Node {
+    kind: NodeKind,
+    key: Option<String>,
+    props: Props,
+    children: Vec<Node>,
+}
+
+impl Node {
+    /// Create a node of any kind
+    pub fn new(kind: NodeKind) -> Self {
+        Node {
+            kind,
+            key: None,
+            props: Props::default(),
+            children: Vec::new(),
+        }
+    }
+
+    fn with_text(kind: NodeKind, text: impl Into<String>) -> Self {
+        let mut node = Node::new(kind);
+        node.props.text = Some(text.into());
+        node
+    }
+
+    /// A vertical LinearLayout
+    pub fn linear_layout() -> Self {
+        Node::new(NodeKind::LinearLayout { vertical: true })
+    }
+
+    /// A horizontal LinearLayout
+    pub fn row() -> Self {
+        Node::new(NodeKind::LinearLayout { vertical: false })
+    }
+
+    /// A FrameLayout
+    pub fn frame_layout() -> Self {
+        Node::new(NodeKind::FrameLayout)
+    }
+
+    /// A NestedScrollView
+    pub fn nested_scroll_view() -> Self {
+        Node::new(NodeKind::NestedScrollView)
+    }
+
+    /// A HorizontalScrollView
+    pub fn horizontal_scroll_view() -> Self {
+        Node::new(NodeKind::HorizontalScrollView)
+    }
+
+    /// A RadioGroup
+    pub fn radio_group() -> Self {
+        Node::new(NodeKind::RadioGroup)
+    }
+
+    /// A TextView
+    pub fn text_view(text: impl Into<String>) -> Self {
+        Node::with_text(NodeKind::TextView, text)
+    }
+
+    /// A Button
+    pub fn button(text: impl Into<String>) -> Self {
+        Node::with_text(NodeKind::Button, text)
+    }
+
+    /// A single-line EditText
+    pub fn edit_text(text: impl Into<String>) -> Self {
+        Node::with_text(NodeKind::EditText, text)
+    }
+
+    /// A Checkbox
+    pub fn checkbox(text: impl Into<String>) -> Self {
+        Node::with_text(NodeKind::Checkbox, text)
+    }
+
+    /// A Switch
+    pub fn switch(text: impl Into<String>) -> Self {
+        Node::with_text(NodeKind::Switch, text)
+    }
+
+    /// A RadioButton
+    pub fn radio_button(text: impl Into<String>) -> Self {
+        Node::with_text(NodeKind::RadioButton, text)
+    }
+
+    /// A ToggleButton
+    pub fn toggle_button(text: impl Into<String>) -> Self {
+        Node::with_text(NodeKind::ToggleButton, text)
+    }
+
+    /// A ProgressBar
+    pub fn progress_bar() -> Self {
+        Node::new(NodeKind::ProgressBar)
+    }
+
+    /// A Space
+    pub fn space() -> Self {
+        Node::new(NodeKind::Space)
+    }
+
+    /// Get the kind of this node
+    pub fn kind(&self) -> NodeKind {
+        self.kind
+    }
+
+    /// Identify the node among its siblings, and look up its view ID with
+    /// [`Ui::id`]
+    pub fn key(mut self, key: impl Into<String>) -> Self {
+        self.key = Some(key.into());
+        self
+    }
+
+    /// Add a child
+    pub fn child(mut self, child: Node) -> Self {
+        self.children.push(child);
+        self
+    }
+
+    /// Add several children
+    pub fn children(mut self, children: impl IntoIterator<Item = Node>) -> Self {
+        self.children.extend(children);
+        self
+    }
+
+    /// Set the text
+    pub fn text(mut self, text: impl Into<String>) -> Self {
+        self.props.text = Some(text.into());
+        self
+    }
+
+    /// Set the checked state of a Checkbox, Switch, RadioButton or ToggleButton
+    pub fn checked(mut self, checked: bool) -> Self {
+        self.props.checked = Some(checked);
+        self
+    }
+
+    /// Set the progress of a ProgressBar (0-100)
+    pub fn progress(mut self, progress: i32) -> Self {
+        self.props.progress = Some(progress.clamp(0, 100));
+        self
+    }
+
+    /// Set the width
+    pub fn width(mut self, width: impl Into<Dimension>) -> Self {
+        self.props.width = Some(width.into());
+        self
+    }
+
+    /// Set the height
+    pub fn height(mut self, height: impl Into<Dimension>) -> Self {
+        self.props.height = Some(height.into());
+        self
+    }
+
+    /// Set the margin on all sides
+    pub fn margin(mut self, margin: impl Into<Dimension>) -> Self {
+        self.props.margin = Some(margin.into());
+        self
+    }
+
+    /// Set the padding on all sides
+    pub fn padding(mut self, padding: impl Into<Dimension>) -> Self {
+        self.props.padding = Some(padding.into());
+        self
+    }
+
+    /// Set the weight inside a LinearLayout
+    pub fn weight(mut self, weight: i32) -> Self {
+        self.props.weight = Some(weight);
+        self
+    }
+
+    /// Set the text size in sp
+    pub fn text_size(mut self, size: i32) -> Self {
+        self.props.text_size = Some(size);
+        self
+    }
+
+    /// Set the text colour
+    pub fn text_color(mut self, color: impl Into<Color>) -> Self {
+        self.props.text_color = Some(color.into());
+        self
+    }
+
+    /// Set the background colour
+    pub fn background_color(mut self, color: impl Into<Color>) -> Self {
+        self.props.background = Some(color.into());
+        self
+    }
+
+    /// Set the visibility
+    pub fn visibility(mut self, visibility: Visibility) -> Self {
+        self.props.visibility = Some(visibility);
+        self
+    }
+}
+
+/// A node that has been created
+#[derive(Debug)]
+struct Mounted {
+    id: i64,
+    kind: NodeKind,
+    key: Option<String>,
+    props: Props,
+    children: Vec<Mounted>,
+}
+
+impl Mounted {
+    fn matches(&self, node: &Node) -> bool {
+        self.kind == node.kind && self.key == node.key
+    }
+
+    fn find(&self, key: &str) -> Option<i64> {
+        if self.key.as_deref() == Some(key) {
+            return Some(self.id);
+        }
+        self.children.iter().find_map(|child| child.find(key))
+    }
+}
+
+/// A mounted tree of widgets, updated by diffing
+#[derive(Debug)]
+pub struct Ui {
+    root: Mounted,
+    parent: Option<i64>,
+}
+
+impl Ui {
+    /// Create all widgets of `root`, inside the layout `parent` if given
+    pub fn mount(activity: &mut Activity, root: Node, parent: Option<i64>) -> Result<Self> {
+        let root = create(activity, root, parent)?;
+        Ok(Ui { root, parent })
+    }
+
+    /// Bring the widgets in line with `root`, sending only the changes
+    pub fn update(&mut self, activity: &mut Activity, root: Node) -> Result<()> {
+        if self.root.matches(&root) {
+            patch(activity, &mut self.root, root)
+        } else {
+            View::new(self.root.id).delete(activity)?;
+            self.root = create(activity, root, self.parent)?;
+            Ok(())
+        }
+    }
+
+    /// Get the view ID of the root widget
+    pub fn root_id(&self) -> i64 {
+        self.root.id
+    }
+
+    /// Get the view ID of the node with `key`
+    pub fn id(&self, key: &str) -> Option<i64> {
+        self.root.find(key)
+    }
+}
+
+/// Apply the settings every builder has
+fn common<'a, 'h, W: Create>(
+    builder: WidgetBuilder<'a, 'h, W>,
+    props: &Props,
+    parent: Option<i64>,
+) -> WidgetBuilder<'a, 'h, W> {
+    let mut builder = builder;
+    if let Some(parent) = parent {
+        builder = builder.parent_id(parent);
+    }
+    if let Some(visibility) = props.visibility {
+        builder = builder.visibility(visibility);
+    }
+    if let Some(width) = props.width {
+        builder = builder.width(width);
+    }
+    if let Some(height) = props.height {
+        builder = builder.height(height);
+    }
+    if let Some(margin) = props.margin {
+        builder = builder.margin(margin);
+    }
+    if let Some(padding) = props.padding {
+        builder = builder.padding(padding);
+    }
+    if let Some(weight) = props.weight {
+        builder = builder.weight(weight);
+    }
+    if let Some(color) = props.background {
+        builder = builder.background_color(color);
+    }
+    builder
+}
+
+/// Apply the settings of text widgets
+fn text<'a, 'h, W: Create + TextWidget>(
+    builder: WidgetBuilder<'a, 'h, W>,
+    props: &Props,
+    parent: Option<i64>,
+) -> WidgetBuilder<'a, 'h, W> {
+    let mut builder = common(builder, props, parent);
+    if let Some(size) = props.text_size {
+        builder = builder.text_size(size);
+    }
+    if let Some(color) = props.text_color {
+        builder = builder.text_color(color);
+    }
+    builder
+}
+
+/// Create the widget of `node` and all its children
+fn create(activity: &mut Activity, node: Node, parent: Option<i64>) -> Result<Mounted> {
+    if !node.children.is_empty() && !node.kind.is_container() {
+        return Err(GuiError::InvalidOperation(format!("{:?} can't have children", node.kind)));
+    }
+
+    let props = &node.props;
+    let label = props.text.as_deref().unwrap_or("");
+    let checked = props.checked.unwrap_or(false);
+    let id = match node.kind {
+        NodeKind::LinearLayout { vertical } => {
+            common(activity.linear_layout(), props, parent).vertical(vertical).build()?.id()
+        }
+        NodeKind::FrameLayout => common(activity.frame_layout(), props, parent).build()?.id(),
+        NodeKind::NestedScrollView => common(activity.nested_scroll_view(), props, parent).build()?.id(),
+        NodeKind::HorizontalScrollView => common(activity.horizontal_scroll_view(), props, parent).build()?.id(),
+        NodeKind::RadioGroup => common(activity.radio_group(), props, parent).build()?.id(),
+        NodeKind::TextView => text(activity.text_view(label), props, parent).build()?.id(),
+        NodeKind::Button => text(activity.button(label), props, parent).build()?.id(),
+        NodeKind::EditText => text(activity.edit_text(label), props, parent).build()?.id(),
+        NodeKind::Checkbox => text(activity.checkbox(label), props, parent).checked(checked).build()?.id(),
+        NodeKind::Switch => text(activity.switch(label), props, parent).checked(checked).build()?.id(),
+        NodeKind::RadioButton => text(activity.radio_button(label), props, parent).checked(checked).build()?.id(),
+        NodeKind::ToggleButton => text(activity.toggle_button(label), props, parent).checked(checked).build()?.id(),
+        NodeKind::ProgressBar => {
+            let bar = common(activity.progress_bar(), props, parent).build()?;
+            if let Some(progress) = props.progress {
+                bar.set_progress(activity, progress)?;
+            }
+            bar.id()
+        }
+        NodeKind::Space => common(activity.space(), props, parent).build()?.id(),
+    };
+
+    let mut children = Vec::with_capacity(node.children.len());
+    for child in node.children {
+        children.push(create(activity, child, Some(id))?);
+    }
+
+    Ok(Mounted {
+        id,
+        kind: node.kind,
+        key: node.key,
+        props: node.props,
+        children,
+    })
+}
+
+/// Update a mounted widget of the same kind and key as `node`
+fn patch(activity: &mut Activity, mounted: &mut Mounted, node: Node) -> Result<()> {
+    let aid = activity.id();
+    let id = mounted.id;
+    let old = &mounted.props;
+    let new = &node.props;
+    let view = View::new(id);
+
+    if let Some(text) = changed(&new.text, &old.text) {
+        raw::set_text(activity, &raw::SetText { aid, id, text: text.clone() })?;
+    }
+    if let Some(checked) = changed(&new.checked, &old.checked) {
+        raw::set_checked(activity, &raw::SetChecked { aid, id, checked: *checked })?;
+    }
+    if let Some(progress) = changed(&new.progress, &old.progress) {
+        raw::set_progress(activity, &raw::SetProgress { aid, id, progress: (*progress).into() })?;
+    }
+    if let Some(size) = changed(&new.text_size, &old.text_size) {
+        raw::set_text_size(activity, &raw::SetTextSize { aid, id, size: (*size).into() })?;
+    }
+    if let Some(color) = changed(&new.text_color, &old.text_color) {
+        raw::set_text_color(activity, &raw::SetTextColor { aid, id, color: color.to_i32().into() })?;
+    }
+    if let Some(width) = changed(&new.width, &old.width) {
+        view.set_width(activity, *width)?;
+    }
+    if let Some(height) = changed(&new.height, &old.height) {
+        view.set_height(activity, *height)?;
+    }
+    if let Some(margin) = changed(&new.margin, &old.margin) {
+        view.set_margin(activity, *margin)?;
+    }
+    if let Some(padding) = changed(&new.padding, &old.padding) {
+        view.set_padding(activity, *padding)?;
+    }
+    if let Some(weight) = changed(&new.weight, &old.weight) {
+        view.set_linear_layout_params(activity, *weight, None)?;
+    }
+    if let Some(color) = changed(&new.background, &old.background) {
+        view.set_background_color(activity, *color)?;
+    }
+    if let Some(visibility) = changed(&new.visibility, &old.visibility) {
+        view.set_visibility(activity, *visibility)?;
+    }
+
+    let old_children = std::mem::take(&mut mounted.children);
+    mounted.children = patch_children(activity, id, old_children, node.children)?;
+    mounted.props = node.props.merged_over(std::mem::take(&mut mounted.props));
+    Ok(())
+}
+
+/// Diff the children of the layout `parent`
+///
+/// Matching children are patched in place, unmatched old ones deleted.
+/// Since new views are always appended, a new child that would land in
+/// front of an existing one causes the rest to be recreated.
+fn patch_children(activity: &mut Activity, parent: i64, old: Vec<Mounted>, new: Vec<Node>) -> Result<Vec<Mounted>> {
+    let mut remaining: VecDeque<Mounted> = old.into();
+    let mut children = Vec::with_capacity(new.len());
+
+    for node in new {
+        // Keyed nodes may have moved; unkeyed ones only match in place
+        let found = match node.key {
+            Some(_) => remaining.iter().position(|m| m.matches(&node)),
+            None => remaining.front().filter(|m| m.matches(&node)).map(|_| 0),
+        };
+
+        match found {
+            Some(index) => {
+                for skipped in remaining.drain(..index) {
+                    View::new(skipped.id).delete(activity)?;
+                }
+                let mut mounted = remaining.pop_front().expect("matched child exists");
+                patch(activity, &mut mounted, node)?;
+                children.push(mounted);
+            }
+            None => {
+                for stale in remaining.drain(..) {
+                    View::new(stale.id).delete(activity)?;
+                }
+                children.push(create(activity, node, Some(parent))?);
+            }
+        }
+    }
+
+    for stale in remaining {
+        View::new(stale.id).delete(activity)?;
+    }
+    Ok(children)
+}
+
+/// Get the new value of a property if it differs from the old one
+fn changed<'v, T: PartialEq>(new: &'v Option<T>, old: &Option<T>) -> Option<&'v T> {
+    new.as_ref().filter(|value| old.as_ref() != Some(*value))
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::*;
+    use crate::testing::MockService;
+
+    fn list(items: &[&str]) -> Node {
+        Node::linear_layout().children(items.iter().map(|item| Node::text_view(*item).key(*item)))
+    }
+
+    fn ids(params: Vec<Value>) -> Vec<i64> {
+        params.iter().map(|p| p["id"].as_i64().unwrap()).collect()
+    }
+
+    #[test]
+    fn mount_creates_children_inside_their_parent() -> Result<()> {
+        let (mock, mut activity) = MockService::activity(false)?;
+        let ui = Ui::mount(&mut activity, list(&["a", "b"]), None)?;
+
+        let texts = mock.calls_to("createTextView");
+        assert_eq!(texts.len(), 2);
+        assert!(texts.iter().all(|p| p["parent"] == json!(ui.root_id())));
+        assert_eq!(texts[1]["text"], "b");
+        assert!(ui.id("a").is_some() && ui.id("a") != ui.id("b"));
+        assert_eq!(ui.id("c"), None);
+        Ok(())
+    }
+
+    #[test]
+    fn unchanged_tree_sends_nothing() -> Result<()> {
+        let (mock, mut activity) = MockService::activity(false)?;
+        let mut ui = Ui::mount(&mut activity, list(&["a", "b"]).text_size(12), None)?;
+        mock.clear_calls();
+
+        ui.update(&mut activity, list(&["a", "b"]).text_size(12))?;
+        assert!(mock.calls().is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn changed_properties_are_set() -> Result<()> {
+        let (mock, mut activity) = MockService::activity(false)?;
+        let node = |text: &str, checked| Node::linear_layout().child(Node::checkbox(text).key("c").checked(checked));
+        let mut ui = Ui::mount(&mut activity, node("Off", false), None)?;
+        mock.clear_calls();
+
+        ui.update(&mut activity, node("On", true))?;
+        let id = json!(ui.id("c"));
+        assert_eq!(mock.calls_to("setText"), [json!({ "aid": activity.id(), "id": id, "text": "On" })]);
+        assert_eq!(mock.calls_to("setChecked")[0]["checked"], json!(true));
+        assert!(mock.calls_to("createCheckbox").is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn keyed_children_survive_removal_of_a_sibling() -> Result<()> {
+        let (mock, mut activity) = MockService::activity(false)?;
+        let mut ui = Ui::mount(&mut activity, list(&["a", "b", "c"]), None)?;
+        let (a, b, c) = (ui.id("a"), ui.id("b"), ui.id("c"));
+        mock.clear_calls();
+
+        ui.update(&mut activity, list(&["a", "c"]))?;
+        assert_eq!(ids(mock.calls_to("deleteView")), [b.unwrap()]);
+        assert!(mock.calls_to("createTextView").is_empty());
+        assert_eq!((ui.id("a"), ui.id("b"), ui.id("c")), (a, None, c));
+        Ok(())
+    }
+
+    #[test]
+    fn appended_children_are_created() -> Result<()> {
+        let (mock, mut activity) = MockService::activity(false)?;
+        let mut ui = Ui::mount(&mut activity, list(&["a"]), None)?;
+        mock.clear_calls();
+
+        ui.update(&mut activity, list(&["a", "b"]))?;
+        assert!(mock.calls_to("deleteView").is_empty());
+        let created = mock.calls_to("createTextView");
+        assert_eq!(created.len(), 1);
+        assert_eq!(created[0]["parent"], json!(ui.root_id()));
+        Ok(())
+    }
+
+    #[test]
+    fn inserting_in_front_recreates_the_following_siblings() -> Result<()> {
+        let (mock, mut activity) = MockService::activity(false)?;
+        let mut ui = Ui::mount(&mut activity, list(&["b", "c"]), None)?;
+        let (b, c) = (ui.id("b").unwrap(), ui.id("c").unwrap());
+        mock.clear_calls();
+
+        ui.update(&mut activity, list(&["a", "b", "c"]))?;
+        assert_eq!(ids(mock.calls_to("deleteView")), [b, c]);
+        let created: Vec<_> = mock.calls_to("createTextView").iter().map(|p| p["text"].clone()).collect();
+        assert_eq!(created, ["a", "b", "c"]);
+        Ok(())
+    }
+
+    #[test]
+    fn unkeyed_children_match_by_position() -> Result<()> {
+        let (mock, mut activity) = MockService::activity(false)?;
+        let node = |first: Node| Node::linear_layout().child(first).child(Node::text_view("x"));
+        let mut ui = Ui::mount(&mut activity, node(Node::text_view("a")), None)?;
+        mock.clear_calls();
+
+        // Same kinds in place: patched
+        ui.update(&mut activity, node(Node::text_view("b")))?;
+        assert_eq!(mock.calls_to("setText").len(), 1);
+        assert!(mock.calls_to("deleteView").is_empty());
+
+        // Another kind in front: both recreated
+        mock.clear_calls();
+        ui.update(&mut activity, node(Node::button("b")))?;
+        assert_eq!(mock.calls_to("deleteView").len(), 2);
+        assert_eq!(mock.calls_to("createButton").len(), 1);
+        assert_eq!(mock.calls_to("createTextView").len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn replacing_the_root_recreates_everything() -> Result<()> {
+        let (mock, mut activity) = MockService::activity(false)?;
+        let mut ui = Ui::mount(&mut activity, list(&["a"]), None)?;
+        let old_root = ui.root_id();
+        mock.clear_calls();
+
+        ui.update(&mut activity, Node::frame_layout().child(Node::space()))?;
+        assert_eq!(ids(mock.calls_to("deleteView")), [old_root]);
+        assert_eq!(mock.calls_to("createFrameLayout").len(), 1);
+        assert_ne!(ui.root_id(), old_root);
+        Ok(())
+    }
+
+    #[test]
+    fn only_containers_have_children() -> Result<()> {
+        let (_mock, mut activity) = MockService::activity(false)?;
+        let result = Ui::mount(&mut activity, Node::button("OK").child(Node::space()), None);
+        assert!(matches!(result, Err(GuiError::InvalidOperation(_))));
+        Ok(())
+    }
+}
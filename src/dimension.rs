@@ -0,0 +1,212 @@
+//! Sizes with explicit units
+//!
+//! Android measures views in **dp** (density-independent pixels) by
+//! default, text in **sp** (dp scaled by the user's font size setting),
+//! while `get_dimensions()` reports **px**. A [`Dimension`] carries its unit
+//! so these can't be mixed up, and is accepted by all sizing and margin
+//! calls. Plain integers convert to dp, with `MATCH_PARENT` (-1) and
+//! `WRAP_CONTENT` (-2) mapping to the special sizes, so existing code keeps
+//! working.
+//!
+//! Conversions between units need the display density, which
+//! [`Activity::display_metrics`](crate::Activity::display_metrics) queries
+//! from the service.
+//!
+//! ## Example
+//!
+//! ```rust,no_run
+//! # use termux_gui::{Activity, HorizontalScrollView, LinearLayout, Result};
+//! use termux_gui::Dimension;
+//!
+//! # fn example(activity: &mut Activity, scroll_view: &HorizontalScrollView, page: &LinearLayout) -> Result<()> {
+//! // get_dimensions() returns px, so say so
+//! let (width_px, _) = scroll_view.view().get_dimensions(activity)?;
+//! page.view().set_width(activity, Dimension::Px(width_px))?;
+//! page.view().set_height(activity, Dimension::MatchParent)?;
+//! page.view().set_margin(activity, Dimension::Dp(8))?;
+//!
+//! // Convert client-side
+//! let metrics = activity.display_metrics()?;
+//! assert_eq!(Dimension::Px(width_px).to_px(&metrics), Some(width_px));
+//! # Ok(())
+//! # }
+//! ```
+
+use std::str::FromStr;
+use crate::error::{GuiError, Result};
+use crate::view::{MATCH_PARENT, WRAP_CONTENT};
+
+/// A size with a unit
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Dimension {
+    /// Density-independent pixels
+    Dp(i32),
+    /// Physical screen pixels
+    Px(i32),
+    /// Scale-independent pixels: dp scaled by the font size setting
+    Sp(i32),
+    /// As large as the parent
+    MatchParent,
+    /// Just large enough for the content
+    WrapContent,
+}
+
+impl Dimension {
+    /// Parse a size such as `"16dp"`, `"120px"`, `"14sp"` or `"match_parent"`
+    ///
+    /// Same as `str::parse`.
+    pub fn parse(s: &str) -> Result<Self> {
+        s.parse()
+    }
+
+    /// Convert to physical pixels
+    ///
+    /// Returns `None` for `MatchParent` and `WrapContent`.
+    pub fn to_px(self, metrics: &DisplayMetrics) -> Option<i32> {
+        match self {
+            Dimension::Dp(dp) => Some((dp as f64 * metrics.density).round() as i32),
+            Dimension::Px(px) => Some(px),
+            Dimension::Sp(sp) => Some((sp as f64 * metrics.density * metrics.font_scale).round() as i32),
+            Dimension::MatchParent | Dimension::WrapContent => None,
+        }
+    }
+
+    /// Convert to density-independent pixels
+    ///
+    /// Returns `None` for `MatchParent` and `WrapContent`.
+    pub fn to_dp(self, metrics: &DisplayMetrics) -> Option<i32> {
+        match self {
+            Dimension::Dp(dp) => Some(dp),
+            Dimension::Px(px) => Some((px as f64 / metrics.density).round() as i32),
+            Dimension::Sp(sp) => Some((sp as f64 * metrics.font_scale).round() as i32),
+            Dimension::MatchParent | Dimension::WrapContent => None,
+        }
+    }
+}
+
+impl From<i32> for Dimension {
+    /// dp, except `MATCH_PARENT` (-1) and `WRAP_CONTENT` (-2)
+    fn from(value: i32) -> Self {
+        match value {
+            MATCH_PARENT => Dimension::MatchParent,
+            WRAP_CONTENT => Dimension::WrapContent,
+            dp => Dimension::Dp(dp),
+        }
+    }
+}
+
+impl FromStr for Dimension {
+    type Err = GuiError;
+
+    /// Parse `"12dp"`, `"12px"`, `"12sp"`, `"match_parent"` or
+    /// `"wrap_content"`; a bare number is dp
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim();
+        match s.to_ascii_lowercase().as_str() {
+            "match_parent" => return Ok(Dimension::MatchParent),
+            "wrap_content" => return Ok(Dimension::WrapContent),
+            _ => {}
+        }
+
+        let (number, unit): (&str, fn(i32) -> Dimension) = if let Some(n) = s.strip_suffix("dp") {
+            (n, Dimension::Dp)
+        } else if let Some(n) = s.strip_suffix("px") {
+            (n, Dimension::Px)
+        } else if let Some(n) = s.strip_suffix("sp") {
+            (n, Dimension::Sp)
+        } else {
+            (s, Dimension::Dp)
+        };
+        number
+            .trim()
+            .parse()
+            .map(unit)
+            .map_err(|_| GuiError::InvalidDimension(s.to_string()))
+    }
+}
+
+/// Display density information needed to convert between units
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DisplayMetrics {
+    /// Pixels per dp, e.g. 2.75 on a typical phone
+    pub density: f64,
+    /// The user's font size setting, 1.0 by default
+    pub font_scale: f64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const METRICS: DisplayMetrics = DisplayMetrics { density: 2.5, font_scale: 1.2 };
+
+    #[test]
+    fn parses_units_and_special_sizes() {
+        assert_eq!(Dimension::parse("16dp").unwrap(), Dimension::Dp(16));
+        assert_eq!(Dimension::parse("120px").unwrap(), Dimension::Px(120));
+        assert_eq!(Dimension::parse(" 14 sp").unwrap(), Dimension::Sp(14));
+        assert_eq!(Dimension::parse("-3").unwrap(), Dimension::Dp(-3));
+        assert_eq!(Dimension::parse("MATCH_PARENT").unwrap(), Dimension::MatchParent);
+        assert_eq!("wrap_content".parse::<Dimension>().unwrap(), Dimension::WrapContent);
+    }
+
+    #[test]
+    fn rejects_malformed_sizes() {
+        for input in ["", "dp", "1.5dp", "12em", "twelve"] {
+            assert!(
+                matches!(Dimension::parse(input), Err(GuiError::InvalidDimension(_))),
+                "accepted {:?}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn integers_keep_the_special_constants() {
+        assert_eq!(Dimension::from(MATCH_PARENT), Dimension::MatchParent);
+        assert_eq!(Dimension::from(WRAP_CONTENT), Dimension::WrapContent);
+        assert_eq!(Dimension::from(8), Dimension::Dp(8));
+    }
+
+    #[test]
+    fn converts_between_units() {
+        assert_eq!(Dimension::Dp(10).to_px(&METRICS), Some(25));
+        assert_eq!(Dimension::Sp(10).to_px(&METRICS), Some(30));
+        assert_eq!(Dimension::Px(25).to_dp(&METRICS), Some(10));
+        assert_eq!(Dimension::Sp(10).to_dp(&METRICS), Some(12));
+        assert_eq!(Dimension::MatchParent.to_px(&METRICS), None);
+        assert_eq!(Dimension::WrapContent.to_dp(&METRICS), None);
+    }
+
+    #[cfg(feature = "testing")]
+    #[test]
+    fn views_send_sizes_in_the_unit_given() -> Result<()> {
+        use serde_json::json;
+
+        use crate::testing::MockService;
+        use crate::view::Side;
+
+        let (mock, mut activity) = MockService::activity(false)?;
+        let view = activity.create_space(None)?;
+        let view = view.view();
+        view.set_width(&mut activity, Dimension::Dp(10))?;
+        view.set_width(&mut activity, Dimension::Px(10))?;
+        // The mock reports a density of 2 and a font scale of 1
+        view.set_width(&mut activity, Dimension::Sp(10))?;
+        view.set_height(&mut activity, Dimension::MatchParent)?;
+        view.set_margin_side(&mut activity, Side::Left, Dimension::Px(8))?;
+
+        let widths = mock.calls_to("setWidth");
+        assert_eq!((widths[0]["width"].clone(), widths[0]["px"].clone()), (json!(10), json!(false)));
+        assert_eq!((widths[1]["width"].clone(), widths[1]["px"].clone()), (json!(10), json!(true)));
+        assert_eq!((widths[2]["width"].clone(), widths[2]["px"].clone()), (json!(20), json!(true)));
+        assert_eq!(mock.calls_to("setHeight")[0]["height"], "MATCH_PARENT");
+
+        let margin = &mock.calls_to("setMargin")[0];
+        assert_eq!((margin["margin"].clone(), margin["dir"].clone()), (json!(4), json!("left")));
+
+        let error = view.set_margin(&mut activity, Dimension::WrapContent).unwrap_err();
+        assert!(matches!(error.root(), GuiError::InvalidOperation(_)));
+        Ok(())
+    }
+}
@@ -1,6 +1,14 @@
 //! Error types for Termux GUI
+//!
+//! Errors from a request to the service are wrapped in [`GuiError::Call`],
+//! which names the method, Activity and view the request was for, e.g.
+//! `getDimensions (aid 2, view 17): Invalid response to getDimensions: null`.
+//! Match on [`GuiError::root`] to look at the underlying error.
 
+use std::fmt;
 use std::io;
+use std::time::Duration;
+use serde_json::Value;
 use thiserror::Error;
 
 /// Result type alias for Termux GUI operations
@@ -25,9 +33,78 @@ pub enum GuiError {
     #[error("Failed to connect to Termux GUI service")]
     ConnectionFailed,
     
-    /// Invalid response from GUI service
-    #[error("Invalid response: {0}")]
-    InvalidResponse(String),
+    /// The Termux:GUI plugin app is not installed
+    #[error("The Termux:GUI plugin (com.termux.gui) is not installed")]
+    PluginMissing,
+    
+    /// The broadcast asking the plugin to connect could not be sent
+    #[error("Broadcast with `{program}` failed: {status}{}", stderr_suffix(.stderr))]
+    BroadcastFailed {
+        /// The program that was run (`termux-am` or `am`)
+        program: String,
+        /// Exit status, or why the program could not be run
+        status: String,
+        /// Captured standard error output
+        stderr: String,
+    },
+    
+    /// The service did not connect back or answer the handshake in time
+    #[error("Timed out after {0:?} waiting for the Termux GUI service")]
+    HandshakeTimeout(Duration),
+    
+    /// The service rejected the protocol version requested in the handshake
+    #[error("Protocol version {requested} not supported by the service (answered {answer})")]
+    ProtocolVersionMismatch {
+        /// The protocol version sent by this crate
+        requested: u8,
+        /// The byte the service answered with
+        answer: u8,
+    },
+    
+    /// The service answered with something the method can't return
+    #[error("Invalid response to {method}: {payload}")]
+    InvalidResponse {
+        /// The method that was answered
+        method: String,
+        /// The reply as received
+        payload: Value,
+    },
+    
+    /// An error while handling a request, with the request it happened in
+    ///
+    /// Use [`GuiError::root`] to match on the underlying error.
+    #[error("{context}: {source}")]
+    Call {
+        /// Method, Activity and view of the request
+        context: CallContext,
+        /// The underlying error
+        #[source]
+        source: Box<GuiError>,
+    },
+    
+    /// A request doesn't match the protocol table, see [`crate::protocol`]
+    #[error("Protocol error: {0}")]
+    Protocol(String),
+    
+    /// Requests and replies on the main socket are out of step
+    #[error("Main socket out of sync: {0}")]
+    Desynchronized(String),
+    
+    /// The Activity has been finished and destroyed
+    #[error("Activity {0} has been destroyed")]
+    ActivityDestroyed(i64),
+    
+    /// A colour string could not be parsed
+    #[error("Invalid colour: {0}")]
+    InvalidColor(String),
+    
+    /// A size string could not be parsed
+    #[error("Invalid dimension: {0}")]
+    InvalidDimension(String),
+    
+    /// A layout description file is malformed
+    #[error("Invalid layout: {0}")]
+    InvalidLayout(String),
     
     /// View not found
     #[error("View with ID {0} not found")]
@@ -41,3 +118,165 @@ pub enum GuiError {
     #[error("Event handling error: {0}")]
     EventError(String),
 }
+
+impl GuiError {
+    /// Get the request the error happened in, if known
+    pub fn context(&self) -> Option<&CallContext> {
+        match self {
+            GuiError::Call { context, .. } => Some(context),
+            _ => None,
+        }
+    }
+    
+    /// Get the error without its request context
+    ///
+    /// ```rust
+    /// use termux_gui::GuiError;
+    ///
+    /// fn gone(error: &GuiError) -> bool {
+    ///     matches!(error.root(), GuiError::ActivityDestroyed(_))
+    /// }
+    /// ```
+    pub fn root(&self) -> &GuiError {
+        match self {
+            GuiError::Call { source, .. } => source.root(),
+            error => error,
+        }
+    }
+    
+    /// Build an `InvalidResponse` for `payload`, the reply to request `msg`
+    pub(crate) fn invalid_response(msg: &Value, payload: &Value) -> GuiError {
+        GuiError::InvalidResponse {
+            method: msg["method"].as_str().unwrap_or_default().to_string(),
+            payload: payload.clone(),
+        }
+        .in_call(msg)
+    }
+    
+    /// Attach the method, Activity and view of request `msg`
+    ///
+    /// Errors that already carry a context are returned unchanged.
+    pub(crate) fn in_call(self, msg: &Value) -> GuiError {
+        match (&self, CallContext::from_request(msg)) {
+            (GuiError::Call { .. }, _) | (_, None) => self,
+            (_, Some(context)) => GuiError::Call { context, source: Box::new(self) },
+        }
+    }
+}
+
+/// The request an error happened in
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CallContext {
+    /// Protocol method, e.g. `setText`
+    pub method: String,
+    /// Activity ID, if the request has one
+    pub aid: Option<i64>,
+    /// View ID, if the request has one
+    pub id: Option<i64>,
+}
+
+impl CallContext {
+    /// Take the context from a request; `None` if it has no method
+    pub fn from_request(msg: &Value) -> Option<Self> {
+        Some(CallContext {
+            method: msg["method"].as_str()?.to_string(),
+            aid: msg["params"]["aid"].as_i64(),
+            id: msg["params"]["id"].as_i64(),
+        })
+    }
+}
+
+impl fmt::Display for CallContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.method)?;
+        match (self.aid, self.id) {
+            (Some(aid), Some(id)) => write!(f, " (aid {}, view {})", aid, id),
+            (Some(aid), None) => write!(f, " (aid {})", aid),
+            (None, Some(id)) => write!(f, " (view {})", id),
+            (None, None) => Ok(()),
+        }
+    }
+}
+
+fn stderr_suffix(stderr: &str) -> String {
+    if stderr.is_empty() {
+        String::new()
+    } else {
+        format!(" ({})", stderr)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn request() -> Value {
+        json!({ "method": "getDimensions", "params": { "aid": 2, "id": 17 } })
+    }
+
+    #[test]
+    fn invalid_responses_name_the_request() {
+        let error = GuiError::invalid_response(&request(), &Value::Null);
+        assert_eq!(
+            error.to_string(),
+            "getDimensions (aid 2, view 17): Invalid response to getDimensions: null"
+        );
+        let context = error.context().unwrap();
+        assert_eq!((context.method.as_str(), context.aid, context.id), ("getDimensions", Some(2), Some(17)));
+        assert!(matches!(error.root(), GuiError::InvalidResponse { payload: Value::Null, .. }));
+    }
+
+    #[test]
+    fn context_is_attached_once() {
+        let error = GuiError::ActivityDestroyed(2).in_call(&request());
+        let again = error.in_call(&json!({ "method": "setText", "params": {} }));
+        assert_eq!(again.context().unwrap().method, "getDimensions");
+        assert!(matches!(again.root(), GuiError::ActivityDestroyed(2)));
+
+        // Messages without a method have no context to add
+        let plain = GuiError::EventError("bad".into()).in_call(&json!({}));
+        assert!(plain.context().is_none());
+        assert!(matches!(plain.root(), GuiError::EventError(_)));
+    }
+
+    #[test]
+    fn context_display() {
+        let context = |msg| CallContext::from_request(&msg).unwrap().to_string();
+        assert_eq!(context(json!({ "method": "toast", "params": { "text": "hi" } })), "toast");
+        assert_eq!(context(json!({ "method": "finishActivity", "params": { "aid": 1 } })), "finishActivity (aid 1)");
+        assert_eq!(context(json!({ "method": "custom", "params": { "id": 4 } })), "custom (view 4)");
+    }
+
+    #[test]
+    fn broadcast_failures_show_stderr() {
+        let error = |stderr: &str| GuiError::BroadcastFailed {
+            program: "am".into(),
+            status: "exit status: 1".into(),
+            stderr: stderr.into(),
+        };
+        assert_eq!(error("").to_string(), "Broadcast with `am` failed: exit status: 1");
+        assert_eq!(error("denied").to_string(), "Broadcast with `am` failed: exit status: 1 (denied)");
+    }
+
+    #[cfg(feature = "testing")]
+    #[test]
+    fn malformed_replies_are_errors_not_defaults() -> Result<()> {
+        use crate::testing::MockService;
+        use crate::view::View;
+
+        let (mock, mut activity) = MockService::activity(false)?;
+        let input = activity.create_edit_text("", None)?;
+        mock.set_response("getText", json!(5));
+        mock.set_response("getDimensions", json!([10]));
+
+        let error = input.get_text(&mut activity).unwrap_err();
+        let context = error.context().unwrap();
+        assert_eq!((context.method.as_str(), context.id), ("getText", Some(input.id())));
+        let error = View::new(input.id()).get_dimensions(&mut activity).unwrap_err();
+        assert_eq!(error.context().unwrap().method, "getDimensions");
+        assert!(matches!(error.root(), GuiError::InvalidResponse { payload, .. } if *payload == json!([10])));
+        Ok(())
+    }
+}
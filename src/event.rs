@@ -0,0 +1,577 @@
+//! Typed events from the Termux GUI event socket
+//!
+//! The service reports user interaction and lifecycle changes on the event
+//! socket as JSON objects of the form `{"type": "...", "value": {...}}`.
+//! This module decodes them into the strongly typed [`Event`] enum.
+//!
+//! Event types this crate does not know about, and known events in a shape
+//! it can't decode, are decoded into [`Event::Unknown`] instead of failing,
+//! so newer plugin versions keep working with older builds of your app.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use serde_json::json;
+//! use termux_gui::event::{Event, SelectedItem};
+//!
+//! let event = Event::from_json(&json!({
+//!     "type": "click",
+//!     "value": {"aid": 0, "id": 7, "set": true}
+//! })).unwrap();
+//! assert_eq!(event, Event::Click { aid: 0, id: 7, checked: Some(true) });
+//! assert_eq!(event.view_id(), Some(7));
+//!
+//! // Spinners report the selected text, TabLayouts report the index
+//! let event = Event::from_json(&json!({
+//!     "type": "itemselected",
+//!     "value": {"aid": 0, "id": 9, "selected": "Option 2"}
+//! })).unwrap();
+//! assert_eq!(event.selected_item(), Some(&SelectedItem::Text("Option 2".to_string())));
+//!
+//! // Unknown types are kept instead of rejected
+//! let event = Event::from_json(&json!({"type": "futureEvent", "value": 1})).unwrap();
+//! assert!(matches!(event, Event::Unknown { .. }));
+//! ```
+
+use std::os::unix::net::UnixStream;
+use serde_json::Value;
+
+use crate::connection::read_message;
+use crate::error::{GuiError, Result};
+
+/// The item reported by an `itemselected` event
+///
+/// Spinners report the **text** of the selected entry, while TabLayouts
+/// report the **index** of the selected tab.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SelectedItem {
+    /// Zero-based index of the selected item (TabLayout)
+    Index(i64),
+    /// Text of the selected item (Spinner)
+    Text(String),
+}
+
+impl SelectedItem {
+    /// Get the index, if the item was reported by index
+    pub fn as_index(&self) -> Option<i64> {
+        match self {
+            SelectedItem::Index(index) => Some(*index),
+            SelectedItem::Text(_) => None,
+        }
+    }
+
+    /// Get the text, if the item was reported by text
+    pub fn as_text(&self) -> Option<&str> {
+        match self {
+            SelectedItem::Index(_) => None,
+            SelectedItem::Text(text) => Some(text),
+        }
+    }
+}
+
+/// The kind of motion reported by a touch event
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TouchAction {
+    Down,
+    Up,
+    PointerDown,
+    PointerUp,
+    Move,
+    Cancel,
+    /// An action this crate does not know about
+    Other(String),
+}
+
+impl TouchAction {
+    fn parse(action: &str) -> Self {
+        match action {
+            "down" => TouchAction::Down,
+            "up" => TouchAction::Up,
+            "pointer_down" => TouchAction::PointerDown,
+            "pointer_up" => TouchAction::PointerUp,
+            "move" => TouchAction::Move,
+            "cancel" => TouchAction::Cancel,
+            other => TouchAction::Other(other.to_string()),
+        }
+    }
+}
+
+/// A single pointer position in a touch event
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TouchPointer {
+    /// Pointer id, stable while the pointer is down
+    pub id: i64,
+    /// X coordinate in pixels, relative to the view
+    pub x: i64,
+    /// Y coordinate in pixels, relative to the view
+    pub y: i64,
+}
+
+/// An event received from the Termux GUI service
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event {
+    /// A view was clicked
+    ///
+    /// `checked` carries the new state for Checkbox, Switch, RadioButton
+    /// and ToggleButton clicks.
+    Click { aid: i64, id: i64, checked: Option<bool> },
+    /// A view was long-clicked
+    LongClick { aid: i64, id: i64 },
+    /// A view gained or lost focus
+    FocusChange { aid: i64, id: i64, focus: bool },
+    /// The text of an EditText (or other text view) changed
+    Text { aid: i64, id: i64, text: String },
+    /// A RadioButton in a RadioGroup was selected
+    ///
+    /// `selected` is the id of the checked RadioButton, or -1 if none.
+    Selected { aid: i64, id: i64, selected: i64 },
+    /// An item of a Spinner or a tab of a TabLayout was selected
+    ItemSelected { aid: i64, id: i64, selected: SelectedItem },
+    /// A SwipeRefreshLayout was pulled down
+    Refresh { aid: i64, id: i64 },
+    /// A touch event on a view (requires touch events to be enabled)
+    Touch {
+        aid: i64,
+        id: i64,
+        action: TouchAction,
+        /// Index of the pointer that triggered the event
+        index: i64,
+        /// Event time in milliseconds
+        time: i64,
+        /// Pointer positions, batched by the system
+        pointers: Vec<Vec<TouchPointer>>,
+    },
+
+    /// The Activity was created
+    Create { aid: i64 },
+    /// The Activity was started
+    Start { aid: i64 },
+    /// The Activity was resumed
+    Resume { aid: i64 },
+    /// The Activity was paused
+    Pause { aid: i64, finishing: bool },
+    /// The Activity was stopped
+    Stop { aid: i64, finishing: bool },
+    /// The Activity was destroyed
+    ///
+    /// If `finishing` is false the system is only recreating the Activity,
+    /// e.g. after a configuration change.
+    Destroy { aid: i64, finishing: bool },
+    /// The back button was pressed (requires the back button to be intercepted)
+    Back { aid: i64 },
+    /// The user is about to leave the Activity (e.g. pressed the home button)
+    UserLeave { aid: i64 },
+    /// The Activity configuration changed
+    Config { aid: i64, configuration: Value },
+    /// Picture-in-picture mode was entered or left
+    PipChanged { aid: i64, pip: bool },
+
+    /// A WebView is about to navigate to a new page
+    WebViewNavigation { aid: i64, id: i64, url: String },
+    /// A WebView received an HTTP error status
+    WebViewHttpError { aid: i64, id: i64, url: String, code: i64 },
+    /// A WebView failed to load a page
+    WebViewError { aid: i64, id: i64, url: String },
+    /// The renderer of a WebView was destroyed
+    WebViewDestroyed { aid: i64, id: i64 },
+    /// The page load progress of a WebView changed (0-100)
+    WebViewProgress { aid: i64, id: i64, progress: i64 },
+    /// A WebView page wrote to the JavaScript console
+    WebViewConsoleMessage { aid: i64, id: i64, message: String },
+
+    /// The screen was turned on
+    ScreenOn,
+    /// The screen was turned off
+    ScreenOff,
+    /// Airplane mode was toggled
+    Airplane { active: bool },
+    /// The system locale changed
+    Locale { locale: String },
+    /// The system timezone changed
+    Timezone { timezone: String },
+
+    /// An event type this crate does not know about, or a known event
+    /// missing fields it needs
+    Unknown { kind: String, value: Value },
+}
+
+impl Event {
+    /// Decode an event from its JSON representation
+    ///
+    /// Returns `GuiError::EventError` if the message has no `type` field.
+    /// Unknown event types, and known ones missing required fields, are
+    /// decoded into [`Event::Unknown`].
+    pub fn from_json(msg: &Value) -> Result<Self> {
+        let kind = msg["type"]
+            .as_str()
+            .ok_or_else(|| GuiError::EventError(format!("Missing event type: {}", msg)))?;
+        let value = &msg["value"];
+        Ok(Self::decode(kind, value).unwrap_or_else(|_| Event::Unknown {
+            kind: kind.to_string(),
+            value: value.clone(),
+        }))
+    }
+
+    /// Decode the `value` of a `kind` event, failing on missing fields
+    fn decode(kind: &str, value: &Value) -> Result<Self> {
+        let v = Fields { kind, value };
+
+        let event = match kind {
+            "click" => Event::Click {
+                aid: v.int("aid")?,
+                id: v.int("id")?,
+                checked: value["set"].as_bool(),
+            },
+            "longClick" => Event::LongClick { aid: v.int("aid")?, id: v.int("id")? },
+            "focusChange" => Event::FocusChange {
+                aid: v.int("aid")?,
+                id: v.int("id")?,
+                focus: v.bool("focus")?,
+            },
+            "text" => Event::Text {
+                aid: v.int("aid")?,
+                id: v.int("id")?,
+                text: v.string("text")?,
+            },
+            "selected" => Event::Selected {
+                aid: v.int("aid")?,
+                id: v.int("id")?,
+                selected: value["selected"].as_i64().unwrap_or(-1),
+            },
+            "itemselected" => {
+                let selected = match &value["selected"] {
+                    Value::String(text) => SelectedItem::Text(text.clone()),
+                    other => SelectedItem::Index(other.as_i64().ok_or_else(|| v.missing("selected"))?),
+                };
+                Event::ItemSelected { aid: v.int("aid")?, id: v.int("id")?, selected }
+            }
+            "refresh" => Event::Refresh { aid: v.int("aid")?, id: v.int("id")? },
+            "touch" => Event::Touch {
+                aid: v.int("aid")?,
+                id: v.int("id")?,
+                action: TouchAction::parse(&v.string("action")?),
+                index: value["index"].as_i64().unwrap_or(0),
+                time: value["time"].as_i64().unwrap_or(0),
+                pointers: parse_pointers(&value["pointers"]),
+            },
+
+            "create" => Event::Create { aid: v.int("aid")? },
+            "start" => Event::Start { aid: v.int("aid")? },
+            "resume" => Event::Resume { aid: v.int("aid")? },
+            "pause" => Event::Pause { aid: v.int("aid")?, finishing: v.finishing(false) },
+            "stop" => Event::Stop { aid: v.int("aid")?, finishing: v.finishing(false) },
+            "destroy" => Event::Destroy { aid: v.int("aid")?, finishing: v.finishing(true) },
+            "back" => Event::Back { aid: v.int("aid")? },
+            "UserLeaveHint" => Event::UserLeave { aid: v.int("aid")? },
+            "config" => Event::Config {
+                aid: v.int("aid")?,
+                configuration: value["configuration"].clone(),
+            },
+            "pipchanged" => Event::PipChanged {
+                aid: v.int("aid")?,
+                pip: v.bool("pip")?,
+            },
+
+            "webviewNavigation" => Event::WebViewNavigation {
+                aid: v.int("aid")?,
+                id: v.int("id")?,
+                url: v.string("url")?,
+            },
+            "webviewHTTPError" => Event::WebViewHttpError {
+                aid: v.int("aid")?,
+                id: v.int("id")?,
+                url: v.string("url")?,
+                code: v.int("code")?,
+            },
+            "webviewError" => Event::WebViewError {
+                aid: v.int("aid")?,
+                id: v.int("id")?,
+                url: v.string("url")?,
+            },
+            "webviewDestroyed" => Event::WebViewDestroyed { aid: v.int("aid")?, id: v.int("id")? },
+            "webviewProgress" => Event::WebViewProgress {
+                aid: v.int("aid")?,
+                id: v.int("id")?,
+                progress: v.int("progress")?,
+            },
+            "webviewConsoleMessage" => Event::WebViewConsoleMessage {
+                aid: v.int("aid")?,
+                id: v.int("id")?,
+                message: v.string("msg")?,
+            },
+
+            "screenon" => Event::ScreenOn,
+            "screenoff" => Event::ScreenOff,
+            "airplane" => Event::Airplane { active: value.as_bool().unwrap_or(false) },
+            "locale" => Event::Locale { locale: value.as_str().unwrap_or("").to_string() },
+            "timezone" => Event::Timezone { timezone: value.as_str().unwrap_or("").to_string() },
+
+            _ => Event::Unknown { kind: kind.to_string(), value: value.clone() },
+        };
+
+        Ok(event)
+    }
+
+    /// Get the Activity ID this event belongs to, if any
+    pub fn aid(&self) -> Option<i64> {
+        match self {
+            Event::Click { aid, .. }
+            | Event::LongClick { aid, .. }
+            | Event::FocusChange { aid, .. }
+            | Event::Text { aid, .. }
+            | Event::Selected { aid, .. }
+            | Event::ItemSelected { aid, .. }
+            | Event::Refresh { aid, .. }
+            | Event::Touch { aid, .. }
+            | Event::Create { aid }
+            | Event::Start { aid }
+            | Event::Resume { aid }
+            | Event::Pause { aid, .. }
+            | Event::Stop { aid, .. }
+            | Event::Destroy { aid, .. }
+            | Event::Back { aid }
+            | Event::UserLeave { aid }
+            | Event::Config { aid, .. }
+            | Event::PipChanged { aid, .. }
+            | Event::WebViewNavigation { aid, .. }
+            | Event::WebViewHttpError { aid, .. }
+            | Event::WebViewError { aid, .. }
+            | Event::WebViewDestroyed { aid, .. }
+            | Event::WebViewProgress { aid, .. }
+            | Event::WebViewConsoleMessage { aid, .. } => Some(*aid),
+            Event::Unknown { value, .. } => value["aid"].as_i64(),
+            _ => None,
+        }
+    }
+
+    /// Get the ID of the view this event belongs to, if any
+    pub fn view_id(&self) -> Option<i64> {
+        match self {
+            Event::Click { id, .. }
+            | Event::LongClick { id, .. }
+            | Event::FocusChange { id, .. }
+            | Event::Text { id, .. }
+            | Event::Selected { id, .. }
+            | Event::ItemSelected { id, .. }
+            | Event::Refresh { id, .. }
+            | Event::Touch { id, .. }
+            | Event::WebViewNavigation { id, .. }
+            | Event::WebViewHttpError { id, .. }
+            | Event::WebViewError { id, .. }
+            | Event::WebViewDestroyed { id, .. }
+            | Event::WebViewProgress { id, .. }
+            | Event::WebViewConsoleMessage { id, .. } => Some(*id),
+            Event::Unknown { value, .. } => value["id"].as_i64(),
+            _ => None,
+        }
+    }
+
+    /// Get the new checked state of a Checkbox, Switch, RadioButton or ToggleButton click
+    pub fn checked(&self) -> Option<bool> {
+        match self {
+            Event::Click { checked, .. } => *checked,
+            _ => None,
+        }
+    }
+
+    /// Get the selected item of an `itemselected` event
+    pub fn selected_item(&self) -> Option<&SelectedItem> {
+        match self {
+            Event::ItemSelected { selected, .. } => Some(selected),
+            _ => None,
+        }
+    }
+
+    /// Check whether this event means the Activity is gone for good
+    ///
+    /// Returns true for a `destroy` event that is not caused by the system
+    /// recreating the Activity.
+    pub fn is_final_destroy(&self) -> bool {
+        matches!(self, Event::Destroy { finishing: true, .. })
+    }
+}
+
+/// Read and decode the next event from an event stream
+pub fn read_event(stream: &mut UnixStream) -> Result<Event> {
+    let msg = read_message(stream)?;
+    Event::from_json(&msg)
+}
+
+/// Field accessor for the `value` object of an event
+struct Fields<'a> {
+    kind: &'a str,
+    value: &'a Value,
+}
+
+impl Fields<'_> {
+    fn missing(&self, field: &str) -> GuiError {
+        GuiError::EventError(format!("'{}' event without valid '{}': {}", self.kind, field, self.value))
+    }
+
+    fn int(&self, field: &str) -> Result<i64> {
+        self.value[field].as_i64().ok_or_else(|| self.missing(field))
+    }
+
+    fn bool(&self, field: &str) -> Result<bool> {
+        self.value[field].as_bool().ok_or_else(|| self.missing(field))
+    }
+
+    fn string(&self, field: &str) -> Result<String> {
+        self.value[field]
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| self.missing(field))
+    }
+
+    /// Older plugin versions don't report `finishing`
+    fn finishing(&self, default: bool) -> bool {
+        self.value["finishing"].as_bool().unwrap_or(default)
+    }
+}
+
+fn parse_pointers(value: &Value) -> Vec<Vec<TouchPointer>> {
+    let Some(groups) = value.as_array() else {
+        return Vec::new();
+    };
+
+    groups
+        .iter()
+        .map(|group| {
+            group
+                .as_array()
+                .map(|pointers| {
+                    pointers
+                        .iter()
+                        .map(|p| TouchPointer {
+                            id: p["id"].as_i64().unwrap_or(0),
+                            x: p["x"].as_i64().unwrap_or(0),
+                            y: p["y"].as_i64().unwrap_or(0),
+                        })
+                        .collect()
+                })
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn decodes_view_events() {
+        let click = Event::from_json(&json!({
+            "type": "click",
+            "value": {"aid": 1, "id": 2, "set": true}
+        }))
+        .unwrap();
+        assert_eq!(click, Event::Click { aid: 1, id: 2, checked: Some(true) });
+        assert_eq!((click.aid(), click.view_id(), click.checked()), (Some(1), Some(2), Some(true)));
+
+        let text = Event::from_json(&json!({
+            "type": "text",
+            "value": {"aid": 1, "id": 3, "text": "hi"}
+        }))
+        .unwrap();
+        assert_eq!(text, Event::Text { aid: 1, id: 3, text: "hi".to_string() });
+    }
+
+    #[test]
+    fn item_selected_is_an_index_or_text() {
+        let by_index = Event::from_json(&json!({
+            "type": "itemselected",
+            "value": {"aid": 1, "id": 2, "selected": 4}
+        }))
+        .unwrap();
+        assert_eq!(by_index.selected_item().and_then(SelectedItem::as_index), Some(4));
+
+        let by_text = Event::from_json(&json!({
+            "type": "itemselected",
+            "value": {"aid": 1, "id": 2, "selected": "Tab"}
+        }))
+        .unwrap();
+        assert_eq!(by_text.selected_item().and_then(SelectedItem::as_text), Some("Tab"));
+    }
+
+    #[test]
+    fn decodes_touch_pointers() {
+        let event = Event::from_json(&json!({
+            "type": "touch",
+            "value": {
+                "aid": 1,
+                "id": 2,
+                "action": "pointer_down",
+                "index": 1,
+                "time": 99,
+                "pointers": [[{"id": 0, "x": 5, "y": 6}, {"id": 1, "x": 7, "y": 8}]]
+            }
+        }))
+        .unwrap();
+
+        let Event::Touch { action, index, time, pointers, .. } = event else {
+            panic!("not a touch event: {:?}", event);
+        };
+        assert_eq!((action, index, time), (TouchAction::PointerDown, 1, 99));
+        assert_eq!(pointers[0][1], TouchPointer { id: 1, x: 7, y: 8 });
+    }
+
+    #[test]
+    fn destroy_is_final_unless_told_otherwise() {
+        let old_plugin = Event::from_json(&json!({"type": "destroy", "value": {"aid": 1}})).unwrap();
+        assert!(old_plugin.is_final_destroy());
+
+        let recreated = Event::from_json(&json!({
+            "type": "destroy",
+            "value": {"aid": 1, "finishing": false}
+        }))
+        .unwrap();
+        assert!(!recreated.is_final_destroy());
+
+        let pause = Event::from_json(&json!({"type": "pause", "value": {"aid": 1}})).unwrap();
+        assert_eq!(pause, Event::Pause { aid: 1, finishing: false });
+    }
+
+    #[test]
+    fn global_events_have_no_aid() {
+        let screen = Event::from_json(&json!({"type": "screenon", "value": null})).unwrap();
+        assert_eq!(screen, Event::ScreenOn);
+        assert_eq!(screen.aid(), None);
+
+        let locale = Event::from_json(&json!({"type": "locale", "value": "de"})).unwrap();
+        assert_eq!(locale, Event::Locale { locale: "de".to_string() });
+    }
+
+    #[test]
+    fn unknown_types_are_kept() {
+        let event = Event::from_json(&json!({"type": "newThing", "value": {"a": 1}})).unwrap();
+        assert_eq!(event, Event::Unknown { kind: "newThing".to_string(), value: json!({"a": 1}) });
+    }
+
+    #[test]
+    fn missing_type_is_an_error() {
+        let no_type = Event::from_json(&json!({"value": {}}));
+        assert!(matches!(no_type, Err(GuiError::EventError(_))));
+    }
+
+    #[test]
+    fn malformed_known_events_are_kept() {
+        let no_id = Event::from_json(&json!({"type": "click", "value": {"aid": 1}})).unwrap();
+        assert_eq!(no_id, Event::Unknown { kind: "click".to_string(), value: json!({"aid": 1}) });
+        assert_eq!(no_id.aid(), Some(1));
+
+        let new_shape = Event::from_json(&json!({"type": "text", "value": {"aid": 1, "id": 2, "text": ["a"]}}));
+        assert!(matches!(new_shape, Ok(Event::Unknown { kind, .. }) if kind == "text"));
+    }
+
+    #[test]
+    fn unknown_touch_actions_are_kept() {
+        let event = Event::from_json(&json!({
+            "type": "touch",
+            "value": {"aid": 1, "id": 2, "action": "wiggle"}
+        }))
+        .unwrap();
+        assert!(matches!(event, Event::Touch { action: TouchAction::Other(action), .. } if action == "wiggle"));
+    }
+}
@@ -0,0 +1,498 @@
+//! Callback-based event dispatching
+//!
+//! Instead of hand-rolling a `loop { match event ... }` that compares view
+//! ids, register closures on an [`EventLoop`] for the views you care about
+//! and let it drive the event socket.
+//!
+//! Handlers get an [`EventContext`] with mutable access to the [`Activity`],
+//! so they can update other widgets directly.
+//!
+//! ## Example
+//!
+//! ```rust,no_run
+//! use termux_gui::{Activity, EventLoop, Result};
+//!
+//! fn main() -> Result<()> {
+//!     let mut activity = Activity::new(true)?;
+//!     let layout = activity.create_linear_layout(None)?;
+//!     let label = activity.create_text_view("Clicked 0 times", Some(layout.id()))?;
+//!     let button = activity.create_button("Click Me", Some(layout.id()))?;
+//!     let quit = activity.create_button("Quit", Some(layout.id()))?;
+//!
+//!     let mut count = 0;
+//!     let mut events = EventLoop::new();
+//!     events.on_click(&button, |ctx| {
+//!         count += 1;
+//!         label.set_text(ctx.activity, &format!("Clicked {} times", count))
+//!     });
+//!     events.on_click(&quit, |ctx| {
+//!         ctx.activity.finish()?;
+//!         ctx.quit();
+//!         Ok(())
+//!     });
+//!
+//!     // Runs until a handler calls `quit()` or the Activity is destroyed
+//!     events.run(&mut activity)
+//! }
+//! ```
+
+use crate::activity::Activity;
+use crate::error::Result;
+use crate::event::{Event, SelectedItem};
+use crate::lifecycle::{ActivityState, SavedState};
+use crate::widget::Widget;
+
+/// Context passed to event handlers
+pub struct EventContext<'a> {
+    /// The Activity the event loop is running on
+    pub activity: &'a mut Activity,
+    /// The event being dispatched
+    pub event: &'a Event,
+    quit: bool,
+}
+
+impl EventContext<'_> {
+    /// Stop the event loop after the current event has been dispatched
+    pub fn quit(&mut self) {
+        self.quit = true;
+    }
+}
+
+type Handler<'h> = Box<dyn FnMut(&mut EventContext<'_>) -> Result<()> + 'h>;
+type SaveHook<'h> = Box<dyn FnMut(&mut EventContext<'_>, &mut SavedState) -> Result<()> + 'h>;
+type RestoreHook<'h> = Box<dyn FnMut(&mut EventContext<'_>, &SavedState) -> Result<()> + 'h>;
+
+/// The Activity and view a handler is registered for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Target {
+    aid: i64,
+    id: i64,
+}
+
+impl Target {
+    fn of(widget: &impl Widget) -> Self {
+        Target { aid: widget.aid(), id: widget.id() }
+    }
+}
+
+/// Which events a handler is registered for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Route {
+    Click(Target),
+    LongClick(Target),
+    FocusChange(Target),
+    Text(Target),
+    Selected(Target),
+    ItemSelected(Target),
+    Refresh(Target),
+    Lifecycle,
+    Destroy,
+    Any,
+}
+
+impl Route {
+    /// Check whether the handler fires for `event`, received by Activity `aid`
+    fn matches(&self, event: &Event, aid: i64) -> bool {
+        match (*self, event) {
+            (Route::Click(target), Event::Click { aid, id, .. })
+            | (Route::LongClick(target), Event::LongClick { aid, id })
+            | (Route::FocusChange(target), Event::FocusChange { aid, id, .. })
+            | (Route::Text(target), Event::Text { aid, id, .. })
+            | (Route::Selected(target), Event::Selected { aid, id, .. })
+            | (Route::ItemSelected(target), Event::ItemSelected { aid, id, .. })
+            | (Route::Refresh(target), Event::Refresh { aid, id }) => target == Target { aid: *aid, id: *id },
+            (Route::Lifecycle, _) => ActivityState::from_event(event).is_some(),
+            (Route::Destroy, _) => event.is_final_destroy() && event.aid() == Some(aid),
+            (Route::Any, _) => true,
+            _ => false,
+        }
+    }
+}
+
+/// Routes events to handlers registered by widget
+///
+/// View handlers only fire for events from the widget's own Activity, so
+/// one loop can serve several activities whose view IDs overlap.
+/// Handlers run in registration order. The `'h` lifetime lets handlers
+/// borrow local state such as widgets and counters.
+#[derive(Default)]
+pub struct EventLoop<'h> {
+    handlers: Vec<(Route, Handler<'h>)>,
+    save_hooks: Vec<SaveHook<'h>>,
+    restore_hooks: Vec<RestoreHook<'h>>,
+    /// State saved when the Activity was last stopped
+    saved: Option<SavedState>,
+    /// Whether the system destroyed the Activity without finishing it
+    recreating: bool,
+}
+
+impl<'h> EventLoop<'h> {
+    /// Create an event loop without any handlers
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn register<F>(&mut self, route: Route, handler: F) -> &mut Self
+    where
+        F: FnMut(&mut EventContext<'_>) -> Result<()> + 'h,
+    {
+        self.handlers.push((route, Box::new(handler)));
+        self
+    }
+
+    /// Call `handler` when `widget` is clicked
+    pub fn on_click<F>(&mut self, widget: &impl Widget, handler: F) -> &mut Self
+    where
+        F: FnMut(&mut EventContext<'_>) -> Result<()> + 'h,
+    {
+        self.register(Route::Click(Target::of(widget)), handler)
+    }
+
+    /// Call `handler` when `widget` is long-clicked
+    pub fn on_long_click<F>(&mut self, widget: &impl Widget, handler: F) -> &mut Self
+    where
+        F: FnMut(&mut EventContext<'_>) -> Result<()> + 'h,
+    {
+        self.register(Route::LongClick(Target::of(widget)), handler)
+    }
+
+    /// Call `handler` with the new state when a Checkbox, Switch,
+    /// RadioButton or ToggleButton is toggled
+    pub fn on_checked<F>(&mut self, widget: &impl Widget, mut handler: F) -> &mut Self
+    where
+        F: FnMut(&mut EventContext<'_>, bool) -> Result<()> + 'h,
+    {
+        self.register(Route::Click(Target::of(widget)), move |ctx| {
+            match ctx.event.checked() {
+                Some(checked) => handler(ctx, checked),
+                None => Ok(()),
+            }
+        })
+    }
+
+    /// Call `handler` with the focus state when `widget` gains or loses focus
+    pub fn on_focus_change<F>(&mut self, widget: &impl Widget, mut handler: F) -> &mut Self
+    where
+        F: FnMut(&mut EventContext<'_>, bool) -> Result<()> + 'h,
+    {
+        self.register(Route::FocusChange(Target::of(widget)), move |ctx| {
+            match ctx.event {
+                Event::FocusChange { focus, .. } => handler(ctx, *focus),
+                _ => Ok(()),
+            }
+        })
+    }
+
+    /// Call `handler` with the new text when the text of `widget` changes
+    pub fn on_text<F>(&mut self, widget: &impl Widget, mut handler: F) -> &mut Self
+    where
+        F: FnMut(&mut EventContext<'_>, &str) -> Result<()> + 'h,
+    {
+        self.register(Route::Text(Target::of(widget)), move |ctx| {
+            match ctx.event {
+                Event::Text { text, .. } => handler(ctx, text),
+                _ => Ok(()),
+            }
+        })
+    }
+
+    /// Call `handler` with the id of the checked RadioButton when the
+    /// selection of a RadioGroup changes
+    pub fn on_selected<F>(&mut self, widget: &impl Widget, mut handler: F) -> &mut Self
+    where
+        F: FnMut(&mut EventContext<'_>, i64) -> Result<()> + 'h,
+    {
+        self.register(Route::Selected(Target::of(widget)), move |ctx| {
+            match ctx.event {
+                Event::Selected { selected, .. } => handler(ctx, *selected),
+                _ => Ok(()),
+            }
+        })
+    }
+
+    /// Call `handler` with the selected item of a Spinner or TabLayout
+    pub fn on_item_selected<F>(&mut self, widget: &impl Widget, mut handler: F) -> &mut Self
+    where
+        F: FnMut(&mut EventContext<'_>, &SelectedItem) -> Result<()> + 'h,
+    {
+        self.register(Route::ItemSelected(Target::of(widget)), move |ctx| {
+            match ctx.event {
+                Event::ItemSelected { selected, .. } => handler(ctx, selected),
+                _ => Ok(()),
+            }
+        })
+    }
+
+    /// Call `handler` when a SwipeRefreshLayout is pulled down
+    pub fn on_refresh<F>(&mut self, widget: &impl Widget, handler: F) -> &mut Self
+    where
+        F: FnMut(&mut EventContext<'_>) -> Result<()> + 'h,
+    {
+        self.register(Route::Refresh(Target::of(widget)), handler)
+    }
+
+    /// Call `handler` with the new state whenever the Activity's lifecycle
+    /// state changes
+    pub fn on_lifecycle<F>(&mut self, mut handler: F) -> &mut Self
+    where
+        F: FnMut(&mut EventContext<'_>, ActivityState) -> Result<()> + 'h,
+    {
+        self.register(Route::Lifecycle, move |ctx| {
+            if ctx.event.aid() != Some(ctx.activity.id()) {
+                return Ok(());
+            }
+            match ActivityState::from_event(ctx.event) {
+                Some(state) => handler(ctx, state),
+                None => Ok(()),
+            }
+        })
+    }
+
+    /// Call `handler` to save widget state whenever the Activity is stopped
+    ///
+    /// Android may destroy a stopped Activity and recreate it later; the
+    /// state saved last is then passed to the
+    /// [`on_restore_state`](Self::on_restore_state) hooks.
+    pub fn on_save_state<F>(&mut self, handler: F) -> &mut Self
+    where
+        F: FnMut(&mut EventContext<'_>, &mut SavedState) -> Result<()> + 'h,
+    {
+        self.save_hooks.push(Box::new(handler));
+        self
+    }
+
+    /// Call `handler` with the saved state when the Activity is recreated
+    /// after the system destroyed it
+    ///
+    /// Runs after the handlers for the `create` event, so an
+    /// [`on_lifecycle`](Self::on_lifecycle) handler can rebuild the views
+    /// first.
+    pub fn on_restore_state<F>(&mut self, handler: F) -> &mut Self
+    where
+        F: FnMut(&mut EventContext<'_>, &SavedState) -> Result<()> + 'h,
+    {
+        self.restore_hooks.push(Box::new(handler));
+        self
+    }
+
+    /// Call `handler` when the Activity is finally destroyed
+    ///
+    /// Not called when the system only destroys the Activity to recreate
+    /// it, nor for other activities. The Activity no longer exists at this
+    /// point, so don't send it any more requests from here.
+    pub fn on_destroy<F>(&mut self, handler: F) -> &mut Self
+    where
+        F: FnMut(&mut EventContext<'_>) -> Result<()> + 'h,
+    {
+        self.register(Route::Destroy, handler)
+    }
+
+    /// Call `handler` for every event
+    pub fn on_event<F>(&mut self, handler: F) -> &mut Self
+    where
+        F: FnMut(&mut EventContext<'_>) -> Result<()> + 'h,
+    {
+        self.register(Route::Any, handler)
+    }
+
+    /// Run all handlers registered for `event`
+    ///
+    /// Returns `false` if a handler asked the loop to quit.
+    pub fn dispatch(&mut self, activity: &mut Activity, event: &Event) -> Result<bool> {
+        let mut ctx = EventContext { activity, event, quit: false };
+
+        let lifecycle = match event.aid() {
+            Some(aid) if aid == ctx.activity.id() => ActivityState::from_event(event),
+            _ => None,
+        };
+
+        // Save before handlers get a chance to tear anything down
+        match lifecycle {
+            Some(ActivityState::Stopped) if !self.save_hooks.is_empty() => {
+                let mut saved = SavedState::new();
+                for hook in self.save_hooks.iter_mut() {
+                    hook(&mut ctx, &mut saved)?;
+                }
+                self.saved = Some(saved);
+            }
+            Some(ActivityState::Recreating) => self.recreating = true,
+            _ => {}
+        }
+
+        for (route, handler) in self.handlers.iter_mut() {
+            if route.matches(event, ctx.activity.id()) {
+                handler(&mut ctx)?;
+            }
+        }
+
+        // Restore after `on_lifecycle` handlers had a chance to rebuild views
+        if lifecycle == Some(ActivityState::Created) && self.recreating {
+            self.recreating = false;
+            if let Some(saved) = self.saved.take() {
+                for hook in self.restore_hooks.iter_mut() {
+                    hook(&mut ctx, &saved)?;
+                }
+            }
+        }
+
+        Ok(!ctx.quit)
+    }
+
+    /// Read events from the Activity and dispatch them until a handler
+    /// calls [`EventContext::quit`] or the Activity is finally destroyed
+    pub fn run(&mut self, activity: &mut Activity) -> Result<()> {
+        loop {
+            let event = activity.next_event()?;
+
+            if !self.dispatch(activity, &event)? {
+                return Ok(());
+            }
+
+            if event.is_final_destroy() && event.aid() == Some(activity.id()) {
+                return Ok(());
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use std::cell::{Cell, RefCell};
+
+    use serde_json::json;
+
+    use super::*;
+    use crate::testing::MockService;
+
+    #[test]
+    fn click_handlers_match_activity_and_view() -> Result<()> {
+        let (_mock, mut activity) = MockService::activity(false)?;
+        let button = activity.create_button("OK", None)?;
+        let other = activity.create_button("Other", None)?;
+        let aid = activity.id();
+
+        let clicks = Cell::new(0);
+        let mut events = EventLoop::new();
+        events.on_click(&button, |_| {
+            clicks.set(clicks.get() + 1);
+            Ok(())
+        });
+
+        events.dispatch(&mut activity, &Event::Click { aid, id: button.id(), checked: None })?;
+        events.dispatch(&mut activity, &Event::Click { aid, id: other.id(), checked: None })?;
+        // Same view id, but in another Activity
+        events.dispatch(&mut activity, &Event::Click { aid: aid + 1, id: button.id(), checked: None })?;
+        assert_eq!(clicks.get(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn checked_and_text_handlers_get_the_new_value() -> Result<()> {
+        let (_mock, mut activity) = MockService::activity(false)?;
+        let checkbox = activity.create_checkbox("Check", None)?;
+        let input = activity.create_edit_text("", None)?;
+        let aid = activity.id();
+
+        let checked = RefCell::new(Vec::new());
+        let text = RefCell::new(String::new());
+        let mut events = EventLoop::new();
+        events.on_checked(&checkbox, |_, state| {
+            checked.borrow_mut().push(state);
+            Ok(())
+        });
+        events.on_text(&input, |_, value| {
+            *text.borrow_mut() = value.to_string();
+            Ok(())
+        });
+
+        let id = checkbox.id();
+        events.dispatch(&mut activity, &Event::Click { aid, id, checked: Some(true) })?;
+        // A click without a state isn't a toggle
+        events.dispatch(&mut activity, &Event::Click { aid, id, checked: None })?;
+        let id = input.id();
+        events.dispatch(&mut activity, &Event::Text { aid, id, text: "typed".to_string() })?;
+
+        assert_eq!(*checked.borrow(), [true]);
+        assert_eq!(*text.borrow(), "typed");
+        Ok(())
+    }
+
+    #[test]
+    fn run_stops_when_a_handler_quits() -> Result<()> {
+        let (mock, mut activity) = MockService::activity(false)?;
+        let quit = activity.create_button("Quit", None)?;
+        let seen = Cell::new(0);
+
+        let mut events = EventLoop::new();
+        events.on_event(|_| {
+            seen.set(seen.get() + 1);
+            Ok(())
+        });
+        events.on_click(&quit, |ctx| {
+            ctx.quit();
+            Ok(())
+        });
+
+        mock.send_event(&json!({"type": "resume", "value": {"aid": activity.id()}}))?;
+        mock.send_event(&json!({"type": "click", "value": {"aid": activity.id(), "id": quit.id()}}))?;
+        events.run(&mut activity)?;
+        assert_eq!(seen.get(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn run_stops_after_the_final_destroy() -> Result<()> {
+        let (mock, mut activity) = MockService::activity(false)?;
+        let destroyed = Cell::new(false);
+
+        let mut events = EventLoop::new();
+        events.on_destroy(|_| {
+            destroyed.set(true);
+            Ok(())
+        });
+
+        mock.send_event(&json!({
+            "type": "destroy",
+            "value": {"aid": activity.id(), "finishing": true}
+        }))?;
+        events.run(&mut activity)?;
+        assert!(destroyed.get());
+        Ok(())
+    }
+
+    #[test]
+    fn on_destroy_ignores_recreation() -> Result<()> {
+        let (_mock, mut activity) = MockService::activity(false)?;
+        let aid = activity.id();
+        let destroyed = Cell::new(0);
+
+        let mut events = EventLoop::new();
+        events.on_destroy(|_| {
+            destroyed.set(destroyed.get() + 1);
+            Ok(())
+        });
+
+        events.dispatch(&mut activity, &Event::Destroy { aid, finishing: false })?;
+        assert_eq!(destroyed.get(), 0);
+        events.dispatch(&mut activity, &Event::Destroy { aid, finishing: true })?;
+        assert_eq!(destroyed.get(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn on_destroy_ignores_other_activities() -> Result<()> {
+        let (_mock, mut activity) = MockService::activity(false)?;
+        let other = activity.id() + 1;
+        let destroyed = Cell::new(false);
+
+        let mut events = EventLoop::new();
+        events.on_destroy(|_| {
+            destroyed.set(true);
+            Ok(())
+        });
+
+        events.dispatch(&mut activity, &Event::Destroy { aid: other, finishing: true })?;
+        assert!(!destroyed.get());
+        Ok(())
+    }
+}
@@ -0,0 +1,474 @@
+//! Sessions with several activities on one connection
+//!
+//! A [`Gui`] owns the connection to the Termux GUI service. Every
+//! [`Activity`] created from it shares that connection, so opening another
+//! window doesn't need another socket pair or `am broadcast`.
+//!
+//! Events from the service are routed by Activity ID: each Activity's
+//! [`next_event`](Activity::next_event) only returns events for that
+//! Activity, plus its own copy of every global event such as `screen_on`,
+//! which has no Activity ID. Events read while waiting for another Activity
+//! are queued until that Activity asks for them; threads waiting for
+//! different activities don't block each other. [`Gui::next_event`] returns
+//! all events in arrival order.
+//!
+//! Each queue keeps at most [`MAX_QUEUED_EVENTS`], dropping the oldest when
+//! an Activity doesn't read its events. The queue of a finished Activity is
+//! removed once its final `destroy` event has been taken.
+//!
+//! `Gui` is a cheap, `Send + Sync` handle; clones refer to the same session
+//! and requests from several threads are serialized over the main socket.
+//! To receive events on a background thread instead of blocking in
+//! `next_event`, use [`Gui::spawn_event_reader`].
+//!
+//! ## Example
+//!
+//! ```rust,no_run
+//! use termux_gui::{Event, Gui, Result};
+//!
+//! fn main() -> Result<()> {
+//!     let gui = Gui::new()?;
+//!
+//!     let mut main = gui.new_activity(false)?;
+//!     let layout = main.create_linear_layout(None)?;
+//!     let open = main.create_button("Open dialog", Some(layout.id()))?;
+//!
+//!     loop {
+//!         match gui.next_event()? {
+//!             Event::Click { aid, id, .. } if aid == main.id() && id == open.id() => {
+//!                 // Opens in the same task as the main Activity
+//!                 let mut dialog = gui.new_activity_in_task(main.task_id(), true)?;
+//!                 dialog.create_text_view("Hello from a second Activity", None)?;
+//!             }
+//!             event if event.is_final_destroy() && event.aid() == Some(main.id()) => {
+//!                 return Ok(());
+//!             }
+//!             _ => {}
+//!         }
+//!     }
+//! }
+//! ```
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::thread;
+use serde_json::{json, Value};
+
+use crate::activity::{Activity, ActivityBuilder};
+use crate::connection::{Connection, EventReader};
+use crate::error::{GuiError, Result};
+use crate::event::Event;
+use crate::lifecycle::ActivityState;
+use crate::raw::{self, ActivityIds, NewActivity, Request};
+use crate::trace::debug;
+use crate::transport::Connector;
+
+/// A connection to the Termux GUI service shared by several activities
+#[derive(Clone)]
+pub struct Gui {
+    shared: Arc<Shared>,
+}
+
+struct Shared {
+    conn: Mutex<Connection>,
+    reader: Mutex<EventReader>,
+    events: Mutex<EventQueue>,
+    /// Signalled whenever an event is queued or a read ends
+    arrived: Condvar,
+    states: Mutex<HashMap<i64, ActivityState>>,
+}
+
+/// How many events are queued per Activity before the oldest are dropped
+pub const MAX_QUEUED_EVENTS: usize = 1024;
+
+/// Events read from the socket but not yet taken
+///
+/// Every Activity has its own inbox; global events are copied into each of
+/// them, or into the `None` inbox while there is no Activity to take them.
+/// Copies share a sequence number, which keeps [`Gui::next_event`] in
+/// arrival order and lets it take all copies at once.
+#[derive(Default)]
+struct EventQueue {
+    inboxes: HashMap<Option<i64>, Inbox>,
+    next_seq: u64,
+    /// Whether a thread is blocked reading the event socket
+    reading: bool,
+}
+
+#[derive(Default)]
+struct Inbox {
+    events: VecDeque<(u64, Event)>,
+    /// The final `destroy` event has been queued; nothing follows it
+    finished: bool,
+}
+
+impl EventQueue {
+    /// Route a freshly read event to the inboxes that should see it
+    fn push(&mut self, event: Event) {
+        let seq = self.next_seq;
+        self.next_seq += 1;
+
+        match event.aid() {
+            Some(aid) => {
+                let inbox = self.inboxes.entry(Some(aid)).or_default();
+                if inbox.finished {
+                    debug!(aid, ?event, "dropping event for a finished Activity");
+                    return;
+                }
+                inbox.finished = event.is_final_destroy();
+                inbox.push(seq, event);
+            }
+            None => {
+                let mut inboxes: Vec<_> = self
+                    .inboxes
+                    .iter_mut()
+                    .filter(|(aid, inbox)| aid.is_some() && !inbox.finished)
+                    .map(|(_, inbox)| inbox)
+                    .collect();
+                if inboxes.is_empty() {
+                    self.inboxes.entry(None).or_default().push(seq, event);
+                } else {
+                    for inbox in inboxes.iter_mut() {
+                        inbox.push(seq, event.clone());
+                    }
+                }
+            }
+        }
+    }
+
+    /// Take the next event for Activity `aid`, or any Activity if `None`
+    fn take(&mut self, aid: Option<i64>) -> Option<Event> {
+        let seq = match aid {
+            Some(aid) => self.inboxes.get(&Some(aid))?.events.front()?.0,
+            None => self
+                .inboxes
+                .values()
+                .filter_map(|inbox| inbox.events.front().map(|(seq, _)| *seq))
+                .min()?,
+        };
+
+        // `Gui::next_event` takes every copy of a global event
+        let mut event = None;
+        self.inboxes.retain(|key, inbox| {
+            let matches = inbox.events.front().is_some_and(|(front, _)| *front == seq);
+            if matches && (aid.is_none() || *key == aid) {
+                event = inbox.events.pop_front().map(|(_, event)| event);
+            }
+            !(inbox.finished && inbox.events.is_empty())
+        });
+        event
+    }
+}
+
+impl Inbox {
+    fn push(&mut self, seq: u64, event: Event) {
+        if self.events.len() >= MAX_QUEUED_EVENTS {
+            let _dropped = self.events.pop_front();
+            debug!(event = ?_dropped, "event queue full, dropping the oldest event");
+        }
+        self.events.push_back((seq, event));
+    }
+}
+
+impl Gui {
+    /// Connect to the Termux GUI service
+    ///
+    /// Uses the same [`default_connector`](crate::transport::default_connector)
+    /// as `Connection::new`.
+    pub fn new() -> Result<Self> {
+        Self::with_connection(Connection::new()?)
+    }
+
+    /// Connect with a custom [`Connector`]
+    pub fn with_connector<C: Connector + ?Sized>(connector: &C) -> Result<Self> {
+        Self::with_connection(connector.connect()?)
+    }
+
+    /// Start a session on an already established connection
+    pub fn with_connection(conn: Connection) -> Result<Self> {
+        Ok(Gui {
+            shared: Arc::new(Shared {
+                reader: Mutex::new(conn.event_reader()?),
+                conn: Mutex::new(conn),
+                events: Mutex::new(EventQueue::default()),
+                arrived: Condvar::new(),
+                states: Mutex::new(HashMap::new()),
+            }),
+        })
+    }
+
+    /// Create a new Activity in a new task
+    ///
+    /// # Arguments
+    /// * `dialog` - If true, creates a dialog-style window; if false, creates a full-screen activity
+    pub fn new_activity(&self, dialog: bool) -> Result<Activity> {
+        ActivityBuilder::new().dialog(dialog).build_in(self)
+    }
+
+    /// Create a new Activity in the task of an existing one
+    ///
+    /// Get the task ID with [`Activity::task_id`]. For more options use
+    /// [`ActivityBuilder::build_in`].
+    pub fn new_activity_in_task(&self, tid: i64, dialog: bool) -> Result<Activity> {
+        ActivityBuilder::new().dialog(dialog).task(tid).build_in(self)
+    }
+
+    /// Send `newActivity` with `params` and wrap the result
+    pub(crate) fn launch(&self, params: &NewActivity) -> Result<Activity> {
+        let ActivityIds { aid, tid } = raw::new_activity(&mut self.clone(), params)?;
+
+        // Only launching into an existing task leaves out the tid
+        let tid = tid.or(params.tid).ok_or_else(|| GuiError::InvalidResponse {
+            method: NewActivity::METHOD.to_string(),
+            payload: json!(aid),
+        })?;
+
+        // The reader may already have seen lifecycle events for it
+        lock(&self.shared.states).entry(aid).or_insert(ActivityState::Created);
+        // Global events read from now on are copied for it
+        lock(&self.shared.events).inboxes.entry(Some(aid)).or_default();
+        Activity::attach(self.clone(), aid, tid)
+    }
+
+    /// Send a message and read the response
+    pub fn send_read(&self, msg: &Value) -> Result<Value> {
+        self.conn().send_read(msg)
+    }
+
+    /// Send a message without waiting for response
+    pub fn send(&self, msg: &Value) -> Result<()> {
+        self.conn().send(msg)
+    }
+
+    /// Block until the next event of any Activity arrives
+    pub fn next_event(&self) -> Result<Event> {
+        self.next_queued(None)
+    }
+
+    /// Get the last known lifecycle state of Activity `aid`
+    ///
+    /// Returns `None` for activities not created through this session.
+    pub fn activity_state(&self, aid: i64) -> Option<ActivityState> {
+        lock(&self.shared.states).get(&aid).copied()
+    }
+
+    /// Read events on a background thread and deliver them through a channel
+    ///
+    /// All events of all activities are sent in arrival order, starting with
+    /// any that are already queued. After a read error the error is sent and
+    /// the thread stops; it also stops once the receiver is dropped and the
+    /// next event arrives.
+    ///
+    /// The thread takes over the event socket: don't call `next_event` on
+    /// this session or its activities afterwards, as they would block.
+    ///
+    /// ```rust,no_run
+    /// use std::time::Duration;
+    /// use termux_gui::{Gui, Result};
+    ///
+    /// # fn main() -> Result<()> {
+    /// let gui = Gui::new()?;
+    /// let activity = gui.new_activity(true)?;
+    /// let events = gui.spawn_event_reader()?;
+    ///
+    /// loop {
+    ///     match events.recv_timeout(Duration::from_millis(100)) {
+    ///         Ok(event) => println!("{:?}", event?),
+    ///         Err(_) => { /* do other work */ }
+    ///     }
+    /// }
+    /// # }
+    /// ```
+    pub fn spawn_event_reader(&self) -> Result<Receiver<Result<Event>>> {
+        let (tx, rx) = mpsc::channel();
+        let gui = self.clone();
+
+        thread::Builder::new()
+            .name("termux-gui-events".to_string())
+            .spawn(move || loop {
+                let event = gui.next_event();
+                let failed = event.is_err();
+                if tx.send(event).is_err() || failed {
+                    return;
+                }
+            })?;
+
+        Ok(rx)
+    }
+
+    /// Block until the next event for Activity `aid`, or a global event, arrives
+    pub(crate) fn next_event_for(&self, aid: i64) -> Result<Event> {
+        self.next_queued(Some(aid))
+    }
+
+    /// Take the next queued event for `aid`, reading from the socket if needed
+    ///
+    /// Only one thread reads at a time, without holding the queue lock, so
+    /// threads waiting for other activities can take events it queues for
+    /// them. Lifecycle events update the Activity states as soon as they
+    /// are read.
+    fn next_queued(&self, aid: Option<i64>) -> Result<Event> {
+        let mut events = lock(&self.shared.events);
+        loop {
+            if let Some(event) = events.take(aid) {
+                return Ok(event);
+            }
+            if events.reading {
+                events = self.shared.arrived.wait(events).unwrap_or_else(|e| e.into_inner());
+                continue;
+            }
+
+            events.reading = true;
+            drop(events);
+            let read = lock(&self.shared.reader).read();
+            if let Ok(event) = &read {
+                if let (Some(aid), Some(state)) = (event.aid(), ActivityState::from_event(event)) {
+                    lock(&self.shared.states).insert(aid, state);
+                }
+            }
+
+            events = lock(&self.shared.events);
+            events.reading = false;
+            let read = read.map(|event| events.push(event));
+            // Wake the others even after an error, so one of them reads next
+            self.shared.arrived.notify_all();
+            read?;
+        }
+    }
+
+    /// Clone the raw event socket
+    pub(crate) fn clone_event_stream(&self) -> Result<std::os::unix::net::UnixStream> {
+        Ok(self.conn().event_stream.try_clone()?)
+    }
+
+    /// Lock the connection, e.g. to pipeline several requests
+    pub(crate) fn conn(&self) -> MutexGuard<'_, Connection> {
+        lock(&self.shared.conn)
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+// Handles are meant to be shared with worker threads
+const _: () = {
+    fn assert_send_sync<T: Send + Sync>() {}
+    let _ = assert_send_sync::<Gui>;
+    let _ = assert_send_sync::<crate::activity::ActivityHandle>;
+};
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use std::time::Duration;
+
+    use serde_json::json;
+
+    use super::*;
+    use crate::testing::MockService;
+
+    fn click(aid: i64, id: i64) -> Value {
+        json!({"type": "click", "value": {"aid": aid, "id": id}})
+    }
+
+    #[test]
+    fn events_are_routed_by_activity() -> Result<()> {
+        let (mock, gui) = MockService::gui()?;
+        let mut first = gui.new_activity(false)?;
+        let mut second = gui.new_activity(false)?;
+
+        mock.send_event(&click(first.id(), 1))?;
+        mock.send_event(&click(second.id(), 2))?;
+        mock.send_event(&click(first.id(), 3))?;
+
+        assert_eq!(second.next_event()?.view_id(), Some(2));
+        assert_eq!(first.next_event()?.view_id(), Some(1));
+        assert_eq!(first.next_event()?.view_id(), Some(3));
+        Ok(())
+    }
+
+    #[test]
+    fn global_events_reach_every_activity() -> Result<()> {
+        let (mock, gui) = MockService::gui()?;
+        let mut first = gui.new_activity(false)?;
+        let mut second = gui.new_activity(false)?;
+
+        mock.send_event(&json!({"type": "screenoff", "value": null}))?;
+        assert_eq!(first.next_event()?, Event::ScreenOff);
+        assert_eq!(second.next_event()?, Event::ScreenOff);
+        Ok(())
+    }
+
+    #[test]
+    fn gui_takes_events_in_arrival_order_once() -> Result<()> {
+        let (mock, gui) = MockService::gui()?;
+        let first = gui.new_activity(false)?;
+        let second = gui.new_activity(false)?;
+
+        mock.send_event(&click(second.id(), 1))?;
+        mock.send_event(&json!({"type": "screenon", "value": null}))?;
+        mock.send_event(&click(first.id(), 2))?;
+
+        assert_eq!(gui.next_event()?.view_id(), Some(1));
+        // One copy of the global event, not one per Activity
+        assert_eq!(gui.next_event()?, Event::ScreenOn);
+        assert_eq!(gui.next_event()?.view_id(), Some(2));
+        Ok(())
+    }
+
+    #[test]
+    fn waiting_for_one_activity_doesnt_block_another() -> Result<()> {
+        let (mock, gui) = MockService::gui()?;
+        let first = gui.new_activity(false)?;
+        let mut second = gui.new_activity(false)?;
+        let first_aid = first.id();
+
+        // Blocks in the read until its event arrives
+        let waiting = {
+            let gui = gui.clone();
+            thread::spawn(move || gui.next_event_for(first_aid))
+        };
+        thread::sleep(Duration::from_millis(50));
+
+        mock.send_event(&click(second.id(), 1))?;
+        assert_eq!(second.next_event()?.view_id(), Some(1));
+
+        mock.send_event(&click(first_aid, 2))?;
+        assert_eq!(waiting.join().unwrap()?.view_id(), Some(2));
+        Ok(())
+    }
+
+    #[test]
+    fn lifecycle_events_update_states_when_read() -> Result<()> {
+        let (mock, gui) = MockService::gui()?;
+        let first = gui.new_activity(false)?;
+        let mut second = gui.new_activity(false)?;
+
+        mock.send_event(&json!({"type": "resume", "value": {"aid": first.id()}}))?;
+        mock.send_event(&click(second.id(), 1))?;
+        second.next_event()?;
+
+        assert_eq!(gui.activity_state(first.id()), Some(ActivityState::Resumed));
+        Ok(())
+    }
+
+    #[test]
+    fn queues_are_bounded_and_removed_after_the_final_destroy() {
+        let mut queue = EventQueue::default();
+        queue.inboxes.entry(Some(1)).or_default();
+        for id in 0..MAX_QUEUED_EVENTS as i64 + 10 {
+            queue.push(Event::Click { aid: 1, id, checked: None });
+        }
+        assert_eq!(queue.inboxes[&Some(1)].events.len(), MAX_QUEUED_EVENTS);
+        // The oldest were dropped
+        assert_eq!(queue.take(Some(1)).and_then(|event| event.view_id()), Some(10));
+
+        queue.push(Event::Destroy { aid: 1, finishing: true });
+        queue.push(Event::ScreenOn);
+        while let Some(event) = queue.take(Some(1)) {
+            assert_ne!(event, Event::ScreenOn);
+        }
+        assert!(!queue.inboxes.contains_key(&Some(1)));
+    }
+}
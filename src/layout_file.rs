@@ -0,0 +1,708 @@
+//! Layouts loaded from JSON or TOML files
+//!
+//! A screen can be described as a tree of widgets in a data file and
+//! created at runtime with [`LoadedLayout`], so layouts can be tweaked
+//! without recompiling. Widgets with a `name` are returned in a map and can
+//! be looked up by type with [`LoadedLayout::get`].
+//!
+//! ## Format
+//!
+//! Every node is an object with a `type` (the component name, e.g.
+//! `"LinearLayout"` or `"Button"`) and optional properties:
+//!
+//! - `name`: key for [`LoadedLayout::get`] and [`LoadedLayout::id`]
+//! - `width`, `height`, `margin`, `padding`: a number (dp) or a string such
+//!   as `"16dp"`, `"100px"`, `"14sp"`, `"match_parent"` or `"wrap_content"`
+//! - `weight`: weight inside a LinearLayout
+//! - `background`: a colour, `"#RRGGBB"`, `"#AARRGGBB"` or a CSS name
+//! - `visibility`: `"visible"`, `"invisible"` or `"gone"`
+//! - `grid`: position inside a GridLayout, `{"row", "col", "row_size",
+//!   "col_size", "align_row", "align_col"}`
+//! - `children`: child nodes, for layouts
+//!
+//! Text widgets also take `text`, `text_size` and `text_color`. Component
+//! specific properties are `orientation` (`"vertical"` or `"horizontal"`)
+//! for LinearLayout, `rows` and `cols` for GridLayout, `checked` for
+//! Checkbox, Switch, RadioButton and ToggleButton, `hint`, `input_type` and
+//! `single_line` for EditText, `items` for Spinner, `tabs` for TabLayout and
+//! `progress` for ProgressBar. Unknown properties are rejected, so typos
+//! don't go unnoticed. The whole file is checked before the first widget
+//! is created, so a mistake never leaves a half-built screen.
+//!
+//! TOML files need the `toml` feature.
+//!
+//! ## Example
+//!
+//! ```rust,no_run
+//! use termux_gui::layout_file::LoadedLayout;
+//! use termux_gui::{Activity, Button, EditText, Result};
+//!
+//! const LOGIN: &str = r##"{
+//!     "type": "LinearLayout",
+//!     "children": [
+//!         {"type": "TextView", "text": "Login", "text_size": 24, "margin": 8},
+//!         {"type": "EditText", "name": "user", "hint": "User name"},
+//!         {"type": "Button", "name": "ok", "text": "OK",
+//!          "width": "match_parent", "background": "#2196F3"}
+//!     ]
+//! }"##;
+//!
+//! fn main() -> Result<()> {
+//!     let mut activity = Activity::new(false)?;
+//!     let layout = LoadedLayout::from_json(&mut activity, LOGIN, None)?;
+//!
+//!     let user: &EditText = layout.get("user").expect("user field");
+//!     let ok: &Button = layout.get("ok").expect("ok button");
+//!     println!("{} / {}", user.id(), ok.id());
+//!     Ok(())
+//! }
+//! ```
+
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use serde_json::{Map, Value};
+use crate::activity::Activity;
+use crate::builder::sealed::Create;
+use crate::builder::WidgetBuilder;
+use crate::color::Color;
+use crate::components::{GridLayout, ProgressBar, Spinner, TabLayout};
+use crate::dimension::Dimension;
+use crate::error::{GuiError, Result};
+use crate::view::{View, Visibility};
+use crate::widget::{TextWidget, Widget};
+
+/// Properties every node may have
+const COMMON_KEYS: &[&str] = &[
+    "type", "name", "width", "height", "margin", "padding", "weight", "background",
+    "visibility", "grid", "children",
+];
+
+/// Properties of text widgets
+const TEXT_KEYS: &[&str] = &["text", "text_size", "text_color"];
+
+/// Widgets created from a layout file
+pub struct LoadedLayout {
+    root: i64,
+    widgets: HashMap<String, Box<dyn Widget>>,
+}
+
+impl LoadedLayout {
+    /// Create the widgets described by a JSON document
+    pub fn from_json(activity: &mut Activity, json: &str, parent: Option<i64>) -> Result<Self> {
+        let value: Value = serde_json::from_str(json)?;
+        Self::from_value(activity, &value, parent)
+    }
+
+    /// Create the widgets described by a TOML document
+    #[cfg(feature = "toml")]
+    pub fn from_toml(activity: &mut Activity, toml: &str, parent: Option<i64>) -> Result<Self> {
+        let value: Value = toml::from_str(toml).map_err(|e| GuiError::InvalidLayout(e.to_string()))?;
+        Self::from_value(activity, &value, parent)
+    }
+
+    /// Create the widgets described by a `.json` or `.toml` file
+    pub fn load(activity: &mut Activity, path: impl AsRef<Path>, parent: Option<i64>) -> Result<Self> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)?;
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("json") => Self::from_json(activity, &text, parent),
+            #[cfg(feature = "toml")]
+            Some("toml") => Self::from_toml(activity, &text, parent),
+            _ => Err(GuiError::InvalidLayout(format!(
+                "unsupported layout file {}",
+                path.display()
+            ))),
+        }
+    }
+
+    /// Create the widgets described by an already parsed document
+    pub fn from_value(activity: &mut Activity, root: &Value, parent: Option<i64>) -> Result<Self> {
+        validate(root, "root", &mut HashSet::new())?;
+        let mut widgets = HashMap::new();
+        let root = create(activity, root, parent, "root", &mut widgets)?;
+        Ok(LoadedLayout { root, widgets })
+    }
+
+    /// Get the view ID of the root widget
+    pub fn root_id(&self) -> i64 {
+        self.root
+    }
+
+    /// Get the view ID of the widget called `name`
+    pub fn id(&self, name: &str) -> Option<i64> {
+        self.widgets.get(name).map(|widget| widget.id())
+    }
+
+    /// Get the widget called `name`, if it is a `T`
+    pub fn get<T: Widget>(&self, name: &str) -> Option<&T> {
+        self.widget(name)?.downcast_ref::<T>()
+    }
+
+    /// Get the widget called `name`
+    pub fn widget(&self, name: &str) -> Option<&dyn Widget> {
+        self.widgets.get(name).map(|widget| widget.as_ref())
+    }
+
+    /// Get the names of all named widgets
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.widgets.keys().map(String::as_str)
+    }
+
+    /// Take the widget map
+    pub fn into_widgets(self) -> HashMap<String, Box<dyn Widget>> {
+        self.widgets
+    }
+}
+
+/// A node being read, with its location for error messages
+struct NodeReader<'v> {
+    props: &'v Map<String, Value>,
+    path: &'v str,
+}
+
+impl<'v> NodeReader<'v> {
+    fn new(node: &'v Value, path: &'v str) -> Result<Self> {
+        let props = node
+            .as_object()
+            .ok_or_else(|| GuiError::InvalidLayout(format!("{}: expected an object", path)))?;
+        Ok(NodeReader { props, path })
+    }
+
+    fn error(&self, message: impl std::fmt::Display) -> GuiError {
+        GuiError::InvalidLayout(format!("{}: {}", self.path, message))
+    }
+
+    fn check_keys(&self, extra: &[&str]) -> Result<()> {
+        for key in self.props.keys() {
+            if !COMMON_KEYS.contains(&key.as_str()) && !extra.contains(&key.as_str()) {
+                return Err(self.error(format!("unknown property `{}`", key)));
+            }
+        }
+        Ok(())
+    }
+
+    fn str(&self, key: &str) -> Result<Option<&str>> {
+        match self.props.get(key) {
+            None => Ok(None),
+            Some(Value::String(s)) => Ok(Some(s)),
+            Some(_) => Err(self.error(format!("`{}` must be a string", key))),
+        }
+    }
+
+    fn int(&self, key: &str) -> Result<Option<i32>> {
+        match self.props.get(key) {
+            None => Ok(None),
+            Some(value) => {
+                let value = value
+                    .as_i64()
+                    .ok_or_else(|| self.error(format!("`{}` must be an integer", key)))?;
+                self.in_range(key, value).map(Some)
+            }
+        }
+    }
+
+    fn in_range<N: TryFrom<i64>>(&self, key: &str, value: i64) -> Result<N> {
+        N::try_from(value).map_err(|_| self.error(format!("`{}` is out of range: {}", key, value)))
+    }
+
+    fn bool(&self, key: &str) -> Result<Option<bool>> {
+        match self.props.get(key) {
+            None => Ok(None),
+            Some(value) => value
+                .as_bool()
+                .map(Some)
+                .ok_or_else(|| self.error(format!("`{}` must be true or false", key))),
+        }
+    }
+
+    fn strings(&self, key: &str) -> Result<Option<Vec<&str>>> {
+        match self.props.get(key) {
+            None => Ok(None),
+            Some(Value::Array(items)) => items
+                .iter()
+                .map(|item| item.as_str().ok_or_else(|| self.error(format!("`{}` must be a list of strings", key))))
+                .collect::<Result<_>>()
+                .map(Some),
+            Some(_) => Err(self.error(format!("`{}` must be a list of strings", key))),
+        }
+    }
+
+    fn dimension(&self, key: &str) -> Result<Option<Dimension>> {
+        match self.props.get(key) {
+            None => Ok(None),
+            Some(Value::String(s)) => s.parse().map(Some).map_err(|e| self.error(e)),
+            Some(value) => {
+                let value = value
+                    .as_i64()
+                    .ok_or_else(|| self.error(format!("`{}` must be a number or a size string", key)))?;
+                self.in_range::<i32>(key, value).map(|v| Some(Dimension::from(v)))
+            }
+        }
+    }
+
+    fn color(&self, key: &str) -> Result<Option<Color>> {
+        match self.props.get(key) {
+            None => Ok(None),
+            Some(Value::String(s)) => s.parse().map(Some).map_err(|e| self.error(e)),
+            Some(value) => {
+                let value = value
+                    .as_i64()
+                    .ok_or_else(|| self.error(format!("`{}` must be a colour string or ARGB number", key)))?;
+                self.in_range::<u32>(key, value).map(|v| Some(Color::from_argb_u32(v)))
+            }
+        }
+    }
+
+    fn visibility(&self) -> Result<Option<Visibility>> {
+        match self.str("visibility")? {
+            None => Ok(None),
+            Some("visible") => Ok(Some(Visibility::Visible)),
+            Some("invisible") => Ok(Some(Visibility::Invisible)),
+            Some("gone") => Ok(Some(Visibility::Gone)),
+            Some(other) => Err(self.error(format!("unknown visibility `{}`", other))),
+        }
+    }
+
+    /// Whether a LinearLayout is vertical, the default
+    fn vertical(&self) -> Result<bool> {
+        match self.str("orientation")? {
+            None | Some("vertical") => Ok(true),
+            Some("horizontal") => Ok(false),
+            Some(other) => Err(self.error(format!("unknown orientation `{}`", other))),
+        }
+    }
+
+    /// Check that the value of `key` has the right type
+    fn check_value(&self, key: &str) -> Result<()> {
+        match key {
+            "width" | "height" | "margin" | "padding" => self.dimension(key).map(drop),
+            "weight" | "text_size" | "rows" | "cols" | "progress" => self.int(key).map(drop),
+            "background" | "text_color" => self.color(key).map(drop),
+            "visibility" => self.visibility().map(drop),
+            "orientation" => self.vertical().map(drop),
+            "checked" | "single_line" => self.bool(key).map(drop),
+            "items" | "tabs" => self.strings(key).map(drop),
+            // Checked by `validate`
+            "grid" | "children" => Ok(()),
+            _ => self.str(key).map(drop),
+        }
+    }
+
+    /// Apply the properties every node may have
+    fn common<'a, 'h, W: Create>(
+        &self,
+        builder: WidgetBuilder<'a, 'h, W>,
+        parent: Option<i64>,
+    ) -> Result<WidgetBuilder<'a, 'h, W>> {
+        let mut builder = builder;
+        if let Some(parent) = parent {
+            builder = builder.parent_id(parent);
+        }
+        if let Some(visibility) = self.visibility()? {
+            builder = builder.visibility(visibility);
+        }
+        if let Some(width) = self.dimension("width")? {
+            builder = builder.width(width);
+        }
+        if let Some(height) = self.dimension("height")? {
+            builder = builder.height(height);
+        }
+        if let Some(margin) = self.dimension("margin")? {
+            builder = builder.margin(margin);
+        }
+        if let Some(padding) = self.dimension("padding")? {
+            builder = builder.padding(padding);
+        }
+        if let Some(weight) = self.int("weight")? {
+            builder = builder.weight(weight);
+        }
+        if let Some(color) = self.color("background")? {
+            builder = builder.background_color(color);
+        }
+        Ok(builder)
+    }
+
+    /// Apply the properties of text widgets
+    fn text<'a, 'h, W: Create + TextWidget>(
+        &self,
+        builder: WidgetBuilder<'a, 'h, W>,
+        parent: Option<i64>,
+    ) -> Result<WidgetBuilder<'a, 'h, W>> {
+        let mut builder = self.common(builder, parent)?;
+        if let Some(size) = self.int("text_size")? {
+            builder = builder.text_size(size);
+        }
+        if let Some(color) = self.color("text_color")? {
+            builder = builder.text_color(color);
+        }
+        Ok(builder)
+    }
+}
+
+/// Properties a component takes besides [`COMMON_KEYS`], and whether it
+/// can have children; `None` for unknown types
+fn component(ty: &str) -> Option<(&'static [&'static str], bool)> {
+    const CHECKABLE: &[&str] = &["text", "text_size", "text_color", "checked"];
+    Some(match ty {
+        "LinearLayout" => (&["orientation"], true),
+        "GridLayout" => (&["rows", "cols"], true),
+        "FrameLayout" | "NestedScrollView" | "HorizontalScrollView" | "SwipeRefreshLayout"
+        | "RadioGroup" => (&[], true),
+        "TextView" | "Button" => (TEXT_KEYS, false),
+        "EditText" => (
+            &["text", "text_size", "text_color", "hint", "input_type", "single_line"],
+            false,
+        ),
+        "Checkbox" | "Switch" | "RadioButton" | "ToggleButton" => (CHECKABLE, false),
+        "Spinner" => (&["items"], false),
+        "TabLayout" => (&["tabs"], false),
+        "ProgressBar" => (&["progress"], false),
+        "ImageView" | "Space" | "WebView" => (&[], false),
+        _ => return None,
+    })
+}
+
+/// Check the node at `node` and its children without creating anything
+fn validate(node: &Value, path: &str, names: &mut HashSet<String>) -> Result<()> {
+    let node = NodeReader::new(node, path)?;
+    let ty = node.str("type")?.ok_or_else(|| node.error("missing `type`"))?;
+    let (extra, container) = component(ty).ok_or_else(|| node.error(format!("unknown type `{}`", ty)))?;
+    node.check_keys(extra)?;
+    for key in node.props.keys() {
+        node.check_value(key)?;
+    }
+    if ty == "GridLayout" {
+        node.int("rows")?.ok_or_else(|| node.error("missing `rows`"))?;
+        node.int("cols")?.ok_or_else(|| node.error("missing `cols`"))?;
+    }
+    if let Some(grid) = node.props.get("grid") {
+        GridCell::read(grid, path)?;
+    }
+    if let Some(name) = node.str("name")? {
+        if !names.insert(name.to_string()) {
+            return Err(node.error(format!("duplicate name `{}`", name)));
+        }
+    }
+
+    match node.props.get("children") {
+        None => Ok(()),
+        Some(Value::Array(children)) if container => {
+            for (i, child) in children.iter().enumerate() {
+                validate(child, &format!("{}.children[{}]", path, i), names)?;
+            }
+            Ok(())
+        }
+        Some(Value::Array(_)) => Err(node.error(format!("{} can't have children", ty))),
+        Some(_) => Err(node.error("`children` must be a list")),
+    }
+}
+
+/// Create the widget at `node` and its children, returning its view ID
+///
+/// `node` must have passed [`validate`].
+fn create(
+    activity: &mut Activity,
+    node: &Value,
+    parent: Option<i64>,
+    path: &str,
+    widgets: &mut HashMap<String, Box<dyn Widget>>,
+) -> Result<i64> {
+    let node = NodeReader::new(node, path)?;
+    let ty = node.str("type")?.ok_or_else(|| node.error("missing `type`"))?;
+
+    let text = node.str("text")?.unwrap_or("");
+    let checked = node.bool("checked")?.unwrap_or(false);
+    let widget: Box<dyn Widget> = match ty {
+        "LinearLayout" => {
+            let vertical = node.vertical()?;
+            Box::new(node.common(activity.linear_layout(), parent)?.vertical(vertical).build()?)
+        }
+        "GridLayout" => {
+            let rows = node.int("rows")?.ok_or_else(|| node.error("missing `rows`"))?;
+            let cols = node.int("cols")?.ok_or_else(|| node.error("missing `cols`"))?;
+            let grid: GridLayout = node.common(activity.grid_layout(rows, cols), parent)?.build()?;
+            Box::new(grid)
+        }
+        "FrameLayout" => Box::new(node.common(activity.frame_layout(), parent)?.build()?),
+        "NestedScrollView" => Box::new(node.common(activity.nested_scroll_view(), parent)?.build()?),
+        "HorizontalScrollView" => Box::new(node.common(activity.horizontal_scroll_view(), parent)?.build()?),
+        "SwipeRefreshLayout" => Box::new(node.common(activity.swipe_refresh_layout(), parent)?.build()?),
+        "RadioGroup" => Box::new(node.common(activity.radio_group(), parent)?.build()?),
+        "TextView" => Box::new(node.text(activity.text_view(text), parent)?.build()?),
+        "Button" => Box::new(node.text(activity.button(text), parent)?.build()?),
+        "EditText" => {
+            let mut builder = node.text(activity.edit_text(text), parent)?;
+            if let Some(hint) = node.str("hint")? {
+                builder = builder.hint(hint);
+            }
+            if let Some(input_type) = node.str("input_type")? {
+                builder = builder.input_type(input_type);
+            }
+            if let Some(single_line) = node.bool("single_line")? {
+                builder = builder.single_line(single_line);
+            }
+            Box::new(builder.build()?)
+        }
+        "Checkbox" => Box::new(node.text(activity.checkbox(text), parent)?.checked(checked).build()?),
+        "Switch" => Box::new(node.text(activity.switch(text), parent)?.checked(checked).build()?),
+        "RadioButton" => Box::new(node.text(activity.radio_button(text), parent)?.checked(checked).build()?),
+        "ToggleButton" => Box::new(node.text(activity.toggle_button(text), parent)?.checked(checked).build()?),
+        "Spinner" => {
+            let spinner: Spinner = node.common(activity.spinner(), parent)?.build()?;
+            if let Some(items) = node.strings("items")? {
+                spinner.set_list(activity, &items)?;
+            }
+            Box::new(spinner)
+        }
+        "TabLayout" => {
+            let tabs: TabLayout = node.common(activity.tab_layout(), parent)?.build()?;
+            if let Some(names) = node.strings("tabs")? {
+                tabs.set_list(activity, &names)?;
+            }
+            Box::new(tabs)
+        }
+        "ProgressBar" => {
+            let bar: ProgressBar = node.common(activity.progress_bar(), parent)?.build()?;
+            if let Some(progress) = node.int("progress")? {
+                bar.set_progress(activity, progress)?;
+            }
+            Box::new(bar)
+        }
+        "ImageView" => Box::new(node.common(activity.image_view(), parent)?.build()?),
+        "Space" => Box::new(node.common(activity.space(), parent)?.build()?),
+        "WebView" => Box::new(node.common(activity.web_view(), parent)?.build()?),
+        other => return Err(node.error(format!("unknown type `{}`", other))),
+    };
+
+    let id = widget.id();
+    if let Some(grid) = node.props.get("grid") {
+        GridCell::read(grid, path)?.apply(activity, widget.view())?;
+    }
+    if let Some(name) = node.str("name")? {
+        widgets.insert(name.to_string(), widget);
+    }
+
+    if let Some(Value::Array(children)) = node.props.get("children") {
+        for (i, child) in children.iter().enumerate() {
+            let child_path = format!("{}.children[{}]", path, i);
+            create(activity, child, Some(id), &child_path, widgets)?;
+        }
+    }
+    Ok(id)
+}
+
+/// Position of a view inside its GridLayout
+struct GridCell {
+    row: i32,
+    col: i32,
+    row_size: i32,
+    col_size: i32,
+    align_row: String,
+    align_col: String,
+}
+
+impl GridCell {
+    /// Read the `grid` property of the node at `path`
+    fn read(grid: &Value, path: &str) -> Result<Self> {
+        let grid_path = format!("{}.grid", path);
+        let grid = NodeReader::new(grid, &grid_path)?;
+        for key in grid.props.keys() {
+            if !["row", "col", "row_size", "col_size", "align_row", "align_col"].contains(&key.as_str()) {
+                return Err(grid.error(format!("unknown property `{}`", key)));
+            }
+        }
+
+        Ok(GridCell {
+            row: grid.int("row")?.unwrap_or(0),
+            col: grid.int("col")?.unwrap_or(0),
+            row_size: grid.int("row_size")?.unwrap_or(1),
+            col_size: grid.int("col_size")?.unwrap_or(1),
+            align_row: grid.str("align_row")?.unwrap_or("center").to_string(),
+            align_col: grid.str("align_col")?.unwrap_or("center").to_string(),
+        })
+    }
+
+    fn apply(&self, activity: &mut Activity, view: &View) -> Result<()> {
+        view.set_grid_layout_params(
+            activity,
+            self.row,
+            self.col,
+            self.row_size,
+            self.col_size,
+            &self.align_row,
+            &self.align_col,
+        )
+    }
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::components::{Button, EditText, LinearLayout};
+    use crate::testing::MockService;
+
+    const FORM: &str = r##"{
+        "type": "LinearLayout",
+        "name": "form",
+        "orientation": "horizontal",
+        "children": [
+            {"type": "EditText", "name": "user", "hint": "User", "single_line": true, "weight": 1},
+            {"type": "Button", "name": "ok", "text": "OK", "margin": "4dp", "background": "#2196F3"},
+            {"type": "Spinner", "items": ["a", "b"]}
+        ]
+    }"##;
+
+    fn layout_error(result: Result<LoadedLayout>) -> String {
+        match result {
+            Err(GuiError::InvalidLayout(message)) => message,
+            other => panic!("expected InvalidLayout, got {:?}", other.map(|l| l.root_id())),
+        }
+    }
+
+    #[test]
+    fn creates_the_tree_and_names_widgets() -> Result<()> {
+        let (mock, mut activity) = MockService::activity(false)?;
+        let layout = LoadedLayout::from_json(&mut activity, FORM, None)?;
+
+        let root = layout.root_id();
+        assert_eq!(layout.id("form"), Some(root));
+        assert!(layout.get::<LinearLayout>("form").is_some());
+        assert!(layout.get::<EditText>("user").is_some());
+        assert!(layout.get::<EditText>("ok").is_none());
+        let ok: &Button = layout.get("ok").expect("ok button");
+        let mut names: Vec<_> = layout.names().collect();
+        names.sort();
+        assert_eq!(names, ["form", "ok", "user"]);
+
+        assert_eq!(mock.calls_to("createLinearLayout")[0]["vertical"], json!(false));
+        let user = &mock.calls_to("createEditText")[0];
+        assert_eq!((user["parent"].clone(), user["singleline"].clone()), (json!(root), json!(true)));
+        assert_eq!(mock.calls_to("setHint")[0]["hint"], "User");
+        assert_eq!(mock.calls_to("setLinearLayoutParams")[0]["weight"], json!(1));
+        let margin = &mock.calls_to("setMargin")[0];
+        assert_eq!((margin["id"].clone(), margin["margin"].clone()), (json!(ok.id()), json!(4)));
+        let blue = "#2196F3".parse::<Color>().unwrap().to_i32();
+        assert_eq!(mock.calls_to("setBackgroundColor")[0]["color"], json!(blue));
+        assert_eq!(mock.calls_to("setList")[0]["list"], json!(["a", "b"]));
+        Ok(())
+    }
+
+    #[test]
+    fn grid_positions_use_defaults() -> Result<()> {
+        let (mock, mut activity) = MockService::activity(false)?;
+        let grid = json!({
+            "type": "GridLayout", "rows": 2, "cols": 2,
+            "children": [{"type": "Space", "grid": {"row": 1, "col_size": 2}}]
+        });
+        LoadedLayout::from_value(&mut activity, &grid, None)?;
+
+        let create = &mock.calls_to("createGridLayout")[0];
+        assert_eq!((create["rows"].clone(), create["cols"].clone()), (json!(2), json!(2)));
+        let params = &mock.calls_to("setGridLayoutParams")[0];
+        assert_eq!(params["row"], json!(1));
+        assert_eq!(params["col"], json!(0));
+        assert_eq!(params["colsize"], json!(2));
+        assert_eq!(params["alignmentrow"], "center");
+        Ok(())
+    }
+
+    #[test]
+    fn errors_name_the_offending_node() -> Result<()> {
+        let (_mock, mut activity) = MockService::activity(false)?;
+        let mut load = |value: Value| layout_error(LoadedLayout::from_value(&mut activity, &value, None));
+
+        let message = load(json!({"type": "LinearLayout", "children": [{"type": "Button", "colour": "red"}]}));
+        assert_eq!(message, "root.children[0]: unknown property `colour`");
+        assert_eq!(load(json!({"text": "no type"})), "root: missing `type`");
+        assert_eq!(load(json!({"type": "Slider"})), "root: unknown type `Slider`");
+        assert_eq!(load(json!({"type": "Button", "children": []})), "root: Button can't have children");
+        assert!(load(json!({"type": "Space", "width": "wide"})).starts_with("root: "));
+        assert_eq!(load(json!({"type": "GridLayout", "rows": 1})), "root: missing `cols`");
+        assert_eq!(load(json!({"type": "Space", "grid": {"span": 2}})), "root.grid: unknown property `span`");
+
+        let duplicate = json!({"type": "FrameLayout", "children": [
+            {"type": "Space", "name": "gap"}, {"type": "Space", "name": "gap"}
+        ]});
+        assert_eq!(load(duplicate), "root.children[1]: duplicate name `gap`");
+        Ok(())
+    }
+
+    #[test]
+    fn nothing_is_created_for_an_invalid_file() -> Result<()> {
+        let (mock, mut activity) = MockService::activity(false)?;
+        mock.clear_calls();
+        let late_typo = json!({"type": "LinearLayout", "children": [
+            {"type": "TextView", "name": "a"},
+            {"type": "FrameLayout", "children": [{"type": "Button", "txt": "OK"}]}
+        ]});
+        let duplicate = json!({"type": "LinearLayout", "children": [
+            {"type": "TextView", "name": "a"}, {"type": "Button", "name": "a"}
+        ]});
+
+        let message = layout_error(LoadedLayout::from_value(&mut activity, &late_typo, None));
+        assert_eq!(message, "root.children[1].children[0]: unknown property `txt`");
+        assert!(LoadedLayout::from_value(&mut activity, &duplicate, None).is_err());
+        assert!(mock.calls().is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn out_of_range_numbers_are_rejected() -> Result<()> {
+        let (_mock, mut activity) = MockService::activity(false)?;
+        let mut load = |value: Value| layout_error(LoadedLayout::from_value(&mut activity, &value, None));
+
+        assert_eq!(
+            load(json!({"type": "TextView", "text_size": 4_294_967_312_i64})),
+            "root: `text_size` is out of range: 4294967312"
+        );
+        assert!(load(json!({"type": "Space", "margin": -3_000_000_000_i64})).contains("`margin` is out of range"));
+        assert!(load(json!({"type": "Space", "background": 4_294_967_296_i64})).contains("`background` is out of range"));
+        assert!(load(json!({"type": "Space", "background": -1})).contains("`background` is out of range"));
+        assert!(load(json!({"type": "Space", "grid": {"row": 1_i64 << 40}})).starts_with("root.grid: `row`"));
+        Ok(())
+    }
+
+    #[test]
+    fn load_picks_the_format_by_extension() -> Result<()> {
+        let (_mock, mut activity) = MockService::activity(false)?;
+        let dir = std::env::temp_dir().join(format!("termux-gui-layout-{}", std::process::id()));
+        std::fs::create_dir_all(&dir)?;
+        let json_path = dir.join("form.json");
+        std::fs::write(&json_path, FORM)?;
+        let xml_path = dir.join("form.xml");
+        std::fs::write(&xml_path, "<LinearLayout/>")?;
+
+        let loaded = LoadedLayout::load(&mut activity, &json_path, None).map(|l| l.names().count());
+        let unsupported = layout_error(LoadedLayout::load(&mut activity, &xml_path, None));
+        let missing = LoadedLayout::load(&mut activity, dir.join("missing.json"), None);
+        std::fs::remove_dir_all(&dir)?;
+
+        assert_eq!(loaded?, 3);
+        assert!(unsupported.starts_with("unsupported layout file"));
+        assert!(matches!(missing, Err(GuiError::Io(_))));
+        Ok(())
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn toml_layouts() -> Result<()> {
+        let (mock, mut activity) = MockService::activity(false)?;
+        let toml = r#"
+            type = "LinearLayout"
+
+            [[children]]
+            type = "TextView"
+            name = "title"
+            text = "Hello"
+            text_size = 20
+        "#;
+        let layout = LoadedLayout::from_toml(&mut activity, toml, None)?;
+
+        assert!(layout.id("title").is_some());
+        assert_eq!(mock.calls_to("createTextView")[0]["text"], "Hello");
+        assert_eq!(mock.calls_to("setTextSize")[0]["size"], json!(20));
+        Ok(())
+    }
+}
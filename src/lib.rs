@@ -6,7 +6,7 @@
 //! ## Quick Start
 //!
 //! ```rust,no_run
-//! use termux_gui::{Activity, Result};
+//! use termux_gui::{Activity, Event, Result};
 //!
 //! fn main() -> Result<()> {
 //!     // Create an activity (dialog mode)
@@ -16,36 +16,47 @@
 //!     let layout = activity.create_linear_layout(None)?;
 //!     
 //!     // Add a title
-//!     let mut title = activity.create_text_view("Hello Termux!", Some(layout.id()))?;
+//!     let title = activity.create_text_view("Hello Termux!", Some(layout.id()))?;
 //!     title.set_text_size(&mut activity, 24)?;
 //!     
 //!     // Add a button
 //!     let button = activity.create_button("Click Me", Some(layout.id()))?;
 //!     
 //!     // Event loop
-//!     use std::io::Read;
-//!     let mut buf = [0u8; 1024];
 //!     loop {
-//!         let n = activity.event_stream().read(&mut buf)?;
-//!         if n > 0 {
-//!             let event: serde_json::Value = serde_json::from_slice(&buf[..n])?;
-//!             if event["type"] == "UserLeave" {
-//!                 break;
+//!         match activity.next_event()? {
+//!             Event::Click { id, .. } if id == button.id() => {
+//!                 title.set_text(&mut activity, "Clicked!")?;
 //!             }
+//!             Event::Destroy { .. } => return Ok(()),
+//!             _ => {}
 //!         }
 //!     }
-//!     
-//!     activity.finish()?;
-//!     Ok(())
 //! }
 //! ```
 //!
 //! ## Architecture
 //!
 //! - **Connection**: Low-level socket communication with Termux GUI service
+//! - **Protocol**: Table of methods, their parameters and which ones reply
+//! - **Raw**: A typed function for every protocol method, generated from
+//!   the same protocol description as the table
+//! - **Connector**: Pluggable ways to establish a connection (`am broadcast`,
+//!   existing sockets, test harnesses)
+//! - **Gui**: A session sharing one connection between several activities
 //! - **Activity**: Represents a GUI window (dialog or full-screen)
 //! - **View**: Base view type with common operations
 //! - **Components**: UI widgets (TextView, Button, EditText, etc.)
+//! - **Widget**: Traits shared by all components, for generic code
+//! - **Builder**: Create a widget with all its options in one expression
+//! - **Batch**: Queue many create/set calls and send them without waiting
+//! - **Declarative**: Describe the UI as a tree and update it by diffing
+//! - **Layout files**: Create screens described in JSON or TOML files
+//! - **Binding**: Observable state that updates bound widgets when it changes
+//! - **Event**: Typed events decoded from the event socket
+//! - **EventLoop**: Routes events to closures registered per view
+//! - **Lifecycle**: Tracks Activity state and saves widget state across recreation
+//! - **Record**: Capture protocol traffic to a file and replay it without a phone
 //!
 //! ## Features
 //!
@@ -53,18 +64,67 @@
 //! - Type-safe error handling with `thiserror`
 //! - Zero-cost abstractions using lifetimes
 //! - All Termux GUI components supported
+//!
+//! ## Cargo Features
+//!
+//! - `testing`: In-process mock service ([`testing::MockService`]) for
+//!   testing GUI code without a phone
+//! - `tracing`: Protocol tracing through the [`tracing`](https://docs.rs/tracing)
+//!   crate. Every request gets a `termux_gui::call` span with the method
+//!   name, Activity ID, view ID and latency; nothing is printed otherwise
+//! - `async`: Async connection and event stream over tokio
+//!   ([`asynchronous::AsyncConnection`]); the blocking API stays available
+//! - `toml`: TOML layout files in [`layout_file`], next to JSON
 
 pub mod connection;
+pub mod protocol;
+pub mod raw;
+pub mod transport;
 pub mod activity;
+pub mod gui;
 pub mod view;
+pub mod widget;
+pub mod builder;
+pub mod batch;
+pub mod declarative;
+pub mod layout_file;
+pub mod binding;
 pub mod components;
 pub mod error;
+pub mod event;
+pub mod event_loop;
+pub mod lifecycle;
+pub mod theme;
+pub mod color;
+pub mod dimension;
+pub mod record;
+mod trace;
+
+#[cfg(feature = "testing")]
+pub mod testing;
+
+#[cfg(feature = "async")]
+pub mod asynchronous;
 
 // Re-exports for convenience
 pub use connection::Connection;
-pub use activity::Activity;
-pub use view::{View, MATCH_PARENT, WRAP_CONTENT};
-pub use error::{GuiError, Result};
+pub use transport::Connector;
+pub use activity::{
+    Activity, ActivityBuilder, ActivityHandle, Configuration, Orientation, SoftInputMode,
+};
+pub use gui::Gui;
+pub use view::{Side, View, Visibility, MATCH_PARENT, WRAP_CONTENT};
+pub use widget::{CheckableWidget, TextWidget, Widget};
+pub use builder::WidgetBuilder;
+pub use batch::{Batch, BatchResult, Pending};
+pub use binding::{Signal, Subscription};
+pub use error::{CallContext, GuiError, Result};
+pub use event::Event;
+pub use event_loop::{EventContext, EventLoop};
+pub use lifecycle::{ActivityState, SavedState};
+pub use theme::Theme;
+pub use color::Color;
+pub use dimension::{Dimension, DisplayMetrics};
 
 // Re-export all components
 pub use components::{
@@ -0,0 +1,236 @@
+//! Activity lifecycle tracking
+//!
+//! The service reports lifecycle changes of every Activity as events
+//! (`create`, `start`, `resume`, `pause`, `stop`, `destroy`). Every event
+//! read through a [`Gui`](crate::Gui) updates the [`ActivityState`] of its
+//! Activity, which [`Activity::state`](crate::Activity::state) returns.
+//! Once an Activity is finally destroyed, requests for it fail with
+//! `GuiError::ActivityDestroyed` instead of reaching the service.
+//!
+//! When the system destroys an Activity without finishing it (e.g. to
+//! reclaim memory) and recreates it later, its widget state can be carried
+//! over with [`EventLoop::on_save_state`](crate::EventLoop::on_save_state)
+//! and [`EventLoop::on_restore_state`](crate::EventLoop::on_restore_state).
+//!
+//! ## Example
+//!
+//! ```rust,no_run
+//! use termux_gui::{Activity, ActivityState, EventLoop, Result};
+//!
+//! fn main() -> Result<()> {
+//!     let mut activity = Activity::new(false)?;
+//!     let input = activity.create_edit_text("", None)?;
+//!
+//!     let mut events = EventLoop::new();
+//!     events.on_lifecycle(|_ctx, state| {
+//!         if state == ActivityState::Paused {
+//!             println!("paused, stopping animations");
+//!         }
+//!         Ok(())
+//!     });
+//!     events.on_save_state(|ctx, saved| {
+//!         saved.put("input", input.get_text(ctx.activity)?);
+//!         Ok(())
+//!     });
+//!     events.on_restore_state(|ctx, saved| {
+//!         if let Some(text) = saved.get_str("input") {
+//!             input.set_text(ctx.activity, text)?;
+//!         }
+//!         Ok(())
+//!     });
+//!     events.run(&mut activity)
+//! }
+//! ```
+
+use serde_json::{Map, Value};
+
+use crate::event::Event;
+
+/// Lifecycle state of an Activity
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ActivityState {
+    /// Created but not yet visible
+    Created,
+    /// Visible but not in the foreground
+    Started,
+    /// In the foreground and receiving input
+    Resumed,
+    /// Partially hidden, e.g. behind a dialog or in picture-in-picture mode
+    Paused,
+    /// No longer visible
+    Stopped,
+    /// Destroyed by the system without finishing; it will be recreated
+    Recreating,
+    /// Finished and destroyed; requests for it fail
+    Destroyed,
+}
+
+impl ActivityState {
+    /// Get the state a lifecycle event moves its Activity to
+    ///
+    /// Returns `None` for events that aren't lifecycle changes.
+    pub fn from_event(event: &Event) -> Option<Self> {
+        match event {
+            Event::Create { .. } => Some(ActivityState::Created),
+            Event::Start { .. } => Some(ActivityState::Started),
+            Event::Resume { .. } => Some(ActivityState::Resumed),
+            Event::Pause { .. } => Some(ActivityState::Paused),
+            Event::Stop { .. } => Some(ActivityState::Stopped),
+            Event::Destroy { finishing: true, .. } => Some(ActivityState::Destroyed),
+            Event::Destroy { finishing: false, .. } => Some(ActivityState::Recreating),
+            _ => None,
+        }
+    }
+
+    /// Check whether the Activity is on screen
+    pub fn is_visible(self) -> bool {
+        matches!(self, ActivityState::Started | ActivityState::Resumed | ActivityState::Paused)
+    }
+
+    /// Check whether the Activity is gone for good
+    pub fn is_destroyed(self) -> bool {
+        self == ActivityState::Destroyed
+    }
+}
+
+/// Widget state saved before the system destroys an Activity
+///
+/// A key-value store of JSON values filled by save hooks and handed to
+/// restore hooks once the Activity is recreated.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SavedState {
+    values: Map<String, Value>,
+}
+
+impl SavedState {
+    /// Create an empty saved state
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Store a value under `key`, replacing any previous one
+    pub fn put(&mut self, key: impl Into<String>, value: impl Into<Value>) {
+        self.values.insert(key.into(), value.into());
+    }
+
+    /// Get the value stored under `key`
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.values.get(key)
+    }
+
+    /// Get the string stored under `key`
+    pub fn get_str(&self, key: &str) -> Option<&str> {
+        self.get(key).and_then(Value::as_str)
+    }
+
+    /// Get the integer stored under `key`
+    pub fn get_i64(&self, key: &str) -> Option<i64> {
+        self.get(key).and_then(Value::as_i64)
+    }
+
+    /// Get the boolean stored under `key`
+    pub fn get_bool(&self, key: &str) -> Option<bool> {
+        self.get(key).and_then(Value::as_bool)
+    }
+
+    /// Check whether nothing has been saved
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lifecycle_events_map_to_states() {
+        let resume = Event::Resume { aid: 1 };
+        assert_eq!(ActivityState::from_event(&resume), Some(ActivityState::Resumed));
+
+        let recreate = Event::Destroy { aid: 1, finishing: false };
+        assert_eq!(ActivityState::from_event(&recreate), Some(ActivityState::Recreating));
+
+        let finish = Event::Destroy { aid: 1, finishing: true };
+        assert!(ActivityState::from_event(&finish).is_some_and(ActivityState::is_destroyed));
+
+        assert_eq!(ActivityState::from_event(&Event::ScreenOn), None);
+    }
+
+    #[test]
+    fn only_started_to_paused_is_visible() {
+        assert!(ActivityState::Paused.is_visible());
+        assert!(!ActivityState::Created.is_visible());
+        assert!(!ActivityState::Stopped.is_visible());
+    }
+
+    #[test]
+    fn saved_state_keeps_typed_values() {
+        let mut saved = SavedState::new();
+        assert!(saved.is_empty());
+
+        saved.put("text", "hi");
+        saved.put("count", 3);
+        saved.put("on", true);
+        assert_eq!(saved.get_str("text"), Some("hi"));
+        assert_eq!(saved.get_i64("count"), Some(3));
+        assert_eq!(saved.get_bool("on"), Some(true));
+        assert_eq!(saved.get_str("count"), None);
+    }
+
+    #[cfg(feature = "testing")]
+    #[test]
+    fn requests_fail_once_the_activity_is_destroyed() -> crate::error::Result<()> {
+        use serde_json::json;
+
+        use crate::error::GuiError;
+        use crate::testing::MockService;
+
+        let (mock, mut activity) = MockService::activity(false)?;
+        assert_eq!(activity.state(), ActivityState::Created);
+
+        mock.send_event(&json!({
+            "type": "destroy",
+            "value": {"aid": activity.id(), "finishing": true}
+        }))?;
+        activity.next_event()?;
+        assert_eq!(activity.state(), ActivityState::Destroyed);
+
+        let aid = activity.id();
+        let error = activity.create_text_view("late", None).err().unwrap();
+        assert!(matches!(error.root(), GuiError::ActivityDestroyed(id) if *id == aid));
+        let error = activity.finish().unwrap_err();
+        assert!(matches!(error.root(), GuiError::ActivityDestroyed(id) if *id == aid));
+        Ok(())
+    }
+
+    #[cfg(feature = "testing")]
+    #[test]
+    fn state_is_saved_on_stop_and_restored_after_recreation() -> crate::error::Result<()> {
+        use std::cell::RefCell;
+
+        use crate::event_loop::EventLoop;
+        use crate::testing::MockService;
+
+        let (_mock, mut activity) = MockService::activity(false)?;
+        let aid = activity.id();
+        let restored = RefCell::new(None);
+
+        let mut events = EventLoop::new();
+        events.on_save_state(|_, saved| {
+            saved.put("input", "draft");
+            Ok(())
+        });
+        events.on_restore_state(|_, saved| {
+            *restored.borrow_mut() = saved.get_str("input").map(str::to_string);
+            Ok(())
+        });
+
+        events.dispatch(&mut activity, &Event::Stop { aid, finishing: false })?;
+        events.dispatch(&mut activity, &Event::Destroy { aid, finishing: false })?;
+        assert_eq!(*restored.borrow(), None);
+        events.dispatch(&mut activity, &Event::Create { aid })?;
+        assert_eq!(restored.borrow().as_deref(), Some("draft"));
+        Ok(())
+    }
+}
@@ -0,0 +1,327 @@
+//! The Termux:GUI protocol table
+//!
+//! Every method of the protocol is listed here with its parameters and
+//! what the service answers. The table is generated at build time from
+//! `protocol/termux-gui.json`, the same description the typed
+//! [`raw`](crate::raw) bindings are generated from. The service only writes a reply for methods
+//! that return something, so reading after a method that doesn't (or not
+//! reading after one that does) leaves the main socket out of step: every
+//! later response would be matched with the wrong request.
+//!
+//! [`Connection`](crate::connection::Connection) looks up each request in
+//! this table. `send` on a method that returns a value, or `send_read` on
+//! one that doesn't, fails with [`GuiError::Protocol`] before anything is
+//! written. Methods that aren't in the table are passed through unchecked.
+//!
+//! Replies of the wrong shape fail with [`GuiError::InvalidResponse`]. In
+//! debug builds the connection also checks the parameters against the
+//! schema, and detects a desynchronised socket (unexpected unread data
+//! before a request), returning [`GuiError::Desynchronized`] instead of
+//! handing a stale reply to the caller.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use termux_gui::protocol::{self, Reply};
+//!
+//! let create = protocol::method("createButton").unwrap();
+//! assert_eq!(create.reply, Reply::Id);
+//! assert!(!protocol::method("setText").unwrap().returns());
+//! ```
+
+use serde_json::Value;
+use crate::error::{GuiError, Result};
+
+/// Type of a parameter value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParamType {
+    /// An integer, e.g. an ID or a colour
+    Int,
+    /// A boolean
+    Bool,
+    /// A string
+    Str,
+    /// An array of strings
+    StrList,
+    /// A size: an integer or `"MATCH_PARENT"`/`"WRAP_CONTENT"`
+    Size,
+}
+
+impl ParamType {
+    /// Check whether `value` has this type
+    pub fn matches(self, value: &Value) -> bool {
+        match self {
+            ParamType::Int => value.is_i64() || value.is_u64(),
+            ParamType::Bool => value.is_boolean(),
+            ParamType::Str => value.is_string(),
+            ParamType::StrList => value
+                .as_array()
+                .is_some_and(|items| items.iter().all(Value::is_string)),
+            ParamType::Size => {
+                value.is_i64() || matches!(value.as_str(), Some("MATCH_PARENT" | "WRAP_CONTENT"))
+            }
+        }
+    }
+}
+
+/// A parameter of a protocol method
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Param {
+    /// Name in the `params` object
+    pub name: &'static str,
+    /// Type of the value
+    pub ty: ParamType,
+    /// Whether the parameter must be present
+    pub required: bool,
+}
+
+/// What the service answers to a method
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reply {
+    /// Nothing; the request must be sent with `send`
+    None,
+    /// A view, layout or notification ID
+    Id,
+    /// An integer that isn't an ID, e.g. the plugin version
+    Int,
+    /// `[aid, tid]`, or just the aid when launched into an existing task
+    Activity,
+    /// A boolean
+    Bool,
+    /// A string
+    Str,
+    /// Two integers, e.g. `[width, height]`
+    IntPair,
+    /// A JSON object
+    Object,
+}
+
+impl Reply {
+    /// Check whether `value` has the shape of this reply
+    pub fn matches(self, value: &Value) -> bool {
+        let int_pair = |value: &Value| {
+            value
+                .as_array()
+                .is_some_and(|items| items.len() == 2 && items.iter().all(Value::is_i64))
+        };
+        match self {
+            Reply::None => false,
+            Reply::Id | Reply::Int => value.is_i64(),
+            Reply::Activity => value.is_i64() || int_pair(value),
+            Reply::Bool => value.is_boolean(),
+            Reply::Str => value.is_string(),
+            Reply::IntPair => int_pair(value),
+            Reply::Object => value.is_object(),
+        }
+    }
+}
+
+/// A protocol method
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Method {
+    /// Name sent in the `method` field
+    pub name: &'static str,
+    /// Accepted parameters
+    pub params: &'static [Param],
+    /// What the service answers
+    pub reply: Reply,
+}
+
+impl Method {
+    /// Check whether the service answers this method
+    pub fn returns(&self) -> bool {
+        self.reply != Reply::None
+    }
+
+    /// Check `params` against the schema
+    ///
+    /// Fails on missing required parameters, unknown parameters and values
+    /// of the wrong type.
+    pub fn check_params(&self, params: &Value) -> Result<()> {
+        let empty = serde_json::Map::new();
+        let given = match params {
+            Value::Object(map) => map,
+            Value::Null => &empty,
+            _ => return Err(self.error("params must be an object".to_string())),
+        };
+
+        for param in self.params {
+            match given.get(param.name) {
+                Some(value) if !param.ty.matches(value) => {
+                    return Err(self.error(format!(
+                        "{} must be {:?}, got {}",
+                        param.name, param.ty, value
+                    )));
+                }
+                None if param.required => {
+                    return Err(self.error(format!("missing parameter {}", param.name)));
+                }
+                _ => {}
+            }
+        }
+        if let Some(unknown) = given.keys().find(|key| self.params.iter().all(|p| p.name != *key)) {
+            return Err(self.error(format!("unknown parameter {}", unknown)));
+        }
+        Ok(())
+    }
+
+    fn error(&self, message: String) -> GuiError {
+        GuiError::Protocol(format!("{}: {}", self.name, message))
+    }
+}
+
+/// Look up a method by name
+pub fn method(name: &str) -> Option<&'static Method> {
+    METHODS.iter().find(|method| method.name == name)
+}
+
+/// Check that a request is sent the right way
+///
+/// `read` is whether the caller will wait for a reply. Returns the method,
+/// if it is known. In debug builds the parameters are checked too.
+pub(crate) fn check_request(msg: &Value, read: bool) -> Result<Option<&'static Method>> {
+    let Some(method) = msg["method"].as_str().and_then(method) else {
+        return Ok(None);
+    };
+
+    if method.returns() != read {
+        let (used, expected) = if read { ("send_read", "send") } else { ("send", "send_read") };
+        return Err(method.error(format!("sent with {}, must use {}", used, expected)));
+    }
+    #[cfg(debug_assertions)]
+    method.check_params(&msg["params"])?;
+    Ok(Some(method))
+}
+
+/// Check that a reply fits the request it is read for
+pub(crate) fn check_reply(method: Option<&'static Method>, reply: &Value) -> Result<()> {
+    match method {
+        Some(method) if !method.reply.matches(reply) => Err(GuiError::InvalidResponse {
+            method: method.name.to_string(),
+            payload: reply.clone(),
+        }),
+        _ => Ok(()),
+    }
+}
+
+// `METHODS`, generated by build.rs from `protocol/termux-gui.json`
+include!(concat!(env!("OUT_DIR"), "/methods.rs"));
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn message(error: GuiError) -> String {
+        match error {
+            GuiError::Protocol(message) => message,
+            other => panic!("expected a protocol error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn table_lookup() {
+        assert_eq!(method("createButton").unwrap().reply, Reply::Id);
+        assert_eq!(method("getText").unwrap().reply, Reply::Str);
+        assert!(!method("setText").unwrap().returns());
+        assert!(method("noSuchMethod").is_none());
+        let names: std::collections::HashSet<_> = METHODS.iter().map(|m| m.name).collect();
+        assert_eq!(names.len(), METHODS.len(), "duplicate methods in the table");
+    }
+
+    #[test]
+    fn param_types() {
+        assert!(ParamType::Int.matches(&json!(-3)));
+        assert!(!ParamType::Int.matches(&json!(1.5)));
+        assert!(ParamType::StrList.matches(&json!(["a", "b"])));
+        assert!(!ParamType::StrList.matches(&json!(["a", 1])));
+        assert!(ParamType::Size.matches(&json!(48)));
+        assert!(ParamType::Size.matches(&json!("WRAP_CONTENT")));
+        assert!(!ParamType::Size.matches(&json!("48dp")));
+    }
+
+    #[test]
+    fn reply_shapes() {
+        assert!(Reply::Activity.matches(&json!(3)));
+        assert!(Reply::Activity.matches(&json!([3, 7])));
+        assert!(Reply::IntPair.matches(&json!([320, 48])));
+        assert!(!Reply::IntPair.matches(&json!([320])));
+        assert!(!Reply::Id.matches(&json!("1")));
+        assert!(!Reply::None.matches(&Value::Null));
+    }
+
+    #[test]
+    fn check_params_accepts_valid_params() -> Result<()> {
+        let create = method("createButton").unwrap();
+        create.check_params(&json!({ "aid": 0, "text": "OK", "allcaps": false }))?;
+        create.check_params(&json!({ "aid": 0 }))?;
+        method("setWidth").unwrap().check_params(&json!({ "aid": 0, "id": 1, "width": "MATCH_PARENT" }))?;
+        Ok(())
+    }
+
+    #[test]
+    fn check_params_rejects_invalid_params() {
+        let set_text = method("setText").unwrap();
+        let missing = set_text.check_params(&json!({ "aid": 0, "id": 1 })).unwrap_err();
+        assert_eq!(message(missing), "setText: missing parameter text");
+        let wrong = set_text.check_params(&json!({ "aid": 0, "id": 1, "text": 5 })).unwrap_err();
+        assert_eq!(message(wrong), "setText: text must be Str, got 5");
+        let unknown = set_text.check_params(&json!({ "aid": 0, "id": 1, "text": "", "colour": 1 })).unwrap_err();
+        assert_eq!(message(unknown), "setText: unknown parameter colour");
+        let not_object = set_text.check_params(&json!([0, 1])).unwrap_err();
+        assert_eq!(message(not_object), "setText: params must be an object");
+        assert!(set_text.check_params(&Value::Null).is_err());
+    }
+
+    #[test]
+    fn check_request_matches_send_and_send_read() -> Result<()> {
+        let create = json!({ "method": "createButton", "params": { "aid": 0 } });
+        assert_eq!(check_request(&create, true)?.map(|m| m.name), Some("createButton"));
+        let error = check_request(&create, false).unwrap_err();
+        assert_eq!(message(error), "createButton: sent with send, must use send_read");
+
+        let set = json!({ "method": "setText", "params": { "aid": 0, "id": 1, "text": "" } });
+        assert!(check_request(&set, false)?.is_some());
+        assert!(check_request(&set, true).is_err());
+
+        // Unknown methods are passed through
+        let custom = json!({ "method": "customMethod", "params": { "anything": true } });
+        assert!(check_request(&custom, true)?.is_none());
+        assert!(check_request(&custom, false)?.is_none());
+        Ok(())
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    fn debug_builds_check_params() {
+        let bad = json!({ "method": "setText", "params": { "aid": 0, "id": 1 } });
+        assert!(matches!(check_request(&bad, false), Err(GuiError::Protocol(_))));
+    }
+
+    #[test]
+    fn replies_of_the_wrong_shape_are_invalid() {
+        let get_text = method("getText");
+        assert!(check_reply(get_text, &json!("hello")).is_ok());
+        assert!(matches!(
+            check_reply(get_text, &json!(3)),
+            Err(GuiError::InvalidResponse { method, payload }) if method == "getText" && payload == json!(3)
+        ));
+        assert!(check_reply(None, &json!(3)).is_ok());
+    }
+
+    #[cfg(feature = "testing")]
+    #[test]
+    fn connections_check_requests_before_writing() -> Result<()> {
+        use crate::testing::MockService;
+
+        let (mock, mut conn) = MockService::start()?;
+        let create = json!({ "method": "createButton", "params": { "aid": 0 } });
+        let error = conn.send(&create).unwrap_err();
+        assert!(matches!(error.root(), GuiError::Protocol(_)));
+        let set = json!({ "method": "setText", "params": { "aid": 0, "id": 1, "text": "" } });
+        assert!(conn.send_read(&set).is_err());
+        assert!(mock.calls().is_empty());
+        Ok(())
+    }
+}
@@ -0,0 +1,325 @@
+//! Typed bindings for every protocol method
+//!
+//! One function per method of the Termux:GUI protocol, taking the
+//! parameters as a struct and returning the decoded reply, so nothing needs
+//! a hand-written `json!` request. The functions, the parameter structs and
+//! the [protocol table](crate::protocol) are generated at build time from
+//! `protocol/termux-gui.json`; to support a new method, add it there. The
+//! components, [`View`](crate::View) and [`Activity`] send all their
+//! requests through these functions, so the description is the only place
+//! parameter names are spelled out.
+//!
+//! Functions are named after the method in snake case (`setText` is
+//! [`set_text`], taking [`SetText`]). Optional parameters are `Option`s that
+//! are left out of the request when `None`, so the structs are usually
+//! filled with `..Default::default()`. Requests go through any [`Client`]:
+//! a [`Connection`], a [`Gui`], an [`Activity`] or an [`ActivityHandle`].
+//! Nothing is added or checked beyond the protocol table; in particular the
+//! `aid` is whatever the caller passes.
+//!
+//! The shared memory buffer methods (`addBuffer`, `setBuffer`, `blitBuffer`,
+//! `deleteBuffer`) are not included, as the service hands out the buffer as
+//! a file descriptor on the main socket.
+//!
+//! ## Example
+//!
+//! ```rust,no_run
+//! use termux_gui::raw::{self, CreateTextView, SetGravity, Toast};
+//! use termux_gui::{Activity, Result};
+//!
+//! fn main() -> Result<()> {
+//!     let mut activity = Activity::new(true)?;
+//!     let aid = activity.id();
+//!
+//!     let id = raw::create_text_view(&mut activity, &CreateTextView {
+//!         aid,
+//!         text: Some("Centred".to_string()),
+//!         ..Default::default()
+//!     })?;
+//!     raw::set_gravity(&mut activity, &SetGravity { aid, id, horizontal: 0, vertical: 0 })?;
+//!     raw::toast(&mut activity, &Toast { text: "Hello".to_string(), long: None })?;
+//!     Ok(())
+//! }
+//! ```
+
+use serde::{Serialize, Serializer};
+use serde_json::{json, Value};
+
+use crate::activity::{Activity, ActivityHandle, Configuration};
+use crate::connection::Connection;
+use crate::error::{GuiError, Result};
+use crate::gui::Gui;
+use crate::protocol::{self, Method};
+
+/// Something requests can be sent through
+pub trait Client {
+    /// Send a request the service doesn't answer
+    fn send(&mut self, msg: &Value) -> Result<()>;
+
+    /// Send a request and read the reply
+    fn send_read(&mut self, msg: &Value) -> Result<Value>;
+}
+
+impl Client for Connection {
+    fn send(&mut self, msg: &Value) -> Result<()> {
+        Connection::send(self, msg)
+    }
+
+    fn send_read(&mut self, msg: &Value) -> Result<Value> {
+        Connection::send_read(self, msg)
+    }
+}
+
+impl Client for Gui {
+    fn send(&mut self, msg: &Value) -> Result<()> {
+        Gui::send(self, msg)
+    }
+
+    fn send_read(&mut self, msg: &Value) -> Result<Value> {
+        Gui::send_read(self, msg)
+    }
+}
+
+impl Client for Activity {
+    fn send(&mut self, msg: &Value) -> Result<()> {
+        Activity::send(self, msg)
+    }
+
+    fn send_read(&mut self, msg: &Value) -> Result<Value> {
+        Activity::send_read(self, msg)
+    }
+}
+
+impl Client for ActivityHandle {
+    fn send(&mut self, msg: &Value) -> Result<()> {
+        ActivityHandle::send(self, msg)
+    }
+
+    fn send_read(&mut self, msg: &Value) -> Result<Value> {
+        ActivityHandle::send_read(self, msg)
+    }
+}
+
+/// Parameters of a protocol method
+pub trait Request: Serialize {
+    /// Name sent in the `method` field
+    const METHOD: &'static str;
+
+    /// Decoded reply; `()` for methods the service doesn't answer
+    type Reply: FromReply;
+}
+
+/// A value decoded from the reply to a method
+pub trait FromReply: Sized {
+    /// Decode `reply`, the answer to `method`
+    fn from_reply(method: &str, reply: Value) -> Result<Self>;
+}
+
+/// Send a request and decode the reply
+///
+/// The reply is only read if the [protocol table](crate::protocol) says the
+/// method has one. Errors carry the method, Activity and view of the
+/// request, see [`GuiError::context`]. The generated functions all call
+/// this.
+pub fn call<C, R>(client: &mut C, params: &R) -> Result<R::Reply>
+where
+    C: Client + ?Sized,
+    R: Request,
+{
+    let msg = json!({
+        "method": R::METHOD,
+        "params": serde_json::to_value(params)?,
+    });
+
+    let reply = if protocol::method(R::METHOD).is_some_and(Method::returns) {
+        client.send_read(&msg)?
+    } else {
+        client.send(&msg)?;
+        Value::Null
+    };
+    R::Reply::from_reply(R::METHOD, reply).map_err(|e| e.in_call(&msg))
+}
+
+/// Value of a `width` or `height` parameter
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Size {
+    /// A size in dp, or in pixels if `px` is set
+    Value(i64),
+    /// As large as the parent
+    MatchParent,
+    /// Just large enough for the content
+    #[default]
+    WrapContent,
+}
+
+impl Serialize for Size {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        match self {
+            Size::Value(size) => serializer.serialize_i64(*size),
+            Size::MatchParent => serializer.serialize_str("MATCH_PARENT"),
+            Size::WrapContent => serializer.serialize_str("WRAP_CONTENT"),
+        }
+    }
+}
+
+/// Reply to `newActivity`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ActivityIds {
+    /// ID of the new Activity
+    pub aid: i64,
+    /// ID of its task; `None` when launched into an existing task
+    pub tid: Option<i64>,
+}
+
+fn invalid(method: &str, reply: &Value) -> GuiError {
+    GuiError::InvalidResponse {
+        method: method.to_string(),
+        payload: reply.clone(),
+    }
+}
+
+impl FromReply for () {
+    fn from_reply(_method: &str, _reply: Value) -> Result<Self> {
+        Ok(())
+    }
+}
+
+impl FromReply for i64 {
+    fn from_reply(method: &str, reply: Value) -> Result<Self> {
+        reply.as_i64().ok_or_else(|| invalid(method, &reply))
+    }
+}
+
+impl FromReply for bool {
+    fn from_reply(method: &str, reply: Value) -> Result<Self> {
+        reply.as_bool().ok_or_else(|| invalid(method, &reply))
+    }
+}
+
+impl FromReply for String {
+    fn from_reply(method: &str, reply: Value) -> Result<Self> {
+        match reply {
+            Value::String(text) => Ok(text),
+            _ => Err(invalid(method, &reply)),
+        }
+    }
+}
+
+impl FromReply for (i64, i64) {
+    fn from_reply(method: &str, reply: Value) -> Result<Self> {
+        match reply.as_array().map(Vec::as_slice) {
+            Some([a, b]) => a.as_i64().zip(b.as_i64()).ok_or_else(|| invalid(method, &reply)),
+            _ => Err(invalid(method, &reply)),
+        }
+    }
+}
+
+impl FromReply for Value {
+    fn from_reply(_method: &str, reply: Value) -> Result<Self> {
+        Ok(reply)
+    }
+}
+
+impl FromReply for ActivityIds {
+    fn from_reply(method: &str, reply: Value) -> Result<Self> {
+        let ids = match &reply {
+            Value::Array(ids) => ids.first().and_then(Value::as_i64).map(|aid| ActivityIds {
+                aid,
+                tid: ids.get(1).and_then(Value::as_i64),
+            }),
+            Value::Number(aid) => aid.as_i64().map(|aid| ActivityIds { aid, tid: None }),
+            _ => None,
+        };
+        ids.ok_or_else(|| invalid(method, &reply))
+    }
+}
+
+impl FromReply for Configuration {
+    fn from_reply(_method: &str, reply: Value) -> Result<Self> {
+        Configuration::from_json(&reply)
+    }
+}
+
+include!(concat!(env!("OUT_DIR"), "/raw.rs"));
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn optional_params_are_left_out() -> Result<()> {
+        let params = CreateEditText {
+            aid: 1,
+            selectable_text: Some(true),
+            r#type: Some("number".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            serde_json::to_value(&params)?,
+            json!({ "aid": 1, "selectableText": true, "type": "number" })
+        );
+        assert_eq!(serde_json::to_value(GetVersion {})?, json!({}));
+        Ok(())
+    }
+
+    #[test]
+    fn sizes_serialize_like_the_protocol() -> Result<()> {
+        let width = |width| serde_json::to_value(SetWidth { aid: 0, id: 1, width, px: None });
+        assert_eq!(width(Size::Value(48))?["width"], json!(48));
+        assert_eq!(width(Size::MatchParent)?["width"], json!("MATCH_PARENT"));
+        assert_eq!(width(Size::default())?["width"], json!("WRAP_CONTENT"));
+        Ok(())
+    }
+
+    #[test]
+    fn generated_requests_match_the_table() {
+        assert_eq!(<CreateButton as Request>::METHOD, "createButton");
+        assert_eq!(<LoadUri as Request>::METHOD, "loadURI");
+        let reply = |name| protocol::method(name).map(|method| method.reply);
+        assert_eq!(reply(<GetConfiguration as Request>::METHOD), Some(protocol::Reply::Object));
+        assert_eq!(reply(<SetText as Request>::METHOD), Some(protocol::Reply::None));
+    }
+
+    #[test]
+    fn replies_are_decoded() -> Result<()> {
+        assert_eq!(i64::from_reply("createButton", json!(7))?, 7);
+        assert_eq!(<(i64, i64)>::from_reply("getDimensions", json!([320, 48]))?, (320, 48));
+        assert_eq!(
+            ActivityIds::from_reply("newActivity", json!([2, 5]))?,
+            ActivityIds { aid: 2, tid: Some(5) }
+        );
+        assert_eq!(ActivityIds::from_reply("newActivity", json!(2))?, ActivityIds { aid: 2, tid: None });
+        assert_eq!(String::from_reply("getText", json!("hi"))?, "hi");
+        Ok(())
+    }
+
+    #[test]
+    fn malformed_replies_are_invalid_responses() {
+        let error = <(i64, i64)>::from_reply("getDimensions", json!([320])).unwrap_err();
+        assert!(matches!(
+            &error,
+            GuiError::InvalidResponse { method, payload } if method == "getDimensions" && *payload == json!([320])
+        ));
+        assert!(bool::from_reply("evaluateJS", json!("true")).is_err());
+        assert!(String::from_reply("getText", Value::Null).is_err());
+        assert!(ActivityIds::from_reply("newActivity", json!([])).is_err());
+    }
+
+    #[cfg(feature = "testing")]
+    #[test]
+    fn calls_read_only_methods_that_reply() -> Result<()> {
+        use crate::testing::MockService;
+
+        let (mock, mut activity) = MockService::activity(false)?;
+        let aid = activity.id();
+        let id = create_edit_text(&mut activity, &CreateEditText { aid, ..Default::default() })?;
+        set_text(&mut activity, &SetText { aid, id, text: "typed".to_string() })?;
+        assert_eq!(get_text(&mut activity, &GetText { aid, id })?, "typed");
+        mock.set_response("getDimensions", json!([100, 20]));
+        assert_eq!(get_dimensions(&mut activity, &GetDimensions { aid, id })?, (100, 20));
+
+        let sent = mock.calls_to("setText");
+        assert_eq!(sent, [json!({ "aid": aid, "id": id, "text": "typed" })]);
+        Ok(())
+    }
+}
@@ -0,0 +1,396 @@
+//! Protocol recording and replay
+//!
+//! A [`Recorder`] attached to a [`Connection`] writes every outgoing
+//! message, every response and every event to a JSON-lines file, one
+//! [`Entry`] per line:
+//!
+//! ```text
+//! {"time":0,"kind":"send_read","msg":{"method":"newActivity","params":{...}}}
+//! {"time":3,"kind":"response","msg":[0,1]}
+//! {"time":2045,"kind":"event","msg":{"type":"click","value":{...}}}
+//! ```
+//!
+//! A [`Replayer`] feeds such a file back as a fake service: it expects the
+//! client to send the recorded requests, answers with the recorded
+//! responses and emits the recorded events in their original order. This
+//! lets a bug captured on a phone be reproduced deterministically on any
+//! Linux machine.
+//!
+//! ## Example
+//!
+//! ```rust,no_run
+//! use termux_gui::{Activity, Connection, Result};
+//! use termux_gui::record::{Recorder, Replayer};
+//!
+//! fn build_ui(activity: &mut Activity) -> Result<()> {
+//!     let layout = activity.create_linear_layout(None)?;
+//!     activity.create_button("OK", Some(layout.id()))?;
+//!     activity.next_event()?;
+//!     Ok(())
+//! }
+//!
+//! # fn main() -> Result<()> {
+//! // On the phone: record a session
+//! let conn = Connection::new()?.with_recorder(Recorder::create("session.jsonl")?);
+//! build_ui(&mut Activity::with_connection(conn, false)?)?;
+//!
+//! // On CI: replay it
+//! let (replay, conn) = Replayer::open("session.jsonl")?.start()?;
+//! build_ui(&mut Activity::with_connection(conn, false)?)?;
+//! replay.join()?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::os::unix::net::UnixStream;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Instant;
+use serde_json::{json, Value};
+
+use crate::connection::{read_message, send_message, Connection};
+use crate::error::{GuiError, Result};
+
+/// What a recorded message was
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntryKind {
+    /// A request sent without waiting for a response
+    Send,
+    /// A request that expects a response
+    SendRead,
+    /// The response to the oldest unanswered `SendRead`
+    Response,
+    /// A message received on the event socket
+    Event,
+}
+
+impl EntryKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            EntryKind::Send => "send",
+            EntryKind::SendRead => "send_read",
+            EntryKind::Response => "response",
+            EntryKind::Event => "event",
+        }
+    }
+
+    fn parse(kind: &str) -> Option<Self> {
+        match kind {
+            "send" => Some(EntryKind::Send),
+            "send_read" => Some(EntryKind::SendRead),
+            "response" => Some(EntryKind::Response),
+            "event" => Some(EntryKind::Event),
+            _ => None,
+        }
+    }
+}
+
+/// One line of a recording
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entry {
+    /// Milliseconds since recording started
+    pub time: u64,
+    /// What the message was
+    pub kind: EntryKind,
+    /// The JSON message itself
+    pub msg: Value,
+}
+
+impl Entry {
+    /// Encode the entry as a single JSON line (without the newline)
+    pub fn to_json(&self) -> Value {
+        json!({
+            "time": self.time,
+            "kind": self.kind.as_str(),
+            "msg": self.msg
+        })
+    }
+
+    /// Decode an entry from its JSON form
+    pub fn from_json(value: &Value) -> Result<Self> {
+        let kind = value["kind"]
+            .as_str()
+            .and_then(EntryKind::parse)
+            .ok_or_else(|| {
+                io::Error::new(io::ErrorKind::InvalidData, format!("Invalid recording entry: {}", value))
+            })?;
+
+        Ok(Entry {
+            time: value["time"].as_u64().unwrap_or(0),
+            kind,
+            msg: value["msg"].clone(),
+        })
+    }
+}
+
+/// Read all entries of a recording
+pub fn read_log<P: AsRef<Path>>(path: P) -> Result<Vec<Entry>> {
+    let reader = BufReader::new(File::open(path)?);
+    let mut entries = Vec::new();
+
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        entries.push(Entry::from_json(&serde_json::from_str(&line)?)?);
+    }
+
+    Ok(entries)
+}
+
+struct RecorderInner {
+    out: Box<dyn Write + Send>,
+    start: Instant,
+}
+
+/// Writes protocol traffic to a JSON-lines sink
+///
+/// Cloning a `Recorder` shares the sink, so several connections can record
+/// into the same file. Write errors are ignored rather than breaking the
+/// app being recorded.
+#[derive(Clone)]
+pub struct Recorder {
+    inner: Arc<Mutex<RecorderInner>>,
+}
+
+impl Recorder {
+    /// Record into a new file, truncating it if it exists
+    pub fn create<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Ok(Self::new(BufWriter::new(File::create(path)?)))
+    }
+
+    /// Record into any writer
+    pub fn new<W: Write + Send + 'static>(out: W) -> Self {
+        Recorder {
+            inner: Arc::new(Mutex::new(RecorderInner {
+                out: Box::new(out),
+                start: Instant::now(),
+            })),
+        }
+    }
+
+    /// Append one message to the recording
+    pub fn record(&self, kind: EntryKind, msg: &Value) {
+        let mut inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        let entry = Entry {
+            time: inner.start.elapsed().as_millis() as u64,
+            kind,
+            msg: msg.clone(),
+        };
+        // Flush every line so a crash still leaves a usable recording
+        let _ = writeln!(inner.out, "{}", entry.to_json());
+        let _ = inner.out.flush();
+    }
+}
+
+/// Plays a recording back as a fake Termux GUI service
+pub struct Replayer {
+    entries: Vec<Entry>,
+    strict: bool,
+}
+
+impl Replayer {
+    /// Load a recording from a file
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Ok(Self::new(read_log(path)?))
+    }
+
+    /// Replay the given entries
+    ///
+    /// ```rust
+    /// use serde_json::json;
+    /// use termux_gui::record::{Entry, EntryKind, Replayer};
+    ///
+    /// # fn main() -> termux_gui::Result<()> {
+    /// let request = json!({"method": "getText", "params": {"aid": 0, "id": 1}});
+    /// let replay = Replayer::new(vec![
+    ///     Entry { time: 0, kind: EntryKind::SendRead, msg: request.clone() },
+    ///     Entry { time: 1, kind: EntryKind::Response, msg: json!("hello") },
+    /// ]);
+    ///
+    /// let (handle, mut conn) = replay.start()?;
+    /// assert_eq!(conn.send_read(&request)?, "hello");
+    /// handle.join()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn new(entries: Vec<Entry>) -> Self {
+        Replayer { entries, strict: true }
+    }
+
+    /// Set whether requests must match the recording exactly
+    ///
+    /// Strict by default. When disabled only the method names are compared.
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Start replaying on a background thread and return a connection to it
+    pub fn start(self) -> Result<(ReplayHandle, Connection)> {
+        let (client_main, service_main) = UnixStream::pair()?;
+        let (client_events, service_events) = UnixStream::pair()?;
+
+        let thread = thread::spawn(move || self.run(service_main, service_events));
+        let conn = Connection::from_streams(client_main, client_events);
+
+        Ok((ReplayHandle { thread }, conn))
+    }
+
+    fn run(self, mut main: UnixStream, mut events: UnixStream) -> Result<()> {
+        for (line, entry) in self.entries.iter().enumerate() {
+            match entry.kind {
+                EntryKind::Send | EntryKind::SendRead => {
+                    let msg = read_message(&mut main)?;
+                    if !self.matches(&entry.msg, &msg) {
+                        return Err(GuiError::InvalidOperation(format!(
+                            "Replay diverged at entry {}: expected {}, got {}",
+                            line + 1,
+                            entry.msg,
+                            msg
+                        )));
+                    }
+                }
+                EntryKind::Response => send_message(&mut main, &entry.msg)?,
+                EntryKind::Event => send_message(&mut events, &entry.msg)?,
+            }
+        }
+        Ok(())
+    }
+
+    fn matches(&self, expected: &Value, actual: &Value) -> bool {
+        if self.strict {
+            expected == actual
+        } else {
+            expected["method"] == actual["method"]
+        }
+    }
+}
+
+/// Handle to a running replay
+pub struct ReplayHandle {
+    thread: JoinHandle<Result<()>>,
+}
+
+impl ReplayHandle {
+    /// Wait for the replay to finish
+    ///
+    /// Fails if the client diverged from the recording or disconnected
+    /// before all of it was played back.
+    pub fn join(self) -> Result<()> {
+        self.thread
+            .join()
+            .map_err(|_| GuiError::InvalidOperation("Replay thread panicked".to_string()))?
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    /// In-memory sink that can be read back while the recorder holds it
+    #[derive(Clone, Default)]
+    struct Sink(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Sink {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl Sink {
+        fn entries(&self) -> Vec<Entry> {
+            let bytes = self.0.lock().unwrap().clone();
+            String::from_utf8(bytes)
+                .unwrap()
+                .lines()
+                .map(|line| Entry::from_json(&serde_json::from_str(line).unwrap()).unwrap())
+                .collect()
+        }
+    }
+
+    #[test]
+    fn entries_round_trip_through_json() {
+        let entry = Entry { time: 12, kind: EntryKind::SendRead, msg: json!({"method": "getText"}) };
+        assert_eq!(Entry::from_json(&entry.to_json()).unwrap(), entry);
+        assert!(Entry::from_json(&json!({"kind": "shout", "msg": {}})).is_err());
+    }
+
+    #[test]
+    fn recorder_writes_one_entry_per_message() {
+        let sink = Sink::default();
+        let recorder = Recorder::new(sink.clone());
+        recorder.record(EntryKind::SendRead, &json!({"method": "getText"}));
+        recorder.record(EntryKind::Response, &json!("hi"));
+
+        let kinds: Vec<_> = sink.entries().iter().map(|entry| entry.kind).collect();
+        assert_eq!(kinds, [EntryKind::SendRead, EntryKind::Response]);
+    }
+
+    #[test]
+    fn replay_detects_diverging_requests() -> Result<()> {
+        let replay = Replayer::new(vec![Entry {
+            time: 0,
+            kind: EntryKind::Send,
+            msg: json!({"method": "setText", "params": {"aid": 0, "id": 1, "text": "a"}}),
+        }]);
+        let (handle, mut conn) = replay.start()?;
+        conn.send(&json!({"method": "setText", "params": {"aid": 0, "id": 1, "text": "b"}}))?;
+
+        assert!(matches!(handle.join(), Err(GuiError::InvalidOperation(_))));
+        Ok(())
+    }
+
+    #[test]
+    fn lenient_replay_only_compares_methods() -> Result<()> {
+        let replay = Replayer::new(vec![Entry {
+            time: 0,
+            kind: EntryKind::Send,
+            msg: json!({"method": "setText", "params": {"aid": 0, "id": 1, "text": "a"}}),
+        }]);
+        let (handle, mut conn) = replay.strict(false).start()?;
+        conn.send(&json!({"method": "setText", "params": {"aid": 0, "id": 1, "text": "b"}}))?;
+        handle.join()
+    }
+
+    #[cfg(feature = "testing")]
+    #[test]
+    fn a_recorded_session_replays() -> Result<()> {
+        use crate::activity::Activity;
+        use crate::testing::MockService;
+
+        fn session(conn: Connection) -> Result<()> {
+            let mut activity = Activity::with_connection(conn, false)?;
+            let edit = activity.create_edit_text("hello", None)?;
+            edit.set_text(&mut activity, "bye")?;
+            assert_eq!(edit.get_text(&mut activity)?, "bye");
+            assert_eq!(activity.next_event()?.view_id(), Some(edit.id()));
+            Ok(())
+        }
+
+        let sink = Sink::default();
+        let (mock, conn) = MockService::start()?;
+        mock.send_event(&json!({"type": "click", "value": {"aid": 0, "id": 0}}))?;
+        session(conn.with_recorder(Recorder::new(sink.clone())))?;
+
+        let entries = sink.entries();
+        assert_eq!(entries[0].kind, EntryKind::SendRead);
+        assert_eq!(entries[0].msg["method"], "newActivity");
+        assert!(entries.iter().any(|entry| entry.kind == EntryKind::Event));
+
+        let (handle, conn) = Replayer::new(entries).start()?;
+        session(conn)?;
+        handle.join()
+    }
+}
@@ -0,0 +1,376 @@
+//! In-process fake Termux GUI service for tests
+//!
+//! Everything in this crate normally needs a phone running the Termux:GUI
+//! app. [`MockService`] speaks the same length-prefixed JSON protocol over a
+//! pair of local socket pairs instead, so widgets can be exercised on any
+//! Linux machine.
+//!
+//! The mock:
+//! - records every message it receives on the main socket
+//! - answers `newActivity` and `create*` calls with incrementing ids, and
+//!   every other method that returns a value according to the
+//!   [protocol table](crate::protocol)
+//! - remembers the text set on views, so `getText` returns it
+//! - lets tests override responses and inject events
+//!
+//! This module is only available with the `testing` feature.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use serde_json::json;
+//! use termux_gui::testing::MockService;
+//!
+//! # fn main() -> termux_gui::Result<()> {
+//! let (mock, mut activity) = MockService::activity(false)?;
+//!
+//! let layout = activity.create_linear_layout(None)?;
+//! let button = activity.create_button("OK", Some(layout.id()))?;
+//! button.set_text(&mut activity, "Cancel")?;
+//!
+//! let created = mock.calls_to("createButton");
+//! assert_eq!(created[0]["parent"], json!(layout.id()));
+//! assert_eq!(mock.calls_to("setText")[0]["text"], "Cancel");
+//!
+//! // Inject a click and receive it like a real one
+//! mock.send_event(&json!({
+//!     "type": "click",
+//!     "value": {"aid": activity.id(), "id": button.id()}
+//! }))?;
+//! assert_eq!(activity.next_event()?.view_id(), Some(button.id()));
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::HashMap;
+use std::net::Shutdown;
+use std::os::unix::net::UnixStream;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+use serde_json::{json, Value};
+
+use crate::activity::Activity;
+use crate::connection::{read_message, send_message, Connection};
+use crate::error::{GuiError, Result};
+use crate::gui::Gui;
+use crate::protocol::{self, Reply};
+
+/// Method name of the marker message used to wait for pending calls
+const SYNC_METHOD: &str = "__mockSync";
+
+/// How long to wait for the service thread to catch up
+const SYNC_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// State shared between the test and the service thread
+#[derive(Default)]
+struct State {
+    calls: Vec<Value>,
+    next_aid: i64,
+    next_id: i64,
+    texts: HashMap<(i64, i64), String>,
+    responses: HashMap<String, Value>,
+}
+
+impl State {
+    /// Compute the reply for a method, or `None` if it doesn't expect one
+    fn respond(&mut self, method: &str, params: &Value) -> Option<Value> {
+        let aid = params["aid"].as_i64().unwrap_or(-1);
+        let id = params["id"].as_i64().unwrap_or(-1);
+
+        if method == "setText" {
+            let text = params["text"].as_str().unwrap_or("").to_string();
+            self.texts.insert((aid, id), text);
+        }
+
+        if let Some(response) = self.responses.get(method) {
+            return Some(response.clone());
+        }
+
+        // Reply exactly when the protocol table says the service does
+        let reply = protocol::method(method).map_or(Reply::None, |m| m.reply);
+        match (method, reply) {
+            (_, Reply::None) => None,
+            (_, Reply::Activity) => {
+                let aid = self.next_aid;
+                self.next_aid += 1;
+                // Each Activity gets its own task unless launched into one
+                Some(json!([aid, params["tid"].as_i64().unwrap_or(aid)]))
+            }
+            (_, Reply::Id) => {
+                let id = self.next_id;
+                self.next_id += 1;
+                if let Some(text) = params["text"].as_str() {
+                    self.texts.insert((aid, id), text.to_string());
+                }
+                Some(json!(id))
+            }
+            ("getText", _) => Some(json!(self.texts.get(&(aid, id)).cloned().unwrap_or_default())),
+            ("getConfiguration", _) => Some(json!({
+                "dark_mode": false,
+                "country": "US",
+                "language": "en",
+                "orientation": "portrait",
+                "keyboardHidden": true,
+                "screenwidth": 400,
+                "screenheight": 800,
+                "fontscale": 1.0,
+                "density": 2.0
+            })),
+            ("allowJavascript", _) => Some(json!(params["allow"].as_bool().unwrap_or(false))),
+            (_, Reply::Int) => Some(json!(0)),
+            (_, Reply::Bool) => Some(json!(false)),
+            (_, Reply::Str) => Some(json!("")),
+            (_, Reply::IntPair) => Some(json!([0, 0])),
+            (_, Reply::Object) => Some(json!({})),
+        }
+    }
+}
+
+/// A fake Termux GUI service running on a background thread
+pub struct MockService {
+    state: Arc<Mutex<State>>,
+    /// Client side of the main socket, used to send sync markers
+    client_main: UnixStream,
+    /// Service side of the main socket, shut down on drop
+    service_main: UnixStream,
+    /// Service side of the event socket
+    service_events: UnixStream,
+    synced: Receiver<()>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl MockService {
+    /// Start a mock service and return a [`Connection`] to it
+    pub fn start() -> Result<(MockService, Connection)> {
+        let (client_main, service_main) = UnixStream::pair()?;
+        let (client_events, service_events) = UnixStream::pair()?;
+
+        let state = Arc::new(Mutex::new(State::default()));
+        let (sync_tx, synced) = mpsc::channel();
+
+        let thread = {
+            let state = Arc::clone(&state);
+            let stream = service_main.try_clone()?;
+            thread::spawn(move || serve(stream, state, sync_tx))
+        };
+
+        let mock = MockService {
+            state,
+            client_main: client_main.try_clone()?,
+            service_main,
+            service_events,
+            synced,
+            thread: Some(thread),
+        };
+
+        let conn = Connection::from_streams(client_main, client_events);
+
+        Ok((mock, conn))
+    }
+
+    /// Start a mock service and create an [`Activity`] on it
+    pub fn activity(dialog: bool) -> Result<(MockService, Activity)> {
+        let (mock, conn) = Self::start()?;
+        let activity = Activity::with_connection(conn, dialog)?;
+        Ok((mock, activity))
+    }
+
+    /// Start a mock service and open a [`Gui`] session on it
+    ///
+    /// ```rust
+    /// use serde_json::json;
+    /// use termux_gui::testing::MockService;
+    ///
+    /// # fn main() -> termux_gui::Result<()> {
+    /// let (mock, gui) = MockService::gui()?;
+    /// let mut first = gui.new_activity(false)?;
+    /// let mut second = gui.new_activity_in_task(first.task_id(), true)?;
+    ///
+    /// mock.send_event(&json!({"type": "click", "value": {"aid": first.id(), "id": 1}}))?;
+    /// mock.send_event(&json!({"type": "click", "value": {"aid": second.id(), "id": 2}}))?;
+    ///
+    /// // Each Activity only sees its own events
+    /// assert_eq!(second.next_event()?.view_id(), Some(2));
+    /// assert_eq!(first.next_event()?.view_id(), Some(1));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn gui() -> Result<(MockService, Gui)> {
+        let (mock, conn) = Self::start()?;
+        let gui = Gui::with_connection(conn)?;
+        Ok((mock, gui))
+    }
+
+    /// Always answer `method` with `response`
+    ///
+    /// This also makes the mock reply to methods it would otherwise treat
+    /// as fire-and-forget.
+    pub fn set_response(&self, method: &str, response: Value) {
+        self.lock().responses.insert(method.to_string(), response);
+    }
+
+    /// Get every message received so far, in order
+    ///
+    /// Waits until the service has processed everything the client sent.
+    pub fn calls(&self) -> Vec<Value> {
+        self.sync();
+        self.lock().calls.clone()
+    }
+
+    /// Get the `params` of every call to `method`, in order
+    pub fn calls_to(&self, method: &str) -> Vec<Value> {
+        self.calls()
+            .into_iter()
+            .filter(|msg| msg["method"] == method)
+            .map(|msg| msg["params"].clone())
+            .collect()
+    }
+
+    /// Forget all recorded calls
+    pub fn clear_calls(&self) {
+        self.sync();
+        self.lock().calls.clear();
+    }
+
+    /// Send an event to the client, e.g. `{"type": "click", "value": {...}}`
+    pub fn send_event(&self, event: &Value) -> Result<()> {
+        send_message(&mut &self.service_events, event)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Wait until the service thread has read everything written so far
+    fn sync(&self) {
+        if send_message(&mut &self.client_main, &json!({ "method": SYNC_METHOD })).is_ok() {
+            let _ = self.synced.recv_timeout(SYNC_TIMEOUT);
+        }
+    }
+}
+
+impl Drop for MockService {
+    fn drop(&mut self) {
+        let _ = self.service_main.shutdown(Shutdown::Both);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// Service loop: read requests, record them and answer the ones that expect a reply
+fn serve(mut stream: UnixStream, state: Arc<Mutex<State>>, synced: Sender<()>) {
+    loop {
+        let msg = match read_message(&mut stream) {
+            Ok(msg) => msg,
+            Err(GuiError::Json(_)) => continue,
+            Err(_) => return,
+        };
+
+        let method = msg["method"].as_str().unwrap_or("").to_string();
+        if method == SYNC_METHOD {
+            let _ = synced.send(());
+            continue;
+        }
+
+        let response = {
+            let mut state = state.lock().unwrap_or_else(|e| e.into_inner());
+            state.calls.push(msg.clone());
+            state.respond(&method, &msg["params"])
+        };
+
+        if let Some(response) = response {
+            if send_message(&mut stream, &response).is_err() {
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use serde_json::json;
+
+    use super::MockService;
+    use crate::error::Result;
+
+    #[test]
+    fn new_activity_gets_incrementing_ids_and_own_task() -> Result<()> {
+        let (mock, gui) = MockService::gui()?;
+        let first = gui.new_activity(false)?;
+        let second = gui.new_activity(true)?;
+        let third = gui.new_activity_in_task(first.task_id(), true)?;
+
+        assert_eq!((first.id(), first.task_id()), (0, 0));
+        assert_eq!((second.id(), second.task_id()), (1, 1));
+        assert_eq!((third.id(), third.task_id()), (2, 0));
+
+        let launched = mock.calls_to("newActivity");
+        assert_eq!(launched.len(), 3);
+        assert_eq!(launched[1]["dialog"], json!(true));
+        assert_eq!(launched[2]["tid"], json!(first.task_id()));
+        Ok(())
+    }
+
+    #[test]
+    fn records_fire_and_forget_calls_in_order() -> Result<()> {
+        let (mock, mut activity) = MockService::activity(false)?;
+        let text = activity.create_text_view("a", None)?;
+        mock.clear_calls();
+
+        text.set_text(&mut activity, "b")?;
+        text.set_text_size(&mut activity, 20)?;
+
+        let methods: Vec<_> = mock.calls().iter().map(|msg| msg["method"].clone()).collect();
+        assert_eq!(methods, [json!("setText"), json!("setTextSize")]);
+        Ok(())
+    }
+
+    #[test]
+    fn get_text_returns_created_and_set_text() -> Result<()> {
+        let (_mock, mut activity) = MockService::activity(false)?;
+        let edit = activity.create_edit_text("initial", None)?;
+        assert_eq!(edit.get_text(&mut activity)?, "initial");
+
+        edit.set_text(&mut activity, "changed")?;
+        assert_eq!(edit.get_text(&mut activity)?, "changed");
+        Ok(())
+    }
+
+    #[test]
+    fn set_response_overrides_the_default_reply() -> Result<()> {
+        let (mock, mut activity) = MockService::activity(false)?;
+        let edit = activity.create_edit_text("", None)?;
+
+        mock.set_response("getText", json!("from the test"));
+        assert_eq!(edit.get_text(&mut activity)?, "from the test");
+        Ok(())
+    }
+
+    #[test]
+    fn clear_calls_forgets_earlier_calls() -> Result<()> {
+        let (mock, mut activity) = MockService::activity(false)?;
+        activity.create_space(None)?;
+        assert!(!mock.calls().is_empty());
+
+        mock.clear_calls();
+        assert!(mock.calls().is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn injected_events_reach_the_activity() -> Result<()> {
+        let (mock, mut activity) = MockService::activity(false)?;
+        mock.send_event(&json!({
+            "type": "click",
+            "value": {"aid": activity.id(), "id": 7}
+        }))?;
+
+        let event = activity.next_event()?;
+        assert_eq!(event.aid(), Some(activity.id()));
+        assert_eq!(event.view_id(), Some(7));
+        Ok(())
+    }
+}
@@ -0,0 +1,119 @@
+//! Activity themes
+//!
+//! A [`Theme`] sets the colours Android uses for an Activity's window and
+//! default widgets. Apply it with
+//! [`Activity::set_theme`](crate::Activity::set_theme).
+//!
+//! ## Example
+//!
+//! ```rust,no_run
+//! use termux_gui::{Activity, Color, Result, Theme};
+//!
+//! fn main() -> Result<()> {
+//!     let mut activity = Activity::new(false)?;
+//!
+//!     // Start from a preset and change what you need
+//!     let theme = Theme {
+//!         color_accent: Color::parse("orange")?,
+//!         ..Theme::dark()
+//!     };
+//!     activity.set_theme(&theme)?;
+//!     Ok(())
+//! }
+//! ```
+
+use crate::color::Color;
+use crate::raw::SetTheme;
+
+/// Colours of an Activity
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Theme {
+    /// Colour of the status bar
+    pub status_bar_color: Color,
+    /// Primary colour, used for the app bar and task entry
+    pub color_primary: Color,
+    /// Background of the window
+    pub window_background: Color,
+    /// Default text colour
+    pub text_color: Color,
+    /// Accent colour, used for checkboxes, switches and the cursor
+    pub color_accent: Color,
+}
+
+impl Theme {
+    /// Dark text on a light background
+    pub fn light() -> Self {
+        Theme {
+            status_bar_color: Color::rgb(0xE0, 0xE0, 0xE0),
+            color_primary: Color::rgb(0xF5, 0xF5, 0xF5),
+            window_background: Color::WHITE,
+            text_color: Color::rgb(0x21, 0x21, 0x21),
+            color_accent: Color::rgb(0x21, 0x96, 0xF3),
+        }
+    }
+
+    /// Light text on a dark background
+    pub fn dark() -> Self {
+        Theme {
+            status_bar_color: Color::BLACK,
+            color_primary: Color::rgb(0x21, 0x21, 0x21),
+            window_background: Color::rgb(0x12, 0x12, 0x12),
+            text_color: Color::WHITE,
+            color_accent: Color::rgb(0x90, 0xCA, 0xF9),
+        }
+    }
+
+    /// Build the `setTheme` parameters for Activity `aid`
+    pub(crate) fn to_params(self, aid: i64) -> SetTheme {
+        SetTheme {
+            aid,
+            status_bar_color: Some(self.status_bar_color.to_i32().into()),
+            color_primary: Some(self.color_primary.to_i32().into()),
+            window_background: Some(self.window_background.to_i32().into()),
+            text_color: Some(self.text_color.to_i32().into()),
+            color_accent: Some(self.color_accent.to_i32().into()),
+        }
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::light()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn params_carry_every_colour_as_argb() {
+        let params = Theme::dark().to_params(3);
+        assert_eq!(params.aid, 3);
+        assert_eq!(params.status_bar_color, Some(Color::BLACK.to_i32().into()));
+        assert_eq!(params.text_color, Some(Color::WHITE.to_i32().into()));
+        assert_eq!(params.window_background, Some(0xFF121212u32 as i32 as i64));
+    }
+
+    #[test]
+    fn default_is_light() {
+        assert_eq!(Theme::default(), Theme::light());
+    }
+
+    #[cfg(feature = "testing")]
+    #[test]
+    fn set_theme_sends_every_colour() -> crate::error::Result<()> {
+        use serde_json::json;
+
+        use crate::testing::MockService;
+
+        let (mock, mut activity) = MockService::activity(false)?;
+        activity.set_theme(&Theme::light())?;
+
+        let set = &mock.calls_to("setTheme")[0];
+        assert_eq!(set["aid"], json!(activity.id()));
+        assert_eq!(set["colorAccent"], json!(Color::rgb(0x21, 0x96, 0xF3).to_i32()));
+        assert_eq!(set.as_object().map(|params| params.len()), Some(6));
+        Ok(())
+    }
+}
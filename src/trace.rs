@@ -0,0 +1,162 @@
+//! Opt-in protocol tracing
+//!
+//! With the `tracing` feature enabled, every request on the main socket runs
+//! inside a `termux_gui::call` span carrying the method name, Activity ID
+//! and view ID, and logs its latency when done. Without the feature all of
+//! this compiles to nothing.
+
+use serde_json::Value;
+
+use crate::error::Result;
+
+/// Log a debug message through `tracing`, if enabled
+macro_rules! debug {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "tracing")]
+        tracing::debug!($($arg)*);
+    };
+}
+
+pub(crate) use debug;
+
+/// Tracing state for one request on the main socket
+///
+/// The span is created by [`start`](Self::start) but only entered around
+/// the work done for the request, so a pipelined request doesn't stay
+/// entered while others are sent.
+pub(crate) struct CallTrace {
+    #[cfg(feature = "tracing")]
+    span: tracing::Span,
+    #[cfg(feature = "tracing")]
+    start: std::time::Instant,
+}
+
+impl CallTrace {
+    /// Open a span for `msg`; `kind` is `"send"` or `"send_read"`
+    #[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+    pub(crate) fn start(kind: &'static str, msg: &Value) -> Self {
+        CallTrace {
+            #[cfg(feature = "tracing")]
+            span: tracing::debug_span!(
+                "termux_gui::call",
+                kind,
+                method = msg["method"].as_str().unwrap_or(""),
+                aid = msg["params"]["aid"].as_i64(),
+                id = msg["params"]["id"].as_i64(),
+            ),
+            #[cfg(feature = "tracing")]
+            start: std::time::Instant::now(),
+        }
+    }
+
+    /// Run `f` inside the span
+    pub(crate) fn in_scope<T>(&self, f: impl FnOnce() -> T) -> T {
+        #[cfg(feature = "tracing")]
+        return self.span.in_scope(f);
+        #[cfg(not(feature = "tracing"))]
+        f()
+    }
+
+    /// Await `future` inside the span
+    #[cfg(feature = "async")]
+    pub(crate) async fn instrument<F: std::future::Future>(&self, future: F) -> F::Output {
+        #[cfg(feature = "tracing")]
+        return tracing::Instrument::instrument(future, self.span.clone()).await;
+        #[cfg(not(feature = "tracing"))]
+        future.await
+    }
+
+    /// Log the outcome and latency of the request
+    #[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+    pub(crate) fn finish<T: std::fmt::Debug>(self, result: &Result<T>) {
+        #[cfg(feature = "tracing")]
+        self.span.in_scope(|| {
+            let latency_us = self.start.elapsed().as_micros() as u64;
+            match result {
+                Ok(response) => tracing::debug!(latency_us, ?response, "done"),
+                Err(error) => tracing::warn!(latency_us, %error, "failed"),
+            }
+        });
+    }
+}
+
+#[cfg(all(test, feature = "tracing"))]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use serde_json::json;
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::{Event, Metadata, Subscriber};
+
+    use super::CallTrace;
+    use crate::error::GuiError;
+
+    /// Subscriber that logs span entries and exits and where events happen
+    #[derive(Clone, Default)]
+    struct Log(Arc<Mutex<(Vec<String>, usize)>>);
+
+    impl Log {
+        fn push(&self, entry: String) {
+            self.0.lock().unwrap().0.push(entry);
+        }
+
+        fn entries(&self) -> Vec<String> {
+            self.0.lock().unwrap().0.clone()
+        }
+    }
+
+    impl Subscriber for Log {
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, span: &Attributes<'_>) -> Id {
+            self.push(format!("new {}", span.metadata().name()));
+            Id::from_u64(1)
+        }
+
+        fn record(&self, _: &Id, _: &Record<'_>) {}
+
+        fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+        fn event(&self, event: &Event<'_>) {
+            let depth = self.0.lock().unwrap().1;
+            let place = if depth > 0 { "in span" } else { "outside" };
+            self.push(format!("{} {}", event.metadata().level(), place));
+        }
+
+        fn enter(&self, _: &Id) {
+            self.0.lock().unwrap().1 += 1;
+            self.push("enter".to_string());
+        }
+
+        fn exit(&self, _: &Id) {
+            self.0.lock().unwrap().1 -= 1;
+            self.push("exit".to_string());
+        }
+    }
+
+    #[test]
+    fn work_and_outcome_are_logged_inside_the_call_span() {
+        let log = Log::default();
+        tracing::subscriber::with_default(log.clone(), || {
+            let msg = json!({"method": "setText", "params": {"aid": 1, "id": 2, "text": "hi"}});
+            let trace = CallTrace::start("send", &msg);
+            trace.in_scope(|| tracing::debug!("writing"));
+            trace.finish(&Err::<(), _>(GuiError::ConnectionFailed));
+        });
+
+        assert_eq!(
+            log.entries(),
+            [
+                "new termux_gui::call",
+                "enter",
+                "DEBUG in span",
+                "exit",
+                "enter",
+                "WARN in span",
+                "exit",
+            ]
+        );
+    }
+}
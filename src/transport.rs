@@ -0,0 +1,434 @@
+//! Pluggable ways to establish a connection
+//!
+//! A [`Connector`] produces a ready-to-use [`Connection`]. The crate ships
+//! with two:
+//!
+//! - [`BroadcastConnector`]: the default. Binds two abstract sockets, asks
+//!   the Termux:GUI plugin to connect to them via `am broadcast` (or a custom
+//!   launcher) and performs the handshake, with a configurable timeout.
+//! - [`SocketConnector`]: connects to sockets that already exist, e.g. ones
+//!   provided by a proxy or debugger.
+//!
+//! Any `Fn() -> Result<Connection>` closure is a `Connector` too, which is
+//! handy for test harnesses.
+//!
+//! ## Example
+//!
+//! ```rust,no_run
+//! use termux_gui::{Activity, Result};
+//! use termux_gui::transport::{BroadcastConnector, SocketConnector};
+//!
+//! # fn main() -> Result<()> {
+//! // Launch the plugin with a custom command
+//! let connector = BroadcastConnector::with_launcher(|main, event| {
+//!     println!("connect the plugin to {} and {}", main, event);
+//!     Ok(())
+//! });
+//! let activity = Activity::with_connector(&connector, false)?;
+//!
+//! // Or connect to sockets a proxy is listening on
+//! let connector = SocketConnector::new("@gui-proxy-main", "@gui-proxy-event");
+//! let activity = Activity::with_connector(&connector, false)?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::os::unix::net::UnixStream;
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+use crate::connection::{
+    accept_timeout, bind_abstract_socket, connect_abstract_socket, generate_random_address,
+    handshake, Connection,
+};
+use crate::error::{GuiError, Result};
+use crate::trace::debug;
+
+/// Environment variable naming a pre-existing main socket
+pub const MAIN_SOCKET_ENV: &str = "TERMUX_GUI_MAIN_SOCKET";
+
+/// Environment variable naming a pre-existing event socket
+pub const EVENT_SOCKET_ENV: &str = "TERMUX_GUI_EVENT_SOCKET";
+
+/// Receiver component of the Termux:GUI plugin
+pub const GUI_RECEIVER: &str = "com.termux.gui/.GUIReceiver";
+
+/// Something that can establish a connection to the Termux GUI service
+pub trait Connector {
+    /// Establish a new connection
+    fn connect(&self) -> Result<Connection>;
+}
+
+impl<F> Connector for F
+where
+    F: Fn() -> Result<Connection>,
+{
+    fn connect(&self) -> Result<Connection> {
+        self()
+    }
+}
+
+/// Get the connector used by `Connection::new` and `Activity::new`
+///
+/// Returns a [`SocketConnector`] if both [`MAIN_SOCKET_ENV`] and
+/// [`EVENT_SOCKET_ENV`] are set, otherwise a [`BroadcastConnector`].
+pub fn default_connector() -> Box<dyn Connector> {
+    match SocketConnector::from_env() {
+        Some(connector) => Box::new(connector),
+        None => Box::new(BroadcastConnector::new()),
+    }
+}
+
+type Launcher = Box<dyn Fn(&str, &str) -> Result<()>>;
+
+/// How long [`BroadcastConnector`] waits for the plugin by default
+pub const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Package name of the Termux:GUI plugin
+pub const GUI_PACKAGE: &str = "com.termux.gui";
+
+/// Connects by asking the plugin to connect back to two listening sockets
+///
+/// The launcher receives the abstract names of the main and event sockets.
+/// By default it runs `termux-am broadcast` and falls back to `am broadcast`.
+///
+/// If the plugin doesn't connect back within the timeout (10 seconds by
+/// default) `connect` fails with `GuiError::HandshakeTimeout` instead of
+/// blocking forever, or with `GuiError::PluginMissing` if the timeout turns
+/// out to be caused by the plugin not being installed.
+pub struct BroadcastConnector {
+    launcher: Launcher,
+    timeout: Option<Duration>,
+    check_plugin: bool,
+}
+
+impl BroadcastConnector {
+    /// Create a connector that launches the plugin with `am broadcast`
+    pub fn new() -> Self {
+        Self::with_launcher(am_broadcast)
+    }
+
+    /// Create a connector with a custom launcher
+    pub fn with_launcher<F>(launcher: F) -> Self
+    where
+        F: Fn(&str, &str) -> Result<()> + 'static,
+    {
+        BroadcastConnector {
+            launcher: Box::new(launcher),
+            timeout: Some(DEFAULT_CONNECT_TIMEOUT),
+            check_plugin: false,
+        }
+    }
+
+    /// Set how long to wait for the plugin to connect and answer the handshake
+    ///
+    /// `None` waits forever.
+    pub fn timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Set whether to check that the plugin is installed before launching it
+    ///
+    /// Off by default, as the check runs `pm`, which takes a moment. Without
+    /// it a missing plugin is still reported once the handshake times out.
+    /// The check only runs on Android; elsewhere it is skipped.
+    pub fn check_plugin(mut self, check: bool) -> Self {
+        self.check_plugin = check;
+        self
+    }
+}
+
+impl Default for BroadcastConnector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Connector for BroadcastConnector {
+    fn connect(&self) -> Result<Connection> {
+        if self.check_plugin && plugin_installed() == Some(false) {
+            return Err(GuiError::PluginMissing);
+        }
+
+        let addr_main = generate_random_address();
+        let addr_event = generate_random_address();
+
+        debug!(main = %addr_main, event = %addr_event, "binding sockets");
+        let main_listener = bind_abstract_socket(&addr_main)?;
+        let event_listener = bind_abstract_socket(&addr_event)?;
+
+        debug!("launching plugin");
+        (self.launcher)(&addr_main, &addr_event)?;
+
+        // One deadline for the whole handshake, so a slow plugin start
+        // can't stretch it to several times the timeout
+        let deadline = self.timeout.map(|timeout| Instant::now() + timeout);
+        let remaining = || -> Result<Option<Duration>> {
+            match (deadline, self.timeout) {
+                (Some(deadline), Some(timeout)) => {
+                    let left = deadline.saturating_duration_since(Instant::now());
+                    if left.is_zero() {
+                        Err(GuiError::HandshakeTimeout(timeout))
+                    } else {
+                        Ok(Some(left))
+                    }
+                }
+                _ => Ok(None),
+            }
+        };
+
+        debug!(timeout = ?self.timeout, "waiting for the plugin to connect");
+        let mut main_stream = accept_timeout(&main_listener, remaining()?)
+            .map_err(|e| timeout_error(e, self.timeout))?;
+        let event_stream = accept_timeout(&event_listener, remaining()?)
+            .map_err(|e| timeout_error(e, self.timeout))?;
+
+        handshake(&mut main_stream, remaining()?).map_err(|e| timeout_error(e, self.timeout))?;
+
+        debug!("connected");
+        Ok(Connection::from_streams(main_stream, event_stream))
+    }
+}
+
+/// Explain a handshake timeout
+///
+/// Reports the configured timeout rather than what was left of it, and
+/// `GuiError::PluginMissing` if the plugin isn't installed.
+fn timeout_error(err: GuiError, timeout: Option<Duration>) -> GuiError {
+    match (err, timeout) {
+        (GuiError::HandshakeTimeout(_), _) if plugin_installed() == Some(false) => {
+            GuiError::PluginMissing
+        }
+        (GuiError::HandshakeTimeout(_), Some(timeout)) => GuiError::HandshakeTimeout(timeout),
+        (err, _) => err,
+    }
+}
+
+/// Check whether the Termux:GUI plugin is installed
+///
+/// Asks the package manager with `pm path`. Returns `None` when `pm` can't
+/// be run, e.g. when not running on Android, where this can't be known.
+pub fn plugin_installed() -> Option<bool> {
+    let output = Command::new("pm").args(["path", GUI_PACKAGE]).output().ok()?;
+    // `pm` prints `package:<apk path>` for installed packages and exits
+    // with an error, printing nothing, for unknown ones
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    Some(output.status.success() && stdout.trim_start().starts_with("package:"))
+}
+
+/// Ask the Termux:GUI plugin to connect to the given sockets
+///
+/// Tries `termux-am` first and falls back to `am`. Fails with
+/// `GuiError::BroadcastFailed`, including the exit status and captured
+/// stderr, if neither succeeds.
+pub fn am_broadcast(main: &str, event: &str) -> Result<()> {
+    let args = [
+        "broadcast",
+        "-n",
+        GUI_RECEIVER,
+        "--es",
+        "mainSocket",
+        main,
+        "--es",
+        "eventSocket",
+        event,
+    ];
+
+    let termux_am_err = match run_broadcast("termux-am", &args) {
+        Ok(()) => return Ok(()),
+        Err(e) => e,
+    };
+
+    debug!(error = %termux_am_err, "termux-am failed, trying am");
+    match run_broadcast("am", &args) {
+        Ok(()) => Ok(()),
+        // `am` not being runnable says less than why termux-am failed
+        Err(GuiError::BroadcastFailed { status, .. }) if status.starts_with(NOT_RUN) => {
+            Err(termux_am_err)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Status prefix used when the broadcast program could not be started
+const NOT_RUN: &str = "could not run";
+
+fn run_broadcast(program: &str, args: &[&str]) -> Result<()> {
+    let output = Command::new(program).args(args).output().map_err(|e| {
+        GuiError::BroadcastFailed {
+            program: program.to_string(),
+            status: format!("{}: {}", NOT_RUN, e),
+            stderr: String::new(),
+        }
+    })?;
+
+    if output.status.success() {
+        return Ok(());
+    }
+
+    Err(GuiError::BroadcastFailed {
+        program: program.to_string(),
+        status: output.status.to_string(),
+        stderr: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+    })
+}
+
+/// Connects to a main and an event socket that already exist
+///
+/// Addresses starting with `@` name abstract namespace sockets, anything
+/// else is a filesystem path. The handshake is performed on connect.
+#[derive(Debug, Clone)]
+pub struct SocketConnector {
+    main: String,
+    event: String,
+    timeout: Option<Duration>,
+}
+
+impl SocketConnector {
+    /// Create a connector for the given socket addresses
+    pub fn new(main: impl Into<String>, event: impl Into<String>) -> Self {
+        SocketConnector {
+            main: main.into(),
+            event: event.into(),
+            timeout: Some(DEFAULT_CONNECT_TIMEOUT),
+        }
+    }
+
+    /// Set how long to wait for the handshake answer
+    ///
+    /// `None` waits forever.
+    pub fn timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Create a connector from [`MAIN_SOCKET_ENV`] and [`EVENT_SOCKET_ENV`]
+    ///
+    /// Returns `None` unless both variables are set.
+    pub fn from_env() -> Option<Self> {
+        let main = std::env::var(MAIN_SOCKET_ENV).ok()?;
+        let event = std::env::var(EVENT_SOCKET_ENV).ok()?;
+        Some(Self::new(main, event))
+    }
+}
+
+impl Connector for SocketConnector {
+    fn connect(&self) -> Result<Connection> {
+        let mut main_stream = connect_address(&self.main)?;
+        let event_stream = connect_address(&self.event)?;
+        handshake(&mut main_stream, self.timeout)?;
+        Ok(Connection::from_streams(main_stream, event_stream))
+    }
+}
+
+fn connect_address(address: &str) -> Result<UnixStream> {
+    match address.strip_prefix('@') {
+        Some(name) => connect_abstract_socket(name),
+        None => Ok(UnixStream::connect(address)?),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Read, Write};
+    use std::thread;
+
+    use super::*;
+
+    /// Play the plugin: connect to both sockets and answer the handshake
+    fn connect_back(main: &str, event: &str) -> Result<()> {
+        let mut main = connect_abstract_socket(main)?;
+        let event = connect_abstract_socket(event)?;
+        thread::spawn(move || {
+            let mut version = [0u8; 1];
+            if main.read_exact(&mut version).is_ok() {
+                let _ = main.write_all(&[0]);
+            }
+            // Keep the sockets open until the client is done
+            let _ = main.read(&mut [0u8; 1]);
+            drop(event);
+        });
+        Ok(())
+    }
+
+    #[test]
+    fn broadcast_connector_runs_the_launcher_and_handshakes() {
+        let connector = BroadcastConnector::with_launcher(connect_back);
+        assert!(connector.connect().is_ok());
+    }
+
+    #[test]
+    fn broadcast_connector_reports_launcher_errors() {
+        let connector = BroadcastConnector::with_launcher(|_, _| {
+            Err(GuiError::BroadcastFailed {
+                program: "am".to_string(),
+                status: "exit status: 1".to_string(),
+                stderr: String::new(),
+            })
+        });
+        assert!(matches!(connector.connect(), Err(GuiError::BroadcastFailed { .. })));
+    }
+
+    #[test]
+    fn broadcast_connector_times_out() {
+        let timeout = Duration::from_millis(50);
+        let connector = BroadcastConnector::with_launcher(|_, _| Ok(())).timeout(Some(timeout));
+
+        match connector.connect() {
+            // Off Android there is no `pm` to tell the plugin is missing
+            Err(GuiError::HandshakeTimeout(reported)) => assert_eq!(reported, timeout),
+            Err(GuiError::PluginMissing) => assert_eq!(plugin_installed(), Some(false)),
+            other => panic!("expected a timeout, got {:?}", other.map(|_| ())),
+        }
+    }
+
+    #[test]
+    fn handshake_rejects_other_protocol_versions() {
+        let connector = BroadcastConnector::with_launcher(|main, event| {
+            let mut main = connect_abstract_socket(main)?;
+            let event = connect_abstract_socket(event)?;
+            thread::spawn(move || {
+                let mut version = [0u8; 1];
+                if main.read_exact(&mut version).is_ok() {
+                    let _ = main.write_all(&[1]);
+                }
+                drop(event);
+            });
+            Ok(())
+        });
+
+        match connector.connect() {
+            Err(GuiError::ProtocolVersionMismatch { requested, answer }) => {
+                assert_eq!((requested, answer), (crate::connection::PROTOCOL_VERSION, 1));
+            }
+            other => panic!("expected a version mismatch, got {:?}", other.map(|_| ())),
+        }
+    }
+
+    #[test]
+    fn socket_connector_connects_to_abstract_sockets() -> Result<()> {
+        let main = generate_random_address();
+        let event = generate_random_address();
+        let main_listener = bind_abstract_socket(&main)?;
+        let event_listener = bind_abstract_socket(&event)?;
+
+        let service = thread::spawn(move || -> Result<()> {
+            let (mut main, _) = main_listener.accept()?;
+            let (_event, _) = event_listener.accept()?;
+            let mut version = [0u8; 1];
+            main.read_exact(&mut version)?;
+            main.write_all(&[0])?;
+            Ok(())
+        });
+
+        SocketConnector::new(format!("@{}", main), format!("@{}", event)).connect()?;
+        service.join().unwrap()
+    }
+
+    #[test]
+    fn closures_are_connectors() {
+        let connector = || -> Result<Connection> { Err(GuiError::ConnectionFailed) };
+        assert!(matches!(connector.connect(), Err(GuiError::ConnectionFailed)));
+    }
+}
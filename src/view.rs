@@ -10,34 +10,83 @@
 //!
 //! When working with `HorizontalScrollView` or `TabLayout`:
 //! 1. `get_dimensions()` returns values in **pixels (px)**
-//! 2. `set_width()` and `set_height()` use **dp** by default
-//! 3. **You must use `set_width_px()` and `set_height_px()`** to match dimensions!
+//! 2. `set_width()` and `set_height()` treat plain integers as **dp**
+//! 3. **You must pass `Dimension::Px`** (or use `set_width_px()`) to match dimensions!
+//!
+//! See [`Dimension`] for all units.
 //!
 //! ### Example
 //!
 //! ```rust,no_run
-//! # use termux_gui::{Activity, Result};
-//! # fn example(activity: &mut Activity, scroll_view_id: i64, page_id: i64) -> Result<()> {
+//! # use termux_gui::{Activity, Dimension, HorizontalScrollView, LinearLayout, Result};
+//! # fn example(activity: &mut Activity, scroll_view: &HorizontalScrollView, page: &LinearLayout) -> Result<()> {
 //! // Get dimensions (returns pixels)
 //! let (width_px, height_px) = scroll_view.view().get_dimensions(activity)?;
 //!
 //! // WRONG: This uses dp, not px!
 //! // page.view().set_width(activity, width_px)?;  // Width mismatch!
 //!
-//! // CORRECT: Use set_width_px to match pixel values
-//! page.view().set_width_px(activity, width_px)?;  // Perfect match!
+//! // CORRECT: Say the value is in px
+//! page.view().set_width(activity, Dimension::Px(width_px))?;  // Perfect match!
 //! # Ok(())
 //! # }
 //! ```
 
-use serde_json::json;
 use crate::activity::Activity;
-use crate::error::Result;
+use crate::color::Color;
+use crate::dimension::Dimension;
+use crate::error::{GuiError, Result};
+use crate::raw::{self, Size};
 
 /// Special dimension constants for Android layouts
+///
+/// These convert to [`Dimension::MatchParent`] and [`Dimension::WrapContent`].
 pub const MATCH_PARENT: i32 = -1;
 pub const WRAP_CONTENT: i32 = -2;
 
+/// Visibility of a View
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Visibility {
+    /// Shown normally
+    Visible,
+    /// Hidden, but still takes up space in the layout
+    Invisible,
+    /// Hidden and takes up no space
+    Gone,
+}
+
+impl Visibility {
+    /// Get the value used by the protocol
+    pub fn as_i32(self) -> i32 {
+        match self {
+            Visibility::Gone => 0,
+            Visibility::Invisible => 1,
+            Visibility::Visible => 2,
+        }
+    }
+}
+
+/// One side of a View, for per-side margins and padding
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Side {
+    Top,
+    Bottom,
+    Left,
+    Right,
+}
+
+impl Side {
+    /// Get the name used by the protocol
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Side::Top => "top",
+            Side::Bottom => "bottom",
+            Side::Left => "left",
+            Side::Right => "right",
+        }
+    }
+}
+
 /// Base View structure
 pub struct View {
     id: i64,
@@ -54,74 +103,46 @@ impl View {
         self.id
     }
     
-    /// Set view width in dp (density-independent pixels)
+    /// Set view width
+    /// 
+    /// Plain integers are dp (density-independent pixels).
     /// 
     /// **Note**: If you need to match dimensions from `get_dimensions()`,
-    /// use `set_width_px()` instead, as `get_dimensions()` returns pixels.
-    pub fn set_width(&self, activity: &mut Activity, width: i32) -> Result<()> {
-        activity.send(&json!({
-            "method": "setWidth",
-            "params": {
-                "aid": activity.id(),
-                "id": self.id,
-                "width": width
-            }
-        }))?;
-        Ok(())
+    /// pass `Dimension::Px`, as `get_dimensions()` returns pixels.
+    pub fn set_width(&self, activity: &mut Activity, width: impl Into<Dimension>) -> Result<()> {
+        let (width, px) = size_param(activity, width.into())?;
+        let aid = activity.id();
+        raw::set_width(activity, &raw::SetWidth { aid, id: self.id, width, px: Some(px) })
     }
     
     /// Set view width in pixels
     /// 
-    /// **Use this when matching dimensions from `get_dimensions()`**
-    /// which returns pixel values, not dp values.
+    /// Same as `set_width(activity, Dimension::Px(width))`.
     pub fn set_width_px(&self, activity: &mut Activity, width: i32) -> Result<()> {
-        activity.send(&json!({
-            "method": "setWidth",
-            "params": {
-                "aid": activity.id(),
-                "id": self.id,
-                "width": width,
-                "px": true
-            }
-        }))?;
-        Ok(())
+        self.set_width(activity, Dimension::Px(width))
     }
     
-    /// Set view height in dp (density-independent pixels)
+    /// Set view height
+    /// 
+    /// Plain integers are dp (density-independent pixels).
     /// 
     /// **Note**: If you need to match dimensions from `get_dimensions()`,
-    /// use `set_height_px()` instead, as `get_dimensions()` returns pixels.
-    pub fn set_height(&self, activity: &mut Activity, height: i32) -> Result<()> {
-        activity.send(&json!({
-            "method": "setHeight",
-            "params": {
-                "aid": activity.id(),
-                "id": self.id,
-                "height": height
-            }
-        }))?;
-        Ok(())
+    /// pass `Dimension::Px`, as `get_dimensions()` returns pixels.
+    pub fn set_height(&self, activity: &mut Activity, height: impl Into<Dimension>) -> Result<()> {
+        let (height, px) = size_param(activity, height.into())?;
+        let aid = activity.id();
+        raw::set_height(activity, &raw::SetHeight { aid, id: self.id, height, px: Some(px) })
     }
     
     /// Set view height in pixels
     /// 
-    /// **Use this when matching dimensions from `get_dimensions()`**
-    /// which returns pixel values, not dp values.
+    /// Same as `set_height(activity, Dimension::Px(height))`.
     pub fn set_height_px(&self, activity: &mut Activity, height: i32) -> Result<()> {
-        activity.send(&json!({
-            "method": "setHeight",
-            "params": {
-                "aid": activity.id(),
-                "id": self.id,
-                "height": height,
-                "px": true
-            }
-        }))?;
-        Ok(())
+        self.set_height(activity, Dimension::Px(height))
     }
     
     /// Set view width and height
-    pub fn set_dimensions(&self, activity: &mut Activity, width: i32, height: i32) -> Result<()> {
+    pub fn set_dimensions(&self, activity: &mut Activity, width: impl Into<Dimension>, height: impl Into<Dimension>) -> Result<()> {
         // Call both methods since there's no combined setDimensions
         self.set_width(activity, width)?;
         self.set_height(activity, height)?;
@@ -129,16 +150,55 @@ impl View {
     }
     
     /// Set view margin
-    pub fn set_margin(&self, activity: &mut Activity, margin: i32) -> Result<()> {
-        activity.send(&json!({
-            "method": "setMargin",
-            "params": {
-                "aid": activity.id(),
-                "id": self.id,
-                "margin": margin
-            }
-        }))?;
-        Ok(())
+    /// 
+    /// Plain integers are dp. Margins can't be `MatchParent` or `WrapContent`.
+    pub fn set_margin(&self, activity: &mut Activity, margin: impl Into<Dimension>) -> Result<()> {
+        let margin = dp_param(activity, margin.into(), "margin")?;
+        let aid = activity.id();
+        raw::set_margin(activity, &raw::SetMargin {
+            aid,
+            id: self.id,
+            margin: margin.into(),
+            dir: None,
+        })
+    }
+    
+    /// Set the margin of one side
+    pub fn set_margin_side(&self, activity: &mut Activity, side: Side, margin: impl Into<Dimension>) -> Result<()> {
+        let margin = dp_param(activity, margin.into(), "margin")?;
+        let aid = activity.id();
+        raw::set_margin(activity, &raw::SetMargin {
+            aid,
+            id: self.id,
+            margin: margin.into(),
+            dir: Some(side.as_str().to_string()),
+        })
+    }
+    
+    /// Set view padding
+    /// 
+    /// Plain integers are dp. Padding can't be `MatchParent` or `WrapContent`.
+    pub fn set_padding(&self, activity: &mut Activity, padding: impl Into<Dimension>) -> Result<()> {
+        let padding = dp_param(activity, padding.into(), "padding")?;
+        let aid = activity.id();
+        raw::set_padding(activity, &raw::SetPadding {
+            aid,
+            id: self.id,
+            padding: padding.into(),
+            dir: None,
+        })
+    }
+    
+    /// Set the padding of one side
+    pub fn set_padding_side(&self, activity: &mut Activity, side: Side, padding: impl Into<Dimension>) -> Result<()> {
+        let padding = dp_param(activity, padding.into(), "padding")?;
+        let aid = activity.id();
+        raw::set_padding(activity, &raw::SetPadding {
+            aid,
+            id: self.id,
+            padding: padding.into(),
+            dir: Some(side.as_str().to_string()),
+        })
     }
     
     /// Set view width to WRAP_CONTENT
@@ -168,21 +228,13 @@ impl View {
     /// * `weight` - Layout weight (higher weight = more space)
     /// * `position` - Optional position index in the layout
     pub fn set_linear_layout_params(&self, activity: &mut Activity, weight: i32, position: Option<i32>) -> Result<()> {
-        let mut params = json!({
-            "aid": activity.id(),
-            "id": self.id,
-            "weight": weight
-        });
-        
-        if let Some(pos) = position {
-            params["position"] = json!(pos);
-        }
-        
-        activity.send(&json!({
-            "method": "setLinearLayoutParams",
-            "params": params
-        }))?;
-        Ok(())
+        let aid = activity.id();
+        raw::set_linear_layout_params(activity, &raw::SetLinearLayoutParams {
+            aid,
+            id: self.id,
+            weight: weight.into(),
+            position: position.map(i64::from),
+        })
     }
     
     /// Set GridLayout parameters for this view
@@ -194,76 +246,253 @@ impl View {
     /// * `col_size` - Number of columns this view spans (default 1)
     /// * `alignment_row` - Vertical alignment: "top", "bottom", "center", "baseline", "fill"
     /// * `alignment_col` - Horizontal alignment: "left", "right", "center", "fill"
+    #[allow(clippy::too_many_arguments)]
     pub fn set_grid_layout_params(&self, activity: &mut Activity, 
                                    row: i32, col: i32,
                                    row_size: i32, col_size: i32,
                                    alignment_row: &str, alignment_col: &str) -> Result<()> {
-        activity.send(&json!({
-            "method": "setGridLayoutParams",
-            "params": {
-                "aid": activity.id(),
-                "id": self.id,
-                "row": row,
-                "col": col,
-                "rowsize": row_size,
-                "colsize": col_size,
-                "alignmentrow": alignment_row,
-                "alignmentcol": alignment_col
-            }
-        }))?;
-        Ok(())
+        let aid = activity.id();
+        raw::set_grid_layout_params(activity, &raw::SetGridLayoutParams {
+            aid,
+            id: self.id,
+            row: row.into(),
+            col: col.into(),
+            rowsize: Some(row_size.into()),
+            colsize: Some(col_size.into()),
+            alignmentrow: Some(alignment_row.to_string()),
+            alignmentcol: Some(alignment_col.to_string()),
+        })
     }
     
     /// Get the dimensions (width, height) of this view in pixels
     /// Returns (width, height)
     pub fn get_dimensions(&self, activity: &mut Activity) -> Result<(i32, i32)> {
-        let response = activity.send_read(&json!({
-            "method": "getDimensions",
-            "params": {
-                "aid": activity.id(),
-                "id": self.id
-            }
-        }))?;
-        
-        // Response is an array [width, height]
-        if let Some(arr) = response.as_array() {
-            let width = arr.get(0).and_then(|v| v.as_i64()).unwrap_or(0) as i32;
-            let height = arr.get(1).and_then(|v| v.as_i64()).unwrap_or(0) as i32;
-            Ok((width, height))
-        } else {
-            Ok((0, 0))
-        }
+        let aid = activity.id();
+        let (width, height) = raw::get_dimensions(activity, &raw::GetDimensions {
+            aid,
+            id: self.id,
+        })?;
+        Ok((width as i32, height as i32))
     }
     
     /// Set the background color of this view
     /// 
-    /// Color format: 0xAARRGGBB (alpha, red, green, blue in hexadecimal)
+    /// Accepts a [`Color`] or an ARGB integer in 0xAARRGGBB format
+    /// (alpha, red, green, blue in hexadecimal).
     /// 
     /// # Examples
     /// 
     /// ```rust,no_run
-    /// # use termux_gui::{Activity, Result};
-    /// # fn example(activity: &mut Activity, view_id: i64) -> Result<()> {
+    /// # use termux_gui::{Activity, Color, View, Result};
+    /// # fn example(activity: &mut Activity, view: &View) -> Result<()> {
     /// // Solid red
-    /// view.set_background_color(activity, 0xFFFF0000u32 as i32)?;
+    /// view.set_background_color(activity, Color::RED)?;
     /// 
     /// // Semi-transparent blue
-    /// view.set_background_color(activity, 0x800000FFu32 as i32)?;
+    /// view.set_background_color(activity, Color::BLUE.with_alpha(0x80))?;
     /// 
-    /// // White
-    /// view.set_background_color(activity, 0xFFFFFFFFu32 as i32)?;
+    /// // From a hex string or CSS name
+    /// view.set_background_color(activity, Color::parse("#FFF3E0")?)?;
+    /// view.set_background_color(activity, Color::parse("steelblue")?)?;
+    /// 
+    /// // Raw ARGB values still work
+    /// view.set_background_color(activity, 0xFFFFFFFFu32)?;
     /// # Ok(())
     /// # }
     /// ```
-    pub fn set_background_color(&self, activity: &mut Activity, color: i32) -> Result<()> {
-        activity.send(&json!({
-            "method": "setBackgroundColor",
-            "params": {
-                "aid": activity.id(),
-                "id": self.id,
-                "color": color
-            }
-        }))?;
+    pub fn set_background_color(&self, activity: &mut Activity, color: impl Into<Color>) -> Result<()> {
+        let aid = activity.id();
+        raw::set_background_color(activity, &raw::SetBackgroundColor {
+            aid,
+            id: self.id,
+            color: color.into().to_i32().into(),
+        })
+    }
+    
+    /// Show or hide this view
+    pub fn set_visibility(&self, activity: &mut Activity, visibility: Visibility) -> Result<()> {
+        let aid = activity.id();
+        raw::set_visibility(activity, &raw::SetVisibility {
+            aid,
+            id: self.id,
+            vis: visibility.as_i32().into(),
+        })
+    }
+    
+    /// Move input focus to this view
+    /// 
+    /// With `force_soft_keyboard`, the soft keyboard is shown as well.
+    pub fn request_focus(&self, activity: &mut Activity, force_soft_keyboard: bool) -> Result<()> {
+        let aid = activity.id();
+        raw::request_focus(activity, &raw::RequestFocus {
+            aid,
+            id: self.id,
+            forcesoft: Some(force_soft_keyboard),
+        })
+    }
+    
+    /// Set whether this view reacts to clicks
+    pub fn set_clickable(&self, activity: &mut Activity, clickable: bool) -> Result<()> {
+        let aid = activity.id();
+        raw::set_clickable(activity, &raw::SetClickable { aid, id: self.id, clickable })
+    }
+    
+    /// Set whether this view can take input focus
+    pub fn set_focusable(&self, activity: &mut Activity, focusable: bool) -> Result<()> {
+        let aid = activity.id();
+        raw::set_focusable(activity, &raw::SetFocusable { aid, id: self.id, focusable })
+    }
+    
+    /// Remove this view and its children from the layout
+    /// 
+    /// The view's ID is invalid afterwards.
+    pub fn delete(&self, activity: &mut Activity) -> Result<()> {
+        let aid = activity.id();
+        raw::delete_view(activity, &raw::DeleteView { aid, id: self.id })
+    }
+    
+    /// Remove all children of this view, which must be a layout
+    pub fn delete_children(&self, activity: &mut Activity) -> Result<()> {
+        let aid = activity.id();
+        raw::delete_children(activity, &raw::DeleteChildren { aid, id: self.id })
+    }
+    
+    /// Enable or disable click events for this view
+    pub fn send_click_event(&self, activity: &mut Activity, send: bool) -> Result<()> {
+        let aid = activity.id();
+        raw::send_click_event(activity, &raw::SendClickEvent { aid, id: self.id, send })
+    }
+    
+    /// Enable or disable long click events for this view
+    pub fn send_long_click_event(&self, activity: &mut Activity, send: bool) -> Result<()> {
+        let aid = activity.id();
+        raw::send_long_click_event(activity, &raw::SendLongClickEvent { aid, id: self.id, send })
+    }
+    
+    /// Enable or disable focus change events for this view
+    pub fn send_focus_change_event(&self, activity: &mut Activity, send: bool) -> Result<()> {
+        let aid = activity.id();
+        raw::send_focus_change_event(activity, &raw::SendFocusChangeEvent {
+            aid,
+            id: self.id,
+            send,
+        })
+    }
+    
+    /// Enable or disable touch events for this view
+    pub fn send_touch_event(&self, activity: &mut Activity, send: bool) -> Result<()> {
+        let aid = activity.id();
+        raw::send_touch_event(activity, &raw::SendTouchEvent { aid, id: self.id, send })
+    }
+}
+
+/// Get the value and `px` flag for `setWidth`/`setHeight`
+pub(crate) fn size_param(activity: &mut Activity, size: Dimension) -> Result<(Size, bool)> {
+    Ok(match size {
+        Dimension::Dp(dp) => (Size::Value(dp.into()), false),
+        Dimension::Px(px) => (Size::Value(px.into()), true),
+        Dimension::Sp(_) => {
+            let px = size
+                .to_px(&activity.display_metrics()?)
+                .ok_or_else(|| GuiError::InvalidOperation(format!("invalid size: {:?}", size)))?;
+            (Size::Value(px.into()), true)
+        }
+        Dimension::MatchParent => (Size::MatchParent, false),
+        Dimension::WrapContent => (Size::WrapContent, false),
+    })
+}
+
+/// Convert to dp for calls that only take dp, such as margins
+pub(crate) fn dp_param(activity: &mut Activity, size: Dimension, what: &str) -> Result<i32> {
+    match size {
+        Dimension::Dp(dp) => Ok(dp),
+        Dimension::Px(_) | Dimension::Sp(_) => size
+            .to_dp(&activity.display_metrics()?)
+            .ok_or_else(|| GuiError::InvalidOperation(format!("invalid {}: {:?}", what, size))),
+        Dimension::MatchParent | Dimension::WrapContent => {
+            Err(GuiError::InvalidOperation(format!("{} can't be {:?}", what, size)))
+        }
+    }
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::testing::MockService;
+
+    #[test]
+    fn visibility_and_focus() -> Result<()> {
+        let (mock, mut activity) = MockService::activity(false)?;
+        let view = View::new(activity.create_edit_text("", None)?.id());
+        view.set_visibility(&mut activity, Visibility::Gone)?;
+        view.set_visibility(&mut activity, Visibility::Visible)?;
+        view.request_focus(&mut activity, true)?;
+        view.set_focusable(&mut activity, false)?;
+
+        let visibility: Vec<_> = mock.calls_to("setVisibility").iter().map(|p| p["vis"].clone()).collect();
+        assert_eq!(visibility, [json!(0), json!(2)]);
+        assert_eq!(mock.calls_to("requestFocus")[0]["forcesoft"], json!(true));
+        assert_eq!(mock.calls_to("setFocusable")[0]["focusable"], json!(false));
+        Ok(())
+    }
+
+    #[test]
+    fn padding_on_all_sides_or_one() -> Result<()> {
+        let (mock, mut activity) = MockService::activity(false)?;
+        let view = View::new(activity.create_linear_layout(None)?.id());
+        view.set_padding(&mut activity, 8)?;
+        view.set_padding_side(&mut activity, Side::Bottom, 4)?;
+
+        let padding = mock.calls_to("setPadding");
+        assert_eq!(padding[0]["padding"], json!(8));
+        assert!(padding[0].get("dir").is_none());
+        assert_eq!(padding[1]["dir"], "bottom");
+        Ok(())
+    }
+
+    #[test]
+    fn layout_params_and_dimensions() -> Result<()> {
+        let (mock, mut activity) = MockService::activity(false)?;
+        let view = View::new(activity.create_button("OK", None)?.id());
+        view.set_linear_layout_params(&mut activity, 2, None)?;
+        view.set_grid_layout_params(&mut activity, 1, 0, 1, 2, "center", "fill")?;
+        mock.set_response("getDimensions", json!([320, 48]));
+
+        let linear = &mock.calls_to("setLinearLayoutParams")[0];
+        assert_eq!(linear["weight"], json!(2));
+        assert!(linear.get("position").is_none());
+        let grid = &mock.calls_to("setGridLayoutParams")[0];
+        assert_eq!((grid["colsize"].clone(), grid["alignmentcol"].clone()), (json!(2), json!("fill")));
+        assert_eq!(view.get_dimensions(&mut activity)?, (320, 48));
+        Ok(())
+    }
+
+    #[test]
+    fn deleting_views_and_children() -> Result<()> {
+        let (mock, mut activity) = MockService::activity(false)?;
+        let layout = View::new(activity.create_linear_layout(None)?.id());
+        let child = View::new(activity.create_space(Some(layout.id()))?.id());
+        child.delete(&mut activity)?;
+        layout.delete_children(&mut activity)?;
+
+        assert_eq!(mock.calls_to("deleteView")[0]["id"], json!(child.id()));
+        assert_eq!(mock.calls_to("deleteChildren")[0]["id"], json!(layout.id()));
+        Ok(())
+    }
+
+    #[test]
+    fn event_switches() -> Result<()> {
+        let (mock, mut activity) = MockService::activity(false)?;
+        let view = View::new(activity.create_image_view(None)?.id());
+        view.send_click_event(&mut activity, true)?;
+        view.send_touch_event(&mut activity, false)?;
+        view.set_background_color(&mut activity, Color::BLACK)?;
+
+        assert_eq!(mock.calls_to("sendClickEvent")[0]["send"], json!(true));
+        assert_eq!(mock.calls_to("sendTouchEvent")[0]["send"], json!(false));
+        assert_eq!(mock.calls_to("setBackgroundColor")[0]["color"], json!(Color::BLACK.to_i32()));
         Ok(())
     }
 }
@@ -0,0 +1,190 @@
+//! Traits shared by all components
+//!
+//! Every component implements [`Widget`], so generic code can work on any
+//! of them and different components can be stored together as
+//! `Box<dyn Widget>`. Use [`downcast_ref`](trait.Widget.html#method.downcast_ref)
+//! to get the concrete type back.
+//!
+//! Components that show text (`TextView`, `Button`, `EditText`, `Checkbox`,
+//! `Switch`, `RadioButton` and `ToggleButton`) also implement
+//! [`TextWidget`], and the ones that can be checked implement
+//! [`CheckableWidget`].
+//!
+//! ## Example
+//!
+//! ```rust,no_run
+//! use termux_gui::{Activity, Button, Result, TextWidget, Visibility, Widget};
+//!
+//! fn main() -> Result<()> {
+//!     let mut activity = Activity::new(false)?;
+//!     let layout = activity.create_linear_layout(None)?;
+//!
+//!     let widgets: Vec<Box<dyn Widget>> = vec![
+//!         Box::new(activity.create_text_view("Name", Some(layout.id()))?),
+//!         Box::new(activity.create_edit_text("", Some(layout.id()))?),
+//!         Box::new(activity.create_button("OK", Some(layout.id()))?),
+//!     ];
+//!
+//!     // Hide them all at once
+//!     for widget in &widgets {
+//!         widget.view().set_visibility(&mut activity, Visibility::Gone)?;
+//!     }
+//!
+//!     // Get a concrete type back
+//!     if let Some(button) = widgets[2].downcast_ref::<Button>() {
+//!         button.set_text_size(&mut activity, 20)?;
+//!     }
+//!     Ok(())
+//! }
+//! ```
+
+use std::any::Any;
+use crate::activity::Activity;
+use crate::color::Color;
+use crate::error::Result;
+use crate::raw;
+use crate::view::View;
+
+/// A component created in an Activity
+pub trait Widget: Any {
+    /// Get the underlying View
+    fn view(&self) -> &View;
+
+    /// Get the ID of the Activity this widget belongs to
+    fn aid(&self) -> i64;
+
+    /// Get this widget as `Any`, for downcasting
+    fn as_any(&self) -> &dyn Any;
+
+    /// Get the view ID
+    fn id(&self) -> i64 {
+        self.view().id()
+    }
+}
+
+impl dyn Widget {
+    /// Check whether this widget is a `T`
+    pub fn is<T: Widget>(&self) -> bool {
+        self.as_any().is::<T>()
+    }
+
+    /// Get this widget as a `T`, if it is one
+    pub fn downcast_ref<T: Widget>(&self) -> Option<&T> {
+        self.as_any().downcast_ref::<T>()
+    }
+}
+
+/// A component that shows text
+pub trait TextWidget: Widget {
+    /// Set the text content
+    fn set_text(&self, activity: &mut Activity, text: &str) -> Result<()> {
+        raw::set_text(activity, &raw::SetText {
+            aid: self.aid(),
+            id: self.id(),
+            text: text.to_string(),
+        })
+    }
+
+    /// Get the current text content
+    fn get_text(&self, activity: &mut Activity) -> Result<String> {
+        raw::get_text(activity, &raw::GetText { aid: self.aid(), id: self.id() })
+    }
+
+    /// Set text size in sp
+    fn set_text_size(&self, activity: &mut Activity, size: i32) -> Result<()> {
+        raw::set_text_size(activity, &raw::SetTextSize {
+            aid: self.aid(),
+            id: self.id(),
+            size: size.into(),
+        })
+    }
+
+    /// Set text color
+    ///
+    /// Accepts a [`Color`] or an ARGB integer.
+    fn set_text_color(&self, activity: &mut Activity, color: impl Into<Color>) -> Result<()>
+    where
+        Self: Sized,
+    {
+        raw::set_text_color(activity, &raw::SetTextColor {
+            aid: self.aid(),
+            id: self.id(),
+            color: color.into().to_i32().into(),
+        })
+    }
+}
+
+/// A component with a checked state
+pub trait CheckableWidget: Widget {
+    /// Set checked state
+    fn set_checked(&self, activity: &mut Activity, checked: bool) -> Result<()> {
+        raw::set_checked(activity, &raw::SetChecked { aid: self.aid(), id: self.id(), checked })
+    }
+}
+
+/// Implement [`Widget`] for components with `view` and `aid` fields
+macro_rules! impl_widget {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl $crate::widget::Widget for $ty {
+                fn view(&self) -> &$crate::view::View {
+                    &self.view
+                }
+
+                fn aid(&self) -> i64 {
+                    self.aid
+                }
+
+                fn as_any(&self) -> &dyn ::std::any::Any {
+                    self
+                }
+            }
+        )*
+    };
+}
+
+pub(crate) use impl_widget;
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::components::{Button, Checkbox, Switch, TextView};
+    use crate::testing::MockService;
+
+    #[test]
+    fn heterogeneous_widgets_can_be_downcast() -> Result<()> {
+        let (_mock, mut activity) = MockService::activity(false)?;
+        let widgets: Vec<Box<dyn Widget>> = vec![
+            Box::new(activity.create_button("OK", None)?),
+            Box::new(activity.create_text_view("Label", None)?),
+        ];
+
+        assert!(widgets[0].is::<Button>());
+        assert!(!widgets[0].is::<TextView>());
+        let label = widgets[1].downcast_ref::<TextView>().unwrap();
+        assert_eq!(label.id(), widgets[1].id());
+        assert!(widgets.iter().all(|widget| widget.aid() == activity.id()));
+        Ok(())
+    }
+
+    #[test]
+    fn text_and_checkable_traits_address_the_widget() -> Result<()> {
+        let (mock, mut activity) = MockService::activity(false)?;
+        let checkbox = activity.create_checkbox("Agree", None)?;
+        let switch = activity.create_switch("On", None)?;
+
+        fn reset<W: TextWidget + CheckableWidget>(widget: &W, activity: &mut Activity) -> Result<()> {
+            widget.set_text(activity, "")?;
+            widget.set_checked(activity, false)
+        }
+        reset::<Checkbox>(&checkbox, &mut activity)?;
+        reset::<Switch>(&switch, &mut activity)?;
+
+        let checked: Vec<_> = mock.calls_to("setChecked").iter().map(|p| p["id"].clone()).collect();
+        assert_eq!(checked, [json!(checkbox.id()), json!(switch.id())]);
+        assert_eq!(TextWidget::get_text(&switch, &mut activity)?, "");
+        Ok(())
+    }
+}